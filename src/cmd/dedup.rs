@@ -23,6 +23,10 @@ dedup options:
                                Note that the outputs will remain at the full width
                                of the CSV.
                                See 'qsv select --help' for the format details.
+    -k, --keys <spec>          Compare rows on a list of sort keys, each with its own
+                               type, direction & NULL placement, using the same key
+                               spec as 'qsv sort --keys'. Overrides --select & --numeric.
+                               See 'qsv sort --help' for the format details.
     -N, --numeric              Compare according to string numerical value
     -i, --ignore-case          Compare strings disregarding case.
     --sorted                   The input is already sorted. Do not load the CSV into
@@ -59,7 +63,7 @@ use serde::Deserialize;
 use simdutf8::basic::from_utf8;
 
 use crate::{
-    cmd::sort::{iter_cmp, iter_cmp_num},
+    cmd::sort::{iter_cmp, iter_cmp_num, SortKeys},
     config::{Config, Delimiter},
     select::SelectColumns,
     util, CliResult,
@@ -68,6 +72,7 @@ use crate::{
 struct Args {
     arg_input:           Option<String>,
     flag_select:         SelectColumns,
+    flag_keys:           Option<String>,
     flag_numeric:        bool,
     flag_ignore_case:    bool,
     flag_sorted:         bool,
@@ -85,19 +90,12 @@ enum ComparisonMode {
     Numeric,
     IgnoreCase,
     Normal,
+    Keys(SortKeys),
 }
 
 pub fn run(argv: &[&str]) -> CliResult<()> {
     let args: Args = util::get_args(USAGE, argv)?;
 
-    let compare_mode = if args.flag_numeric {
-        ComparisonMode::Numeric
-    } else if args.flag_ignore_case {
-        ComparisonMode::IgnoreCase
-    } else {
-        ComparisonMode::Normal
-    };
-
     let rconfig = Config::new(&args.arg_input)
         .delimiter(args.flag_delimiter)
         .no_headers(args.flag_no_headers)
@@ -114,6 +112,22 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
     }
     let sel = rconfig.selection(&headers)?;
 
+    let compare_mode = if let Some(spec) = &args.flag_keys {
+        ComparisonMode::Keys(SortKeys::new(
+            spec,
+            &headers,
+            rconfig.no_headers,
+            args.flag_ignore_case,
+            rconfig.get_dmy_preference(),
+        )?)
+    } else if args.flag_numeric {
+        ComparisonMode::Numeric
+    } else if args.flag_ignore_case {
+        ComparisonMode::IgnoreCase
+    } else {
+        ComparisonMode::Normal
+    };
+
     rconfig.write_headers(&mut rdr, &mut wtr)?;
     let mut dupe_count = 0_usize;
 
@@ -130,10 +144,11 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
             };
             let a = sel.select(&record);
             let b = sel.select(&next_record);
-            let comparison = match compare_mode {
+            let comparison = match &compare_mode {
                 ComparisonMode::Normal => iter_cmp(a, b),
                 ComparisonMode::Numeric => iter_cmp_num(a, b),
                 ComparisonMode::IgnoreCase => iter_cmp_ignore_case(a, b),
+                ComparisonMode::Keys(sort_keys) => sort_keys.compare_records(&record, &next_record),
             };
             match comparison {
                 cmp::Ordering::Equal => {
@@ -163,7 +178,7 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
         util::njobs(args.flag_jobs);
 
         let mut all = rdr.byte_records().collect::<Result<Vec<_>, _>>()?;
-        match compare_mode {
+        match &compare_mode {
             ComparisonMode::Normal => {
                 all.par_sort_by(|r1, r2| {
                     let a = sel.select(r1);
@@ -185,13 +200,22 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
                     iter_cmp_ignore_case(a, b)
                 });
             },
+            ComparisonMode::Keys(sort_keys) => {
+                // parse the sort keys of each record once, instead of on every comparison
+                let mut keyed: Vec<_> = all
+                    .drain(..)
+                    .map(|r| (sort_keys.key_values(&r), r))
+                    .collect();
+                keyed.par_sort_by(|(k1, _), (k2, _)| sort_keys.compare(k1, k2));
+                all.extend(keyed.into_iter().map(|(_, r)| r));
+            },
         }
 
         for (current, current_record) in all.iter().enumerate() {
             let a = sel.select(current_record);
             if let Some(next_record) = all.get(current + 1) {
                 let b = sel.select(next_record);
-                match compare_mode {
+                match &compare_mode {
                     ComparisonMode::Normal => {
                         if iter_cmp(a, b) == cmp::Ordering::Equal {
                             dupe_count += 1;
@@ -222,6 +246,18 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
                             wtr.write_byte_record(current_record)?;
                         }
                     },
+                    ComparisonMode::Keys(sort_keys) => {
+                        if sort_keys.compare_records(current_record, next_record)
+                            == cmp::Ordering::Equal
+                        {
+                            dupe_count += 1;
+                            if dupes_output {
+                                dupewtr.write_byte_record(current_record)?;
+                            }
+                        } else {
+                            wtr.write_byte_record(current_record)?;
+                        }
+                    },
                 }
             } else {
                 wtr.write_byte_record(current_record)?;
@@ -3,6 +3,27 @@ Find the difference between two CSVs with ludicrous speed.
 
 Note that diff does not support stdin. A file path is required for both arguments.
 
+By default, the diff result is a CSV with a "diffresult" column, where added rows
+are marked with "+" & deleted rows with "-". A modified row (a row with the same key
+in both CSVs, but with different values) is returned as a deleted row followed by
+an added row.
+
+To review the changes cell by cell, use --format to return a change report instead.
+Modified rows are paired by key, and only the columns that changed are listed, with
+their old & new values:
+
+  json          A JSON object with a "summary" of the number of added, deleted &
+                modified rows & the number of changes per column, and the list of
+                "changes". Each change has a "change" type (added, deleted or modified)
+                & the "key" of the row. Added & deleted changes have the whole "row",
+                & modified changes have the old & new values of the changed "columns".
+  jsonl         The changes, as one JSON object per line.
+  side-by-side  A human-readable view, with the old & new values side by side.
+                Old values are red & new values are green, unless the output is
+                not a terminal or the NO_COLOR environment variable is set.
+  summary       A CSV with the number of added, deleted & modified rows, and the
+                number of modified rows per column.
+
 Examples:
 
 Find the difference between two CSVs:
@@ -29,6 +50,12 @@ Find the difference between two CSVs, but only for the first two columns and
 sort the result by the first and second column:
     qsv diff -k 0,1 --sort-columns 0,1 left.csv right.csv
 
+Review the changes of a data release cell by cell, as JSON:
+    qsv diff --format json last-release.csv this-release.csv
+
+Only show the number of changes per column:
+    qsv diff --format summary last-release.csv this-release.csv
+
 Find the difference between two CSVs, but do not output headers in the result:
     qsv diff --no-headers-output left.csv right.csv
 
@@ -70,6 +97,9 @@ diff options:
                                 but have different content) will always be kept together
                                 in the sorted diff result and so won't be sorted
                                 independently from each other.
+    --format <arg>              The output format - csv, json, jsonl, side-by-side
+                                or summary. See above for details.
+                                [default: csv]
     -j, --jobs <arg>            The number of jobs to run in parallel.
                                 When not set, the number of jobs is set to the number
                                 of CPUs detected.
@@ -79,13 +109,19 @@ Common options:
     -o, --output <file>         Write output to <file> instead of stdout.
 "#;
 
-use std::io::{self, Write};
+use std::{
+    env, fmt,
+    io::{self, IsTerminal, Write},
+    str::FromStr,
+};
 
 use csv_diff::{
     csv_diff::CsvByteDiffBuilder, csv_headers::Headers, diff_result::DiffByteRecords,
     diff_row::DiffByteRecord,
 };
 use serde::Deserialize;
+use serde_json::{json, Map, Value};
+use strum_macros::EnumString;
 
 use super::rename::rename_headers_all_generic;
 use crate::{
@@ -108,11 +144,30 @@ struct Args {
     flag_delimiter_output:  Option<Delimiter>,
     flag_key:               Option<String>,
     flag_sort_columns:      Option<String>,
+    flag_format:            String,
+}
+
+#[derive(Clone, Copy, EnumString, PartialEq)]
+#[strum(ascii_case_insensitive)]
+enum OutputFormat {
+    Csv,
+    Json,
+    Jsonl,
+    #[strum(serialize = "side-by-side")]
+    SideBySide,
+    Summary,
 }
 
 pub fn run(argv: &[&str]) -> CliResult<()> {
     let args: Args = util::get_args(USAGE, argv)?;
 
+    let Ok(format) = OutputFormat::from_str(&args.flag_format) else {
+        return fail_incorrectusage_clierror!(
+            "Invalid --format `{}`. It must be one of csv, json, jsonl, side-by-side or summary.",
+            args.flag_format
+        );
+    };
+
     let rconfig_left = Config::new(&args.arg_input_left)
         .delimiter(args.flag_delimiter_left)
         .no_headers(args.flag_no_headers_left);
@@ -146,9 +201,7 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
         })
         .transpose()?;
 
-    let wtr = Config::new(&args.flag_output)
-        .delimiter(args.flag_delimiter_output)
-        .writer()?;
+    let wconfig = Config::new(&args.flag_output).delimiter(args.flag_delimiter_output);
     let csv_rdr_left = rconfig_left.reader()?;
     let csv_rdr_right = rconfig_right.reader()?;
 
@@ -156,7 +209,7 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
     util::njobs(args.flag_jobs);
 
     let Ok(csv_diff) = CsvByteDiffBuilder::new()
-        .primary_key_columns(primary_key_cols)
+        .primary_key_columns(primary_key_cols.clone())
         .build()
     else {
         return fail_clierror!("Cannot instantiate diff");
@@ -177,8 +230,370 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
         },
     }
 
-    let mut csv_diff_writer = CsvDiffWriter::new(wtr, args.flag_no_headers_output);
-    Ok(csv_diff_writer.write_diff_byte_records(diff_byte_records)?)
+    match format {
+        OutputFormat::Csv => {
+            let mut csv_diff_writer =
+                CsvDiffWriter::new(wconfig.writer()?, args.flag_no_headers_output);
+            csv_diff_writer.write_diff_byte_records(diff_byte_records)?;
+        },
+        OutputFormat::Json => {
+            let report = ChangeReport::new(diff_byte_records, &primary_key_cols);
+            let mut wtr = wconfig.io_writer()?;
+            serde_json::to_writer_pretty(&mut wtr, &report.to_json())?;
+            writeln!(wtr)?;
+            wtr.flush()?;
+        },
+        OutputFormat::Jsonl => {
+            let report = ChangeReport::new(diff_byte_records, &primary_key_cols);
+            let mut wtr = wconfig.io_writer()?;
+            for change in &report.changes {
+                serde_json::to_writer(&mut wtr, &report.change_to_json(change))?;
+                writeln!(wtr)?;
+            }
+            wtr.flush()?;
+        },
+        OutputFormat::SideBySide => {
+            let report = ChangeReport::new(diff_byte_records, &primary_key_cols);
+            let colors = args.flag_output.is_none()
+                && io::stdout().is_terminal()
+                && env::var_os("NO_COLOR").is_none();
+            let mut wtr = wconfig.io_writer()?;
+            report.write_side_by_side(&mut wtr, colors)?;
+            wtr.flush()?;
+        },
+        OutputFormat::Summary => {
+            let report = ChangeReport::new(diff_byte_records, &primary_key_cols);
+            let mut wtr = wconfig.writer()?;
+            report.write_summary(&mut wtr, args.flag_no_headers_output)?;
+            wtr.flush()?;
+        },
+    }
+    Ok(())
+}
+
+// the ANSI SGR codes of the side-by-side colors
+const BOLD: &str = "1";
+const RED: &str = "31";
+const GREEN: &str = "32";
+const YELLOW: &str = "33";
+
+/// wrap `text` in the ANSI escape codes of `sgr` if `colors` is set
+fn paint(text: impl fmt::Display, sgr: &str, colors: bool) -> String {
+    if colors {
+        format!("\x1b[{sgr}m{text}\x1b[0m")
+    } else {
+        text.to_string()
+    }
+}
+
+/// a change to a row, identified by the values of its key columns
+enum Change {
+    Added {
+        key: Vec<String>,
+        row: Vec<String>,
+    },
+    Deleted {
+        key: Vec<String>,
+        row: Vec<String>,
+    },
+    /// the changed columns, with their indices & old & new values
+    Modified {
+        key:     Vec<String>,
+        columns: Vec<(usize, String, String)>,
+    },
+}
+
+/// the changes between two CSVs, with modified rows paired by key
+struct ChangeReport {
+    headers:     Vec<String>,
+    key_columns: Vec<usize>,
+    changes:     Vec<Change>,
+}
+
+impl ChangeReport {
+    fn new(diff_byte_records: DiffByteRecords, key_columns: &[usize]) -> Self {
+        let num_columns = diff_byte_records.num_columns().unwrap_or_default();
+        let headers_record = match (
+            diff_byte_records.headers().headers_left(),
+            diff_byte_records.headers().headers_right(),
+        ) {
+            (Some(bh), _) | (None, Some(bh)) => Some(bh.clone()),
+            (None, None) => None,
+        };
+        let headers: Vec<String> = match headers_record {
+            Some(bh) => bh
+                .iter()
+                .map(|h| String::from_utf8_lossy(h).to_string())
+                .collect(),
+            None => (1..=num_columns).map(|i| format!("_col_{i}")).collect(),
+        };
+
+        let to_strings = |record: &csv::ByteRecord| -> Vec<String> {
+            record
+                .iter()
+                .map(|field| String::from_utf8_lossy(field).to_string())
+                .collect()
+        };
+        let key_of = |row: &[String]| -> Vec<String> {
+            key_columns
+                .iter()
+                .map(|&i| row.get(i).cloned().unwrap_or_default())
+                .collect()
+        };
+
+        let mut changes = Vec::new();
+        for dbr in diff_byte_records {
+            changes.push(match dbr {
+                DiffByteRecord::Add(add) => {
+                    let row = to_strings(add.byte_record());
+                    Change::Added {
+                        key: key_of(&row),
+                        row,
+                    }
+                },
+                DiffByteRecord::Delete(del) => {
+                    let row = to_strings(del.byte_record());
+                    Change::Deleted {
+                        key: key_of(&row),
+                        row,
+                    }
+                },
+                DiffByteRecord::Modify { delete, add, .. } => {
+                    let old = to_strings(delete.byte_record());
+                    let new = to_strings(add.byte_record());
+                    let columns = (0..old.len().max(new.len()))
+                        .filter_map(|i| {
+                            let old_value = old.get(i).cloned().unwrap_or_default();
+                            let new_value = new.get(i).cloned().unwrap_or_default();
+                            (old_value != new_value).then_some((i, old_value, new_value))
+                        })
+                        .collect();
+                    Change::Modified {
+                        key: key_of(&old),
+                        columns,
+                    }
+                },
+            });
+        }
+
+        Self {
+            headers,
+            key_columns: key_columns.to_vec(),
+            changes,
+        }
+    }
+
+    fn header(&self, idx: usize) -> String {
+        self.headers
+            .get(idx)
+            .cloned()
+            .unwrap_or_else(|| format!("_col_{}", idx + 1))
+    }
+
+    /// the number of added, deleted & modified rows
+    fn row_counts(&self) -> (u64, u64, u64) {
+        let (mut added, mut deleted, mut modified) = (0, 0, 0);
+        for change in &self.changes {
+            match change {
+                Change::Added { .. } => added += 1,
+                Change::Deleted { .. } => deleted += 1,
+                Change::Modified { .. } => modified += 1,
+            }
+        }
+        (added, deleted, modified)
+    }
+
+    /// the number of modified rows per column, in column order
+    fn column_counts(&self) -> Vec<(String, u64)> {
+        let mut counts = vec![0_u64; self.headers.len()];
+        for change in &self.changes {
+            if let Change::Modified { columns, .. } = change {
+                for (idx, ..) in columns {
+                    if *idx >= counts.len() {
+                        counts.resize(idx + 1, 0);
+                    }
+                    counts[*idx] += 1;
+                }
+            }
+        }
+        counts
+            .into_iter()
+            .enumerate()
+            .filter(|(_, count)| *count > 0)
+            .map(|(idx, count)| (self.header(idx), count))
+            .collect()
+    }
+
+    fn key_to_json(&self, key: &[String]) -> Value {
+        let map: Map<String, Value> = self
+            .key_columns
+            .iter()
+            .zip(key)
+            .map(|(&idx, value)| (self.header(idx), json!(value)))
+            .collect();
+        Value::Object(map)
+    }
+
+    fn row_to_json(&self, row: &[String]) -> Value {
+        let map: Map<String, Value> = row
+            .iter()
+            .enumerate()
+            .map(|(idx, value)| (self.header(idx), json!(value)))
+            .collect();
+        Value::Object(map)
+    }
+
+    fn change_to_json(&self, change: &Change) -> Value {
+        match change {
+            Change::Added { key, row } => json!({
+                "change": "added",
+                "key": self.key_to_json(key),
+                "row": self.row_to_json(row),
+            }),
+            Change::Deleted { key, row } => json!({
+                "change": "deleted",
+                "key": self.key_to_json(key),
+                "row": self.row_to_json(row),
+            }),
+            Change::Modified { key, columns } => {
+                let columns: Map<String, Value> = columns
+                    .iter()
+                    .map(|(idx, old, new)| (self.header(*idx), json!({"old": old, "new": new})))
+                    .collect();
+                json!({
+                    "change": "modified",
+                    "key": self.key_to_json(key),
+                    "columns": columns,
+                })
+            },
+        }
+    }
+
+    fn to_json(&self) -> Value {
+        let (added, deleted, modified) = self.row_counts();
+        json!({
+            "summary": {
+                "added": added,
+                "deleted": deleted,
+                "modified": modified,
+                "columns": self
+                    .column_counts()
+                    .into_iter()
+                    .map(|(column, count)| (column, json!(count)))
+                    .collect::<Map<String, Value>>(),
+            },
+            "changes": self
+                .changes
+                .iter()
+                .map(|change| self.change_to_json(change))
+                .collect::<Vec<_>>(),
+        })
+    }
+
+    fn write_summary<W: Write>(
+        &self,
+        wtr: &mut csv::Writer<W>,
+        no_headers: bool,
+    ) -> csv::Result<()> {
+        if !no_headers {
+            wtr.write_record(["change", "column", "count"])?;
+        }
+        let (added, deleted, modified) = self.row_counts();
+        wtr.write_record(["added", "", &added.to_string()])?;
+        wtr.write_record(["deleted", "", &deleted.to_string()])?;
+        wtr.write_record(["modified", "", &modified.to_string()])?;
+        for (column, count) in self.column_counts() {
+            wtr.write_record(["modified", &column, &count.to_string()])?;
+        }
+        Ok(())
+    }
+
+    /// write one line per changed cell, with the old & new values side by side.
+    /// Added & deleted rows are written on one line, with their values comma-separated.
+    fn write_side_by_side<W: Write>(&self, wtr: &mut W, colors: bool) -> io::Result<()> {
+        // sign, key, column, old & new value of each line
+        let mut lines: Vec<(char, String, String, String, String)> = Vec::new();
+        for change in &self.changes {
+            match change {
+                Change::Added { key, row } => {
+                    lines.push((
+                        '+',
+                        self.key_text(key),
+                        String::new(),
+                        String::new(),
+                        row.join(", "),
+                    ));
+                },
+                Change::Deleted { key, row } => {
+                    lines.push((
+                        '-',
+                        self.key_text(key),
+                        String::new(),
+                        row.join(", "),
+                        String::new(),
+                    ));
+                },
+                Change::Modified { key, columns } => {
+                    for (i, (idx, old, new)) in columns.iter().enumerate() {
+                        // only show the key on the first line of a modified row
+                        let key_text = if i == 0 {
+                            self.key_text(key)
+                        } else {
+                            String::new()
+                        };
+                        lines.push(('~', key_text, self.header(*idx), old.clone(), new.clone()));
+                    }
+                },
+            }
+        }
+
+        let width = |text: &str| text.chars().count();
+        let key_width = lines.iter().map(|l| width(&l.1)).max().unwrap_or(0).max(3);
+        let column_width = lines.iter().map(|l| width(&l.2)).max().unwrap_or(0).max(6);
+        let old_width = lines.iter().map(|l| width(&l.3)).max().unwrap_or(0).max(3);
+
+        writeln!(
+            wtr,
+            "  {}",
+            paint(
+                format!(
+                    "{:key_width$}  {:column_width$}  {:old_width$} | NEW",
+                    "KEY", "COLUMN", "OLD"
+                ),
+                BOLD,
+                colors
+            )
+        )?;
+        for (sign, key, column, old, new) in lines {
+            let sign_color = match sign {
+                '+' => GREEN,
+                '-' => RED,
+                _ => YELLOW,
+            };
+            // pad before painting, as the escape codes would count towards the width
+            write!(
+                wtr,
+                "{} {key:key_width$}  {column:column_width$}  {} |",
+                paint(sign, sign_color, colors),
+                paint(format!("{old:old_width$}"), RED, colors)
+            )?;
+            if new.is_empty() {
+                writeln!(wtr)?;
+            } else {
+                writeln!(wtr, " {}", paint(new, GREEN, colors))?;
+            }
+        }
+        Ok(())
+    }
+
+    fn key_text(&self, key: &[String]) -> String {
+        self.key_columns
+            .iter()
+            .zip(key)
+            .map(|(&idx, value)| format!("{}={value}", self.header(idx)))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
 }
 
 struct CsvDiffWriter<W: Write> {
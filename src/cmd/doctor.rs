@@ -0,0 +1,630 @@
+static USAGE: &str = r#"
+Diagnoses common data quality problems in a CSV, and suggests the qsv command to fix each one.
+
+It's meant to be the first thing you run on a new file. In a single pass, doctor sniffs
+the file's dialect, checks its RFC 4180 structure and profiles each column, flagging:
+  preamble             lines before the header row
+  delimiter            a delimiter other than the one implied by the file extension
+  encoding             records with invalid UTF-8 sequences
+  ragged_rows          records with a different number of fields than the header
+  bad_quotes           records with stray or unclosed quotes
+  duplicate_rows       records that are exact duplicates of an earlier record
+  header               header names that are empty, duplicated or not "safe"
+                       (see `qsv safenames --help`)
+  empty_column         columns without any values
+  constant_column      columns with the same value in every record
+  mixed_types          columns with a mix of numbers, dates & strings
+  thousands_separator  numbers stored with thousands separators (e.g. 1,234.50)
+  date_formats         date columns that use more than one date format
+  whitespace           values with leading or trailing whitespace
+  placeholder_nulls    placeholder values standing in for a missing value (e.g. N/A, -, NULL)
+
+The report is a CSV with the following columns:
+  field          the column's name (empty for file-level findings)
+  issue          the issue found (see above)
+  count          the number of affected records or values
+  example        an example of an affected value (the line number for file-level findings)
+  suggested_fix  the qsv command to fix the issue, or to list the affected records
+
+The suggested fixes are starting points - review them before running them, as doctor
+only sees the symptoms. For example, a 9999 placeholder may well be a legitimate value.
+
+If any issue is found, doctor exits with exit code 1 after writing the report.
+Otherwise, only the report headers are written.
+
+Examples:
+  $ qsv doctor data.csv
+  $ qsv doctor --placeholders 'N/A,-,NULL,unknown' data.csv --output data.doctor.csv
+
+For more examples, see https://github.com/jqnatividad/qsv/blob/master/tests/test_doctor.rs.
+
+Usage:
+    qsv doctor [options] <input>
+    qsv doctor --help
+
+doctor options:
+    --placeholders <list>  A comma-separated list of placeholder null values.
+                           Values are compared case-insensitively, after trimming.
+                           [default: N/A,NA,#N/A,-,NULL,None,NaN,9999,-9999]
+
+Common options:
+    -h, --help             Display this message
+    -o, --output <file>    Write the report to <file> instead of stdout.
+    -n, --no-headers       When set, the first row will not be interpreted
+                           as headers. Header names are not checked.
+    -d, --delimiter <arg>  The field delimiter for reading CSV data.
+                           Must be a single character. When not set, the delimiter
+                           & the number of preamble lines are sniffed.
+"#;
+
+use std::{io::BufReader, path::Path, sync::OnceLock};
+
+use ahash::AHashSet;
+use qsv_dateparser::parse_with_preference;
+use qsv_sniffer::{SampleSize, Sniffer};
+use regex::Regex;
+use serde::Deserialize;
+
+use crate::{
+    cmd::validate::{invalid_utf8_ranges, RecordScanner},
+    config::{Config, Delimiter},
+    util, CliResult,
+};
+
+// number of rows for qsv_sniffer to sample
+const SNIFFER_SAMPLE: usize = 100;
+
+const NUMBER_PATTERN: &str = r"^[+-]?(\d{1,3}(,\d{3})+(\.\d+)?|\d+(\.\d*)?|\.\d+)([eE][+-]?\d+)?$";
+static NUMBER_REGEX: OnceLock<Regex> = OnceLock::new();
+
+#[derive(Deserialize)]
+struct Args {
+    arg_input:         String,
+    flag_placeholders: String,
+    flag_output:       Option<String>,
+    flag_no_headers:   bool,
+    flag_delimiter:    Option<Delimiter>,
+}
+
+struct Finding {
+    field:   String,
+    issue:   &'static str,
+    count:   u64,
+    example: String,
+    fix:     String,
+}
+
+/// a file-level issue - its count & the line of its first occurrence
+#[derive(Default)]
+struct RecordIssue {
+    count:      u64,
+    first_line: u64,
+}
+
+impl RecordIssue {
+    fn add(&mut self, line: u64) {
+        if self.count == 0 {
+            self.first_line = line;
+        }
+        self.count += 1;
+    }
+}
+
+#[derive(Clone, Copy)]
+enum ValueClass {
+    Number = 0,
+    Date   = 1,
+    String = 2,
+}
+
+const CLASS_NAMES: [&str; 3] = ["number", "date", "string"];
+
+/// the shape of a date, e.g. 9999-99-99 for 2024-01-31, with the regex that matches it
+struct DateShape {
+    shape:   String,
+    regex:   String,
+    count:   u64,
+    example: String,
+}
+
+/// what doctor observed about the values of a column
+#[derive(Default)]
+struct ColumnProfile {
+    name:               String,
+    selector:           String,
+    values:             u64,
+    first_value:        Option<String>,
+    constant:           bool,
+    class_counts:       [u64; 3],
+    class_examples:     [Option<String>; 3],
+    thousands:          u64,
+    thousands_example:  Option<String>,
+    padded:             u64,
+    padded_example:     Option<String>,
+    placeholders:       u64,
+    placeholders_found: Vec<String>,
+    date_shapes:        Vec<DateShape>,
+}
+
+impl ColumnProfile {
+    fn add(&mut self, value: &str, placeholders: &[String], prefer_dmy: bool) {
+        self.values += 1;
+        match self.first_value {
+            None => {
+                self.first_value = Some(value.to_string());
+                self.constant = true;
+            },
+            Some(ref first_value) if self.constant && first_value != value => {
+                self.constant = false;
+            },
+            _ => {},
+        }
+
+        let trimmed = value.trim();
+        if trimmed.is_empty() {
+            return;
+        }
+        if trimmed.len() != value.len() {
+            self.padded += 1;
+            self.padded_example.get_or_insert_with(|| value.to_string());
+        }
+        if placeholders.contains(&trimmed.to_uppercase()) {
+            self.placeholders += 1;
+            if !self.placeholders_found.iter().any(|p| p == trimmed) {
+                self.placeholders_found.push(trimmed.to_string());
+            }
+            return;
+        }
+
+        let has_digit = trimmed.bytes().any(|b| b.is_ascii_digit());
+        let class = if has_digit
+            && NUMBER_REGEX
+                .get_or_init(|| Regex::new(NUMBER_PATTERN).unwrap())
+                .is_match(trimmed)
+        {
+            if trimmed.contains(',') {
+                self.thousands += 1;
+                self.thousands_example
+                    .get_or_insert_with(|| trimmed.to_string());
+            }
+            ValueClass::Number
+        } else if has_digit && parse_with_preference(trimmed, prefer_dmy).is_ok() {
+            let (shape, regex) = date_shape(trimmed);
+            if let Some(date_shape) = self.date_shapes.iter_mut().find(|ds| ds.shape == shape) {
+                date_shape.count += 1;
+            } else {
+                self.date_shapes.push(DateShape {
+                    shape,
+                    regex,
+                    count: 1,
+                    example: trimmed.to_string(),
+                });
+            }
+            ValueClass::Date
+        } else {
+            ValueClass::String
+        };
+        self.class_counts[class as usize] += 1;
+        self.class_examples[class as usize].get_or_insert_with(|| trimmed.to_string());
+    }
+
+    /// the column's findings, in report order
+    fn findings(&mut self, input: &str) -> Vec<Finding> {
+        let mut findings = Vec::new();
+        let mut add_finding = |issue: &'static str, count: u64, example: &str, fix: String| {
+            findings.push(Finding {
+                field: self.name.clone(),
+                issue,
+                count,
+                example: example.to_string(),
+                fix,
+            });
+        };
+
+        if self.constant && self.values > 1 {
+            let value = self.first_value.as_deref().unwrap_or_default();
+            add_finding(
+                if value.is_empty() {
+                    "empty_column"
+                } else {
+                    "constant_column"
+                },
+                self.values,
+                value,
+                format!(
+                    "qsv select {} {input}",
+                    shell_quote(&format!("!{}", self.selector))
+                ),
+            );
+            if value.is_empty() {
+                return findings;
+            }
+        }
+
+        let dominant = (0..3)
+            .max_by_key(|&c| (self.class_counts[c], 3 - c))
+            .unwrap();
+        let minority_count: u64 = (0..3)
+            .filter(|&c| c != dominant)
+            .map(|c| self.class_counts[c])
+            .sum();
+        if minority_count > 0 {
+            // the example is from the most common minority class
+            let minority = (0..3)
+                .filter(|&c| c != dominant)
+                .max_by_key(|&c| (self.class_counts[c], 3 - c))
+                .unwrap();
+            let fix = if dominant == ValueClass::String as usize {
+                // list the records with a number or a date
+                let mut patterns = Vec::new();
+                if self.class_counts[ValueClass::Number as usize] > 0 {
+                    patterns.push(NUMBER_PATTERN.to_string());
+                }
+                if self.class_counts[ValueClass::Date as usize] > 0 {
+                    patterns.push(self.date_pattern());
+                }
+                format!(
+                    "qsv search --select {} {} {input}",
+                    shell_quote(&self.selector),
+                    shell_quote(&patterns.join("|"))
+                )
+            } else {
+                // list the records that are neither empty nor of the dominant class
+                let pattern = if dominant == ValueClass::Number as usize {
+                    NUMBER_PATTERN.to_string()
+                } else {
+                    self.date_pattern()
+                };
+                format!(
+                    "qsv search --select {} --invert-match {} {input}",
+                    shell_quote(&self.selector),
+                    shell_quote(&format!(r"^\s*$|{pattern}"))
+                )
+            };
+            add_finding(
+                "mixed_types",
+                minority_count,
+                &format!(
+                    "{} ({} in a {} column)",
+                    self.class_examples[minority].as_deref().unwrap_or_default(),
+                    CLASS_NAMES[minority],
+                    CLASS_NAMES[dominant]
+                ),
+                fix,
+            );
+        }
+
+        if self.thousands > 0 {
+            add_finding(
+                "thousands_separator",
+                self.thousands,
+                self.thousands_example.as_deref().unwrap_or_default(),
+                format!(
+                    "qsv apply operations regex_replace {} --comparand , --replacement '<NULL>' \
+                     {input}",
+                    shell_quote(&self.selector)
+                ),
+            );
+        }
+
+        if self.date_shapes.len() > 1 {
+            self.date_shapes
+                .sort_by_key(|ds| std::cmp::Reverse(ds.count));
+            add_finding(
+                "date_formats",
+                self.date_shapes.iter().skip(1).map(|ds| ds.count).sum(),
+                &format!(
+                    "{} ({} in a {} column)",
+                    self.date_shapes[1].example,
+                    self.date_shapes[1].shape,
+                    self.date_shapes[0].shape
+                ),
+                format!("qsv datefmt {} {input}", shell_quote(&self.selector)),
+            );
+        }
+
+        if self.padded > 0 {
+            add_finding(
+                "whitespace",
+                self.padded,
+                self.padded_example.as_deref().unwrap_or_default(),
+                format!(
+                    "qsv apply operations trim {} {input}",
+                    shell_quote(&self.selector)
+                ),
+            );
+        }
+
+        if self.placeholders > 0 {
+            let alternatives = self
+                .placeholders_found
+                .iter()
+                .map(|p| regex::escape(p))
+                .collect::<Vec<_>>()
+                .join("|");
+            add_finding(
+                "placeholder_nulls",
+                self.placeholders,
+                &self.placeholders_found.join(", "),
+                format!(
+                    "qsv apply operations regex_replace {} --comparand {} --replacement '<NULL>' \
+                     {input}",
+                    shell_quote(&self.selector),
+                    shell_quote(&format!(r"^\s*({alternatives})\s*$"))
+                ),
+            );
+        }
+
+        findings
+    }
+
+    /// a regex matching any of the date formats seen in the column
+    fn date_pattern(&self) -> String {
+        let alternatives = self
+            .date_shapes
+            .iter()
+            .map(|ds| ds.regex.as_str())
+            .collect::<Vec<_>>();
+        format!("^({})$", alternatives.join("|"))
+    }
+}
+
+/// the shape of a date - runs of 4 digits become 9999, other digit runs 99,
+/// letter runs Aa, and everything else is kept as is
+fn date_shape(value: &str) -> (String, String) {
+    let mut shape = String::new();
+    let mut regex = String::new();
+    let mut chars = value.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c.is_ascii_digit() {
+            let mut run_len = 1;
+            while chars.next_if(char::is_ascii_digit).is_some() {
+                run_len += 1;
+            }
+            if run_len == 4 {
+                shape.push_str("9999");
+                regex.push_str(r"\d{4}");
+            } else {
+                shape.push_str("99");
+                regex.push_str(r"\d{1,3}");
+            }
+        } else if c.is_alphabetic() {
+            while chars.next_if(|c| c.is_alphabetic()).is_some() {}
+            shape.push_str("Aa");
+            regex.push_str(r"\pL+");
+        } else {
+            shape.push(c);
+            regex.push_str(&regex::escape(&c.to_string()));
+        }
+    }
+    (shape, regex)
+}
+
+/// quote an argument of a suggested command for POSIX shells, if required
+fn shell_quote(arg: &str) -> String {
+    if !arg.is_empty()
+        && arg
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "_-./,:=+@%".contains(c))
+    {
+        arg.to_string()
+    } else {
+        format!("'{}'", arg.replace('\'', r"'\''"))
+    }
+}
+
+pub fn run(argv: &[&str]) -> CliResult<()> {
+    let args: Args = util::get_args(USAGE, argv)?;
+
+    let input_path = Path::new(&args.arg_input);
+    if !input_path.exists() {
+        return fail_incorrectusage_clierror!("{} does not exist.", args.arg_input);
+    }
+    let input = shell_quote(&args.arg_input);
+    let input_stem = input_path
+        .file_stem()
+        .map_or_else(|| "input".into(), |stem| stem.to_string_lossy());
+
+    let placeholders: Vec<String> = args
+        .flag_placeholders
+        .split(',')
+        .map(|p| p.trim().to_uppercase())
+        .filter(|p| !p.is_empty())
+        .collect();
+
+    let mut rconfig = Config::new(&Some(args.arg_input.clone()))
+        .delimiter(args.flag_delimiter)
+        .no_headers(args.flag_no_headers);
+
+    let mut findings: Vec<Finding> = Vec::new();
+    let mut add_file_finding = |issue: &'static str, count: u64, example: String, fix: String| {
+        findings.push(Finding {
+            field: String::new(),
+            issue,
+            count,
+            example,
+            fix,
+        });
+    };
+
+    // sniff the dialect, unless the delimiter was set
+    let mut preamble_rows = 0_usize;
+    if args.flag_delimiter.is_none() {
+        match Sniffer::new()
+            .sample_size(SampleSize::Records(SNIFFER_SAMPLE))
+            .sniff_path(&args.arg_input)
+        {
+            Ok(metadata) => {
+                preamble_rows = metadata.dialect.header.num_preamble_rows;
+                if preamble_rows > 0 {
+                    add_file_finding(
+                        "preamble",
+                        preamble_rows as u64,
+                        "1".to_string(),
+                        format!("qsv input --auto-skip {input}"),
+                    );
+                }
+                let sniffed_delimiter = metadata.dialect.delimiter;
+                if sniffed_delimiter != rconfig.get_delimiter() {
+                    let sniffed = (sniffed_delimiter as char).to_string();
+                    add_file_finding(
+                        "delimiter",
+                        1,
+                        sniffed.clone(),
+                        format!("qsv input --delimiter {} {input}", shell_quote(&sniffed)),
+                    );
+                    rconfig = rconfig.delimiter(Some(Delimiter(sniffed_delimiter)));
+                }
+            },
+            Err(e) => {
+                // doctor can still diagnose the file with the default dialect
+                log::warn!("sniff error: {e}");
+            },
+        }
+    }
+
+    let prefer_dmy = rconfig.get_dmy_preference();
+    let mut scanner = RecordScanner::new(
+        BufReader::new(rconfig.io_reader()?),
+        rconfig.get_delimiter(),
+        rconfig.quote,
+    );
+    for _ in 0..preamble_rows {
+        scanner.next_record()?;
+    }
+
+    let mut encoding = RecordIssue::default();
+    let mut ragged_rows = RecordIssue::default();
+    let mut bad_quotes = RecordIssue::default();
+    let mut duplicate_rows = RecordIssue::default();
+    // to save memory, only the hashes of the records are kept
+    let hasher = ahash::RandomState::new();
+    let mut row_hashes: AHashSet<u64> = AHashSet::new();
+
+    let mut columns: Vec<ColumnProfile> = Vec::new();
+    let mut header_findings: Vec<Finding> = Vec::new();
+    let mut record_idx = 0_u64;
+    while let Some(record) = scanner.next_record()? {
+        if !invalid_utf8_ranges(&record.raw, record.byte_offset).is_empty() {
+            encoding.add(record.line);
+        }
+        if !record.bad_quotes.is_empty() {
+            bad_quotes.add(record.line);
+        }
+
+        if record_idx == 0 {
+            let headers: csv::StringRecord = if args.flag_no_headers {
+                (1..=record.fields.len()).map(|i| i.to_string()).collect()
+            } else {
+                record
+                    .fields
+                    .iter()
+                    .map(|field| String::from_utf8_lossy(field))
+                    .collect()
+            };
+            let (safe_names, _) = util::safe_header_names(&headers, true, false, None, "_", true);
+            for (i, (header, safe_name)) in headers.iter().zip(safe_names).enumerate() {
+                // use the column's name in suggested commands if it can't be mistaken
+                // for anything else in a selection, its index otherwise
+                let plain_name =
+                    header == safe_name && headers.iter().filter(|h| *h == header).count() == 1;
+                columns.push(ColumnProfile {
+                    name: header.to_string(),
+                    selector: if plain_name {
+                        header.to_string()
+                    } else {
+                        (i + 1).to_string()
+                    },
+                    ..Default::default()
+                });
+                if !args.flag_no_headers && header != safe_name {
+                    header_findings.push(Finding {
+                        field:   header.to_string(),
+                        issue:   "header",
+                        count:   1,
+                        example: format!("{header} -> {safe_name}"),
+                        fix:     format!("qsv safenames {input}"),
+                    });
+                }
+            }
+            record_idx += 1;
+            if !args.flag_no_headers {
+                continue;
+            }
+        } else {
+            record_idx += 1;
+        }
+
+        if record.fields.len() != columns.len() {
+            ragged_rows.add(record.line);
+        }
+        if !row_hashes.insert(hasher.hash_one(&record.fields)) {
+            duplicate_rows.add(record.line);
+        }
+        for (column, field) in columns.iter_mut().zip(record.fields.iter()) {
+            column.add(&String::from_utf8_lossy(field), &placeholders, prefer_dmy);
+        }
+        // the missing fields of short records are empty
+        for column in columns.iter_mut().skip(record.fields.len()) {
+            column.add("", &placeholders, prefer_dmy);
+        }
+    }
+
+    for (issue, record_issue, fix) in [
+        (
+            "encoding",
+            &encoding,
+            format!("qsv input --encoding-errors replace {input}"),
+        ),
+        (
+            "ragged_rows",
+            &ragged_rows,
+            format!("qsv fixlengths {input}"),
+        ),
+        (
+            "bad_quotes",
+            &bad_quotes,
+            format!(
+                "qsv validate --repair {} {input}",
+                shell_quote(&format!("{input_stem}.repaired.csv"))
+            ),
+        ),
+        (
+            "duplicate_rows",
+            &duplicate_rows,
+            format!("qsv dedup {input}"),
+        ),
+    ] {
+        if record_issue.count > 0 {
+            add_file_finding(
+                issue,
+                record_issue.count,
+                record_issue.first_line.to_string(),
+                fix,
+            );
+        }
+    }
+    findings.extend(header_findings);
+    for column in &mut columns {
+        findings.extend(column.findings(&input));
+    }
+
+    let mut wtr = Config::new(&args.flag_output).writer()?;
+    wtr.write_record(["field", "issue", "count", "example", "suggested_fix"])?;
+    for finding in &findings {
+        wtr.write_record([
+            &finding.field,
+            finding.issue,
+            &finding.count.to_string(),
+            &finding.example,
+            &finding.fix,
+        ])?;
+    }
+    wtr.flush()?;
+
+    if !findings.is_empty() {
+        return fail_clierror!("{} issue/s found in {}.", findings.len(), args.arg_input);
+    }
+    winfo!("No issues found.");
+    Ok(())
+}
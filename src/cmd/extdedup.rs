@@ -8,58 +8,153 @@ to sort the CSV first before deduping it.
 This allows it to run in constant memory and the output will retain the input sort order.
 
 Also, this command is not specific to CSV data, it deduplicates any text file on a 
-line-by-line basis.
+line-by-line basis. Use --select to dedupe a CSV on a subset of its columns instead,
+in which case the input is parsed as CSV and the header row is always kept.
 
 A duplicate count will be sent to <stderr>.
 
+Examples:
+Keep the first row of every customer_id:
+    $ qsv extdedup --select customer_id data.csv deduped.csv
+
+Keep the latest row of every (region, sku) pair, writing the dropped rows to a file:
+    $ qsv extdedup -s region,sku --keep last -D dupes.txt data.csv deduped.csv
+
+Only output the records of a daily drop that were not in any of the previous drops:
+    $ qsv extdedup -s id --store seen.store --stats day2.csv day2_new.csv
+
 Usage:
     qsv extdedup [options] [<input>] [<output>]
     qsv extdedup --help
 
 extdedup options:
+    -s, --select <arg>         Dedupe a CSV on the selected columns instead of whole lines.
+                               See 'qsv select --help' for the format details.
+    --keep <arg>               Which row of a set of duplicates to keep - "first" or "last".
+                               Keeping the last row requires a second pass over the
+                               input, so it does not work with stdin. [default: first]
     --no-output                Do not write deduplicated output to <output>.
                                Use this if you only want to know the duplicate count.
     -D, --dupes-output <file>  Write duplicates to <file>.
                                Note that the file will NOT be a valid CSV.
                                It is a list of duplicate lines, with the row number of the
                                duplicate separated by a tab from the duplicate line itself.
+                               Row numbers are zero-based line numbers, so the header
+                               of a CSV is row 0.
+    --kept-row                 Also write the row number of the kept row of each duplicate
+                               to the --dupes-output, separated by a tab, between the
+                               row number of the duplicate and the duplicate line.
     -H, --human-readable       Comma separate duplicate count.
     --memory-limit <arg>       The maximum amount of memory to buffer the on-disk hash table.
                                This is a percentage of total memory. [default: 10]
+    --store <file>             Persist the on-disk hash table to <file>, so rows seen in
+                               previous runs are also treated as duplicates. Use this
+                               to only output the new records of overlapping feeds.
+                               With --kept-row, the kept row number of a row first
+                               seen in a previous run is empty.
+                               Cannot be used with "--keep last".
+    --store-mode <mode>        How to use the --store file:
+                                 create   - start a new store, replacing any existing one.
+                                 append   - load the store if it exists, and save it
+                                            with the rows of this run added.
+                                 readonly - load the store, but do not save it.
+                               [default: append]
+    --stats                    Report the row & duplicate counts, the store hit rate
+                               and the store size to stderr.
 
 Common options:
     -h, --help                 Display this message
+    -n, --no-headers           When set, the first row will not be interpreted
+                               as headers when using --select.
+    -d, --delimiter <arg>      The field delimiter for reading CSV data with --select.
+                               Must be a single character. (default: ,)
     -Q, --quiet                Do not print duplicate count to stderr.
 "#;
 
 use std::{
     fs,
     io::{self, stdin, stdout, BufRead, Write},
+    path::Path,
+    str::FromStr,
 };
 
 use indicatif::HumanCount;
 use serde::Deserialize;
+use strum_macros::EnumString;
 use sysinfo::System;
 
 // use sysinfo::System::sysinfo;
-use crate::{config, odhtcache, util, CliResult};
+use crate::{
+    config::{self, Config, Delimiter},
+    odhtcache,
+    select::SelectColumns,
+    util, CliResult,
+};
 
 #[derive(Deserialize)]
 struct Args {
     arg_input:           Option<String>,
     arg_output:          Option<String>,
+    flag_select:         Option<SelectColumns>,
+    flag_keep:           String,
     flag_no_output:      bool,
     flag_dupes_output:   Option<String>,
+    flag_kept_row:       bool,
     flag_human_readable: bool,
     flag_memory_limit:   Option<u8>,
+    flag_store:          Option<String>,
+    flag_store_mode:     String,
+    flag_stats:          bool,
+    flag_no_headers:     bool,
+    flag_delimiter:      Option<Delimiter>,
     flag_quiet:          bool,
 }
 
+#[derive(EnumString, PartialEq)]
+#[strum(ascii_case_insensitive)]
+enum Keep {
+    First,
+    Last,
+}
+
+#[derive(EnumString, PartialEq)]
+#[strum(ascii_case_insensitive)]
+enum StoreMode {
+    Create,
+    Append,
+    Readonly,
+}
+
 const MEMORY_LIMITED_BUFFER: u64 = 100 * 1_000_000; // 100 MB
 
 pub fn run(argv: &[&str]) -> CliResult<()> {
     let args: Args = util::get_args(USAGE, argv)?;
 
+    let Ok(keep) = Keep::from_str(&args.flag_keep) else {
+        return fail_incorrectusage_clierror!(
+            "Invalid --keep `{}`. It must be either \"first\" or \"last\".",
+            args.flag_keep
+        );
+    };
+    if keep == Keep::Last && args.arg_input.is_none() {
+        return fail_incorrectusage_clierror!("--keep last requires an input file.");
+    }
+    let Ok(store_mode) = StoreMode::from_str(&args.flag_store_mode) else {
+        return fail_incorrectusage_clierror!(
+            "Invalid --store-mode `{}`. It must be one of \"create\", \"append\" or \"readonly\".",
+            args.flag_store_mode
+        );
+    };
+    let store_path = args.flag_store.as_deref().map(Path::new);
+    if let Some(store_path) = store_path {
+        if keep == Keep::Last {
+            return fail_incorrectusage_clierror!("--store cannot be used with --keep last.");
+        }
+        if store_mode == StoreMode::Readonly && !store_path.exists() {
+            return fail_clierror!("Store {} does not exist.", store_path.display());
+        }
+    }
+
     // memory buffer to use for on-disk hash table,
     // if we can detect the total memory, use 10% of it by default
     // and up to --memory-limit (capped at 50%),
@@ -73,21 +168,15 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
     };
     log::info!("{mem_limited_buffer} bytes used for memory buffer for on-disk hash table...");
 
-    let input_reader: Box<dyn BufRead> = match &args.arg_input {
-        Some(input_path) => {
+    if args.flag_select.is_none() {
+        if let Some(input_path) = &args.arg_input {
             if input_path.to_lowercase().ends_with(".sz") {
                 return fail_clierror!(
                     "Input file cannot be a .sz file. Use 'qsv snappy decompress' first."
                 );
             }
-            let file = fs::File::open(input_path)?;
-            Box::new(io::BufReader::with_capacity(
-                config::DEFAULT_RDR_BUFFER_CAPACITY,
-                file,
-            ))
-        },
-        None => Box::new(io::BufReader::new(stdin().lock())),
-    };
+        }
+    }
 
     let mut output_writer: Box<dyn Write> = match &args.arg_output {
         Some(output_path) => Box::new(io::BufWriter::with_capacity(
@@ -103,7 +192,7 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
     let mut write_dupes = false;
 
     #[cfg(target_family = "unix")]
-    let mut dupes_writer = if let Some(dupes_output) = args.flag_dupes_output {
+    let mut dupes_writer = if let Some(dupes_output) = &args.flag_dupes_output {
         write_dupes = true;
         io::BufWriter::with_capacity(
             config::DEFAULT_WTR_BUFFER_CAPACITY,
@@ -117,7 +206,7 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
     };
 
     #[cfg(target_family = "windows")]
-    let mut dupes_writer = if let Some(dupes_output) = args.flag_dupes_output {
+    let mut dupes_writer = if let Some(dupes_output) = &args.flag_dupes_output {
         write_dupes = true;
         io::BufWriter::with_capacity(
             config::DEFAULT_WTR_BUFFER_CAPACITY,
@@ -130,29 +219,120 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
         )
     };
 
-    let mut dedup_cache = odhtcache::ExtDedupCache::new(mem_limited_buffer);
-
     let mut dupes_count = 0_u64;
-    let mut line_work = String::with_capacity(100);
-    for (row_idx, line) in input_reader.lines().enumerate() {
-        line_work.clone_from(&line?);
-        if dedup_cache.contains(&line_work) {
-            dupes_count += 1;
-            if write_dupes {
-                dupes_writer.write_all(format!("{row_idx}\t{line_work}\n").as_bytes())?;
-            }
-        } else {
-            dedup_cache.insert(&line_work);
-            if args.flag_no_output {
-                continue;
+    let mut itoa_buf = itoa::Buffer::new();
+    // the kept row is None if it was seen in a previous run
+    let mut write_dupe = |row_idx: u64, kept_idx: Option<u64>, line: &[u8]| -> io::Result<()> {
+        dupes_count += 1;
+        if write_dupes {
+            dupes_writer.write_all(itoa_buf.format(row_idx).as_bytes())?;
+            dupes_writer.write_all(b"\t")?;
+            if args.flag_kept_row {
+                if let Some(kept_idx) = kept_idx {
+                    dupes_writer.write_all(itoa_buf.format(kept_idx).as_bytes())?;
+                }
+                dupes_writer.write_all(b"\t")?;
             }
-            output_writer.write_all(format!("{line_work}\n").as_bytes())?;
+            dupes_writer.write_all(line)?;
         }
+        Ok(())
+    };
+
+    let mut stats = Stats::default();
+    match keep {
+        Keep::First => {
+            // the row numbers of a store continue from where the previous run left off,
+            // so the rows seen in previous runs are the ones before `row_offset`
+            let (mut dedup_cache, row_offset) = match store_path {
+                Some(store_path) if store_mode != StoreMode::Create && store_path.exists() => {
+                    odhtcache::ExtDedupCache::load(store_path, mem_limited_buffer)?
+                },
+                _ => (odhtcache::ExtDedupCache::new(mem_limited_buffer), 0),
+            };
+            stats.store_items_before = dedup_cache.len();
+
+            let mut next_row = row_offset;
+            for_each_row(&args, |row_idx, key, line| {
+                let row = row_offset + row_idx;
+                next_row = row + 1;
+                let Some(key) = key else {
+                    if !args.flag_no_output {
+                        output_writer.write_all(line)?;
+                    }
+                    return Ok(());
+                };
+                stats.rows += 1;
+                if let Some(kept_row) = dedup_cache.get(key) {
+                    if kept_row < row_offset {
+                        stats.store_hits += 1;
+                        write_dupe(row_idx, None, line)?;
+                    } else {
+                        write_dupe(row_idx, Some(kept_row - row_offset), line)?;
+                    }
+                } else {
+                    dedup_cache.insert(key, row);
+                    if !args.flag_no_output {
+                        output_writer.write_all(line)?;
+                    }
+                }
+                Ok(())
+            })?;
+            stats.store_items_after = dedup_cache.len();
+
+            if let Some(store_path) = store_path {
+                if store_mode != StoreMode::Readonly {
+                    dedup_cache.save(store_path, next_row)?;
+                }
+                stats.store_bytes = fs::metadata(store_path).map_or(0, |m| m.len());
+            }
+        },
+        Keep::Last => {
+            // first pass - the row of the first occurrence of each key, and for each of those,
+            // the row of the last occurrence. Keying the last occurrences by the row of the
+            // first one keeps their keys short & unique, so their row numbers are exact.
+            let mut firsts = odhtcache::ExtDedupCache::new(mem_limited_buffer / 2);
+            let mut lasts = odhtcache::ExtDedupCache::new(mem_limited_buffer / 2);
+            for_each_row(&args, |row_idx, key, _line| {
+                if let Some(key) = key {
+                    stats.rows += 1;
+                    if let Some(first_idx) = firsts.get(key) {
+                        lasts.update(itoa::Buffer::new().format(first_idx), row_idx);
+                    } else {
+                        firsts.insert(key, row_idx);
+                    }
+                }
+                Ok(())
+            })?;
+
+            // second pass - only keep the last occurrences
+            for_each_row(&args, |row_idx, key, line| {
+                let kept_idx = key
+                    .and_then(|key| firsts.get(key))
+                    .map_or(row_idx, |first_idx| {
+                        lasts
+                            .get(itoa::Buffer::new().format(first_idx))
+                            .unwrap_or(first_idx)
+                    });
+                if kept_idx == row_idx {
+                    if !args.flag_no_output {
+                        output_writer.write_all(line)?;
+                    }
+                } else {
+                    write_dupe(row_idx, Some(kept_idx), line)?;
+                }
+                Ok(())
+            })?;
+        },
     }
 
     dupes_writer.flush()?;
     output_writer.flush()?;
 
+    if args.flag_stats {
+        stats.dupes = dupes_count;
+        stats.report(store_path.is_some());
+    }
+
     if args.flag_quiet {
         return Ok(());
     }
@@ -169,6 +349,132 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
     Ok(())
 }
 
+#[derive(Default)]
+struct Stats {
+    rows:               u64,
+    dupes:              u64,
+    store_hits:         u64,
+    store_items_before: u64,
+    store_items_after:  u64,
+    store_bytes:        u64,
+}
+
+impl Stats {
+    fn report(&self, with_store: bool) {
+        #[allow(clippy::cast_precision_loss)]
+        let pct = |n: u64| {
+            if self.rows == 0 {
+                "0".to_string()
+            } else {
+                util::round_num(n as f64 * 100.0 / self.rows as f64, 2)
+            }
+        };
+        eprintln!("rows: {}", HumanCount(self.rows));
+        eprintln!("new rows: {}", HumanCount(self.rows - self.dupes));
+        eprintln!(
+            "duplicates: {} ({}%)",
+            HumanCount(self.dupes),
+            pct(self.dupes)
+        );
+        if !with_store {
+            return;
+        }
+        eprintln!(
+            "duplicates within input: {}",
+            HumanCount(self.dupes - self.store_hits)
+        );
+        eprintln!(
+            "store hits: {} ({}% hit rate)",
+            HumanCount(self.store_hits),
+            pct(self.store_hits)
+        );
+        eprintln!(
+            "store size: {} keys ({} before), {}",
+            HumanCount(self.store_items_after),
+            HumanCount(self.store_items_before),
+            indicatif::HumanBytes(self.store_bytes)
+        );
+    }
+}
+
+/// Call `f` with the zero-based row number, the dedup key & the line (with its line
+/// terminator) of each row of the input. The key is the whole line, or with --select,
+/// the selected columns. The header row of a CSV has no key, as it is never deduped.
+fn for_each_row(
+    args: &Args,
+    mut f: impl FnMut(u64, Option<&str>, &[u8]) -> CliResult<()>,
+) -> CliResult<()> {
+    let Some(select) = &args.flag_select else {
+        let input_reader: Box<dyn BufRead> = match &args.arg_input {
+            Some(input_path) => {
+                let file = fs::File::open(input_path)?;
+                Box::new(io::BufReader::with_capacity(
+                    config::DEFAULT_RDR_BUFFER_CAPACITY,
+                    file,
+                ))
+            },
+            None => Box::new(io::BufReader::new(stdin().lock())),
+        };
+
+        let mut line_work = String::with_capacity(100);
+        for (row_idx, line) in input_reader.lines().enumerate() {
+            line_work.clone_from(&line?);
+            line_work.push('\n');
+            let key = &line_work[..line_work.len() - 1];
+            f(row_idx as u64, Some(key), line_work.as_bytes())?;
+        }
+        return Ok(());
+    };
+
+    let rconfig = Config::new(&args.arg_input)
+        .delimiter(args.flag_delimiter)
+        .no_headers(args.flag_no_headers)
+        .select(select.clone());
+    let mut rdr = rconfig.reader()?;
+    let headers = rdr.byte_headers()?.clone();
+    let sel = rconfig.selection(&headers)?;
+
+    let mut line_wtr = LineWriter::new(rconfig.get_delimiter());
+    if !rconfig.no_headers {
+        f(0, None, line_wtr.write(&headers)?)?;
+    }
+
+    let mut key_wtr = LineWriter::new(b',');
+    let mut record = csv::ByteRecord::new();
+    while rdr.read_byte_record(&mut record)? {
+        let row_idx = record.position().map_or(0, |pos| pos.line() - 1);
+        let key = String::from_utf8_lossy(key_wtr.write(sel.select(&record))?).into_owned();
+        f(row_idx, Some(&key), line_wtr.write(&record)?)?;
+    }
+    Ok(())
+}
+
+/// Serializes records to CSV lines, reusing its buffer.
+struct LineWriter {
+    delimiter: u8,
+    buf:       Vec<u8>,
+}
+
+impl LineWriter {
+    fn new(delimiter: u8) -> Self {
+        LineWriter {
+            delimiter,
+            buf: Vec::with_capacity(256),
+        }
+    }
+
+    fn write<'a>(&mut self, record: impl IntoIterator<Item = &'a [u8]>) -> io::Result<&[u8]> {
+        let mut buf = std::mem::take(&mut self.buf);
+        buf.clear();
+        let mut wtr = csv::WriterBuilder::new()
+            .delimiter(self.delimiter)
+            .from_writer(buf);
+        wtr.write_record(record)?;
+        self.buf = wtr.into_inner().map_err(|e| e.into_error())?;
+        Ok(&self.buf)
+    }
+}
+
 #[test]
 fn test_extdedup_mem_check() {
     // check to see if sysinfo return meminfo without segfaulting
@@ -1,15 +1,30 @@
 static USAGE: &str = r#"
 Sort an arbitrarily large CSV/text file using a multithreaded external sort algorithm.
 
-This command is not specific to CSV data, it sorts any text file on a 
+By default, this command is not specific to CSV data, it sorts any text file on a 
 line-by-line basis. If sorting a non-CSV file, be sure to set --no-headers, 
 otherwise, the first line will not be included in the external sort.
 
+When --select is set, the input is parsed as CSV instead, and the records are sorted
+on the selected columns. Quoted fields with embedded newlines are handled correctly,
+and the sort can be --numeric and/or --reverse. Sorted runs of records that fit in
+the --memory-limit buffer (a percentage of the total memory) are spilled to --tmp-dir,
+and then merged. A --memory-limit of 0 spills every record. The CSV sort is
+stable, i.e. records with equal sort keys keep their input order. To sort entire
+records in CSV mode, use `--select 1-`.
+
+For examples, see https://github.com/jqnatividad/qsv/blob/master/tests/test_extsort.rs.
+
 Usage:
     qsv extsort [options] [<input>] [<output>]
     qsv extsort --help
 
 External sort option:
+    -s, --select <arg>     Parse the input as CSV and sort on the selected columns.
+                           See 'qsv select --help' for the format details.
+    -N, --numeric          Compare the selected columns numerically.
+                           Requires --select.
+    -R, --reverse          Reverse the sort order. Requires --select.
     --memory-limit <arg>   The maximum amount of memory to buffer the on-disk hash table.
                            This is a percentage of total memory.
                            [default: 10]
@@ -21,6 +36,9 @@ External sort option:
 
 Common options:
     -h, --help             Display this message
+    -d, --delimiter <arg>  The field delimiter for reading & writing CSV data
+                           when sorting with --select.
+                           Must be a single character. (default: ,)
     -n, --no-headers       When set, the first row will not be interpreted
                            as headers and will be sorted with the rest
                            of the rows. Otherwise, the first row will always
@@ -28,24 +46,37 @@ Common options:
 "#;
 
 use std::{
+    cmp,
+    collections::BinaryHeap,
     fs,
     io::{self, stdin, stdout, BufRead, Write},
-    path,
+    mem, path,
 };
 
+use csv::ByteRecord;
 use ext_sort::{buffer::mem::MemoryLimitedBufferBuilder, ExternalSorter, ExternalSorterBuilder};
+use rayon::slice::ParallelSliceMut;
 use serde::Deserialize;
 use sysinfo::System;
 
-use crate::{config, util, CliResult};
+use crate::{
+    cmd::sort::{iter_cmp, iter_cmp_num},
+    config::{self, Config, Delimiter},
+    select::SelectColumns,
+    util, CliResult,
+};
 
 #[derive(Deserialize)]
 struct Args {
     arg_input:         Option<String>,
     arg_output:        Option<String>,
+    flag_select:       Option<SelectColumns>,
+    flag_numeric:      bool,
+    flag_reverse:      bool,
     flag_jobs:         Option<usize>,
     flag_memory_limit: Option<u8>,
     flag_tmp_dir:      Option<String>,
+    flag_delimiter:    Option<Delimiter>,
     flag_no_headers:   bool,
 }
 
@@ -66,6 +97,24 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
         None => "./".to_string(),
     };
 
+    if let Some(select) = args.flag_select.clone() {
+        // the CSV sort compares the bytes it buffers against this budget, so it's
+        // --memory-limit percent of the total memory in bytes (capped at 50%),
+        // otherwise, if we cannot detect the total memory use a default of 100 MB
+        let csv_buffer = if sysinfo::IS_SUPPORTED_SYSTEM {
+            let mut sys = System::new();
+            sys.refresh_memory();
+            sys.total_memory() / 100 * u64::from(u8::min(args.flag_memory_limit.unwrap_or(10), 50))
+        } else {
+            MEMORY_LIMITED_BUFFER
+        };
+        log::info!("{csv_buffer} bytes used for the CSV sort buffer...");
+        return extsort_csv(&args, select, &tmp_dir, csv_buffer);
+    }
+    if args.flag_numeric || args.flag_reverse {
+        return fail_incorrectusage_clierror!("--numeric and --reverse require --select.");
+    }
+
     // memory buffer to use for external merge sort,
     // if we can detect the total memory, use 10% of it by default
     // and up to --memory-limit (capped at 50%),
@@ -148,6 +197,152 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
     Ok(())
 }
 
+/// CSV-aware external merge sort on the --select columns. The records are buffered
+/// until the buffer exceeds the memory limit, and each buffer is sorted in parallel &
+/// spilled to a temporary "run" file. The sorted runs are then merged.
+fn extsort_csv(
+    args: &Args,
+    select: SelectColumns,
+    tmp_dir: &str,
+    mem_limited_buffer: u64,
+) -> CliResult<()> {
+    let rconfig = Config::new(&args.arg_input)
+        .delimiter(args.flag_delimiter)
+        .no_headers(args.flag_no_headers)
+        .select(select);
+    let mut rdr = rconfig.reader()?;
+    let mut wtr = Config::new(&args.arg_output)
+        .delimiter(args.flag_delimiter)
+        .writer()?;
+
+    let headers = rdr.byte_headers()?.clone();
+    let sel = rconfig.selection(&headers)?;
+    rconfig.write_headers(&mut rdr, &mut wtr)?;
+
+    let numeric = args.flag_numeric;
+    let reverse = args.flag_reverse;
+    let compare = |r1: &ByteRecord, r2: &ByteRecord| {
+        let (a, b) = if reverse {
+            (sel.select(r2), sel.select(r1))
+        } else {
+            (sel.select(r1), sel.select(r2))
+        };
+        if numeric {
+            iter_cmp_num(a, b)
+        } else {
+            iter_cmp(a, b)
+        }
+    };
+
+    // set RAYON_NUM_THREADS for the parallel sort of each run
+    util::njobs(args.flag_jobs);
+
+    let spill_dir = tempfile::Builder::new()
+        .prefix("qsv-extsort-")
+        .tempdir_in(tmp_dir)?;
+    let mut run_paths = Vec::new();
+    let mut buffer: Vec<ByteRecord> = Vec::new();
+    let mut buffered_bytes = 0_u64;
+    let mut record = ByteRecord::new();
+    loop {
+        let more_records = rdr.read_byte_record(&mut record)?;
+        if more_records {
+            // approximate the memory used by the record - its data & field bounds
+            buffered_bytes +=
+                (record.as_slice().len() + record.len() * mem::size_of::<usize>()) as u64;
+            buffer.push(record.clone());
+        }
+        // only spill the last buffer if we already spilled, otherwise we can sort in memory
+        if buffered_bytes >= mem_limited_buffer
+            || (!more_records && !run_paths.is_empty() && !buffer.is_empty())
+        {
+            buffer.par_sort_by(|r1, r2| compare(r1, r2));
+            let run_path = spill_dir
+                .path()
+                .join(format!("run_{}.csv", run_paths.len()));
+            let mut run_wtr = csv::WriterBuilder::new()
+                .flexible(true)
+                .from_path(&run_path)?;
+            for r in buffer.drain(..) {
+                run_wtr.write_byte_record(&r)?;
+            }
+            run_wtr.flush()?;
+            run_paths.push(run_path);
+            buffered_bytes = 0;
+        }
+        if !more_records {
+            break;
+        }
+    }
+
+    if run_paths.is_empty() {
+        log::info!("all records fit in the memory buffer, sorting in memory...");
+        buffer.par_sort_by(|r1, r2| compare(r1, r2));
+        for r in &buffer {
+            wtr.write_byte_record(r)?;
+        }
+        return Ok(wtr.flush()?);
+    }
+    log::info!("merging {} sorted runs...", run_paths.len());
+
+    let mut run_rdrs = run_paths
+        .iter()
+        .map(|run_path| {
+            csv::ReaderBuilder::new()
+                .has_headers(false)
+                .flexible(true)
+                .from_path(run_path)
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let mut heap = BinaryHeap::with_capacity(run_rdrs.len());
+    for (run, run_rdr) in run_rdrs.iter_mut().enumerate() {
+        let mut record = ByteRecord::new();
+        if run_rdr.read_byte_record(&mut record)? {
+            heap.push(RunHead {
+                record,
+                run,
+                compare: &compare,
+            });
+        }
+    }
+    while let Some(mut head) = heap.pop() {
+        wtr.write_byte_record(&head.record)?;
+        if run_rdrs[head.run].read_byte_record(&mut head.record)? {
+            heap.push(head);
+        }
+    }
+    Ok(wtr.flush()?)
+}
+
+/// The head record of a sorted run in the merge heap. As BinaryHeap is a max-heap,
+/// the ordering is reversed so the smallest record is popped first. On ties, the
+/// earliest run wins, which keeps the sort stable as the runs are in input order.
+struct RunHead<'a, F> {
+    record:  ByteRecord,
+    run:     usize,
+    compare: &'a F,
+}
+
+impl<F: Fn(&ByteRecord, &ByteRecord) -> cmp::Ordering> Ord for RunHead<'_, F> {
+    fn cmp(&self, other: &Self) -> cmp::Ordering {
+        (self.compare)(&other.record, &self.record).then_with(|| other.run.cmp(&self.run))
+    }
+}
+
+impl<F: Fn(&ByteRecord, &ByteRecord) -> cmp::Ordering> PartialOrd for RunHead<'_, F> {
+    fn partial_cmp(&self, other: &Self) -> Option<cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<F: Fn(&ByteRecord, &ByteRecord) -> cmp::Ordering> PartialEq for RunHead<'_, F> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == cmp::Ordering::Equal
+    }
+}
+
+impl<F: Fn(&ByteRecord, &ByteRecord) -> cmp::Ordering> Eq for RunHead<'_, F> {}
+
 #[test]
 fn test_mem_check() {
     // check to see if sysinfo return meminfo without segfaulting
@@ -156,3 +351,45 @@ fn test_mem_check() {
     let mem10percent = (sys.total_memory() * 1000) / 10; // 10 percent of total memory
     assert!(mem10percent > 0);
 }
+
+#[test]
+fn test_extsort_csv_spill() {
+    let tmp_dir = tempfile::tempdir().unwrap();
+    let input = tmp_dir.path().join("in.csv");
+    let output = tmp_dir.path().join("out.csv");
+    let mut rows: Vec<(u64, u64)> = (0..50).map(|i| ((i * 37) % 13, i)).collect();
+    let mut data = "n,seq\n".to_string();
+    for (n, seq) in &rows {
+        data.push_str(&format!("{n},{seq}\n"));
+    }
+    fs::write(&input, data).unwrap();
+
+    let args = Args {
+        arg_input:         Some(input.to_string_lossy().to_string()),
+        arg_output:        Some(output.to_string_lossy().to_string()),
+        flag_select:       None,
+        flag_numeric:      true,
+        flag_reverse:      true,
+        flag_jobs:         None,
+        flag_memory_limit: None,
+        flag_tmp_dir:      None,
+        flag_delimiter:    None,
+        flag_no_headers:   false,
+    };
+    // a one byte buffer spills every record to its own sorted run
+    extsort_csv(
+        &args,
+        SelectColumns::parse("n").unwrap(),
+        &tmp_dir.path().to_string_lossy(),
+        1,
+    )
+    .unwrap();
+
+    // the merged runs are sorted in reverse numeric order, and the sort is stable
+    rows.sort_by_key(|row| cmp::Reverse(row.0));
+    let mut expected = "n,seq\n".to_string();
+    for (n, seq) in &rows {
+        expected.push_str(&format!("{n},{seq}\n"));
+    }
+    assert_eq!(fs::read_to_string(&output).unwrap(), expected);
+}
@@ -1,6 +1,9 @@
 static USAGE: &str = r#"
 Fill empty fields in selected columns of a CSV.
 
+Fields with one of the values set with --null-values are also
+considered empty.
+
 This command fills empty fields in the selected column
 using the last seen non-empty field in the CSV. This is
 useful to forward-fill values which may only be included
@@ -49,6 +52,9 @@ Common options:
                            sliced, etc.)
     -d, --delimiter <arg>  The field delimiter for reading CSV data.
                            Must be a single character. (default: ,)
+    --null-values <list>   A comma-delimited list of case-sensitive values to treat
+                           as NULL, besides the empty string (e.g. NA,NULL,\N).
+                           Overrides the QSV_NULL_VALUES env var.
 "#;
 
 use std::{io, iter, ops};
@@ -57,7 +63,7 @@ use ahash::AHashMap;
 use serde::Deserialize;
 
 use crate::{
-    config::{Config, Delimiter},
+    config::{Config, Delimiter, NullValues},
     select::{SelectColumns, Selection},
     util,
     util::ByteString,
@@ -69,15 +75,16 @@ type BoxedReader = csv::Reader<Box<dyn io::Read + Send + 'static>>;
 
 #[derive(Deserialize)]
 struct Args {
-    arg_input:       Option<String>,
-    arg_selection:   SelectColumns,
-    flag_output:     Option<String>,
-    flag_no_headers: bool,
-    flag_delimiter:  Option<Delimiter>,
-    flag_groupby:    Option<SelectColumns>,
-    flag_first:      bool,
-    flag_backfill:   bool,
-    flag_default:    Option<String>,
+    arg_input:        Option<String>,
+    arg_selection:    SelectColumns,
+    flag_output:      Option<String>,
+    flag_no_headers:  bool,
+    flag_delimiter:   Option<Delimiter>,
+    flag_groupby:     Option<SelectColumns>,
+    flag_first:       bool,
+    flag_backfill:    bool,
+    flag_default:     Option<String>,
+    flag_null_values: Option<String>,
 }
 
 pub fn run(argv: &[&str]) -> CliResult<()> {
@@ -86,7 +93,8 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
     let rconfig = Config::new(&args.arg_input)
         .delimiter(args.flag_delimiter)
         .no_headers(args.flag_no_headers)
-        .select(args.arg_selection);
+        .select(args.arg_selection)
+        .null_values(args.flag_null_values);
 
     let wconfig = Config::new(&args.flag_output);
 
@@ -107,7 +115,8 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
     let filler = Filler::new(groupby, select)
         .use_first_value(args.flag_first)
         .backfill_empty_values(args.flag_backfill)
-        .use_default_value(args.flag_default);
+        .use_default_value(args.flag_default)
+        .use_null_values(rconfig.get_null_values().clone());
     filler.fill(&mut rdr, &mut wtr)
 }
 
@@ -163,15 +172,17 @@ impl GroupKeyConstructor for GroupKeySelection {
 
 #[derive(Debug)]
 struct GroupValues {
-    map:     AHashMap<usize, ByteString>,
-    default: Option<ByteString>,
+    map:         AHashMap<usize, ByteString>,
+    default:     Option<ByteString>,
+    null_values: NullValues,
 }
 
 impl GroupValues {
-    fn new(default: Option<ByteString>) -> Self {
+    fn new(default: Option<ByteString>, null_values: NullValues) -> Self {
         Self {
             map: AHashMap::new(),
             default,
+            null_values,
         }
     }
 }
@@ -184,13 +195,19 @@ trait GroupMemorizer {
 
 impl GroupMemorizer for GroupValues {
     fn memorize(&mut self, selection: &Selection, record: &csv::ByteRecord) {
-        for &col in selection.iter().filter(|&col| !record[*col].is_empty()) {
+        for &col in selection
+            .iter()
+            .filter(|&col| !self.null_values.is_null(&record[*col]))
+        {
             self.map.insert(col, record[col].to_vec());
         }
     }
 
     fn memorize_first(&mut self, selection: &Selection, record: &csv::ByteRecord) {
-        for &col in selection.iter().filter(|&col| !record[*col].is_empty()) {
+        for &col in selection
+            .iter()
+            .filter(|&col| !self.null_values.is_null(&record[*col]))
+        {
             self.map.entry(col).or_insert_with(|| record[col].to_vec());
         }
     }
@@ -202,7 +219,7 @@ impl GroupMemorizer for GroupValues {
             .map_selected(selection, |(col, field)| {
                 (
                     col,
-                    if field.is_empty() {
+                    if self.null_values.is_null(&field) {
                         self.default
                             .clone()
                             .or_else(|| self.map.get(&col).cloned())
@@ -225,6 +242,7 @@ struct Filler {
     first:         bool,
     backfill:      bool,
     default_value: Option<ByteString>,
+    null_values:   NullValues,
 }
 
 impl Filler {
@@ -237,6 +255,7 @@ impl Filler {
             first: false,
             backfill: false,
             default_value: None,
+            null_values: NullValues::default(),
         }
     }
 
@@ -255,6 +274,11 @@ impl Filler {
         self
     }
 
+    fn use_null_values(mut self, null_values: NullValues) -> Self {
+        self.null_values = null_values;
+        self
+    }
+
     fn fill(mut self, rdr: &mut BoxedReader, wtr: &mut BoxedWriter) -> CliResult<()> {
         let mut record = csv::ByteRecord::new();
 
@@ -264,10 +288,11 @@ impl Filler {
 
             // Record valid fields, and fill empty fields
             let default_value = self.default_value.clone();
+            let null_values = &self.null_values;
             let group = self
                 .grouper
                 .entry(key.clone())
-                .or_insert_with(|| GroupValues::new(default_value));
+                .or_insert_with(|| GroupValues::new(default_value, null_values.clone()));
 
             match (self.default_value.is_some(), self.first) {
                 (true, _) => {},
@@ -278,7 +303,12 @@ impl Filler {
             let row = group.fill(&self.select, ByteRecord::from(&record));
 
             // Handle buffering rows which still have nulls.
-            if self.backfill && (self.select.iter().any(|&i| row[i] == b"")) {
+            if self.backfill
+                && (self
+                    .select
+                    .iter()
+                    .any(|&i| self.null_values.is_null(&row[i])))
+            {
                 self.buffer.entry(key.clone()).or_default().push(row);
             } else {
                 if let Some(rows) = self.buffer.remove(&key) {
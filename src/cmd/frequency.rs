@@ -68,6 +68,8 @@ frequency options:
     -a, --asc               Sort the frequency tables in ascending order by
                             count. The default is descending order.
     --no-nulls              Don't include NULLs in the frequency table.
+                            NULLs are empty values, and any values set
+                            with the --null-values option.
     -i, --ignore-case       Ignore case when computing frequencies.
     -j, --jobs <arg>        The number of jobs to run in parallel.
                             This works much faster when the given CSV data has
@@ -85,6 +87,10 @@ Common options:
                            names.
     -d, --delimiter <arg>  The field delimiter for reading CSV data.
                            Must be a single character. (default: ,)
+    --null-values <list>   A comma-delimited list of case-sensitive values to treat
+                           as NULL, besides the empty string (e.g. NA,NULL,\N).
+                           They are counted as NULLs, after trimming.
+                           Overrides the QSV_NULL_VALUES env var.
     --memcheck             Check if there is enough memory to load the entire
                            CSV into memory using CONSERVATIVE heuristics.
 "#;
@@ -124,6 +130,7 @@ pub struct Args {
     pub flag_output:         Option<String>,
     pub flag_no_headers:     bool,
     pub flag_delimiter:      Option<Delimiter>,
+    pub flag_null_values:    Option<String>,
     pub flag_memcheck:       bool,
 }
 
@@ -223,6 +230,7 @@ impl Args {
             .delimiter(self.flag_delimiter)
             .no_headers(self.flag_no_headers)
             .select(self.flag_select.clone())
+            .null_values(self.flag_null_values.clone())
     }
 
     #[inline]
@@ -360,8 +368,10 @@ impl Args {
         let mut row_buffer: csv::ByteRecord = csv::ByteRecord::with_capacity(200, nsel_len);
 
         let flag_no_nulls = self.flag_no_nulls;
+        let null_values = self.rconfig().get_null_values().clone();
         if self.flag_ignore_case {
             let mut buf = String::new();
+            let mut is_null;
             // safety: we do get_unchecked_mut on freq_tables
             // as we know that nsel_len is the same as freq_tables.len()
             // so we can skip the bounds check
@@ -371,13 +381,16 @@ impl Args {
                 for (i, field) in nsel.select(row_buffer.into_iter()).enumerate() {
                     field_buffer = {
                         if let Ok(s) = simdutf8::basic::from_utf8(field) {
+                            // NULL values are case-sensitive, so check before lowercasing
+                            is_null = null_values.is_null(s.trim().as_bytes());
                             util::to_lowercase_into(s.trim(), &mut buf);
                             buf.as_bytes().to_vec()
                         } else {
+                            is_null = null_values.is_null(field);
                             field.to_vec()
                         }
                     };
-                    if !field_buffer.is_empty() {
+                    if !is_null {
                         unsafe {
                             freq_tables.get_unchecked_mut(i).add(field_buffer);
                         }
@@ -400,7 +413,7 @@ impl Args {
                             field.to_vec()
                         }
                     };
-                    if !field_buffer.is_empty() {
+                    if !null_values.is_null(&field_buffer) {
                         unsafe {
                             freq_tables.get_unchecked_mut(i).add(field_buffer);
                         }
@@ -0,0 +1,498 @@
+static USAGE: &str = r#"
+Find near-duplicate rows by clustering the values of the selected columns.
+
+Unlike the 'dedup' & 'extdedup' commands, which only find exact duplicates, this
+command groups rows whose values are probably the same entity spelled differently -
+e.g. "Acme Corp.", "ACME corp" & "Corp, Acme" - so they can be reviewed & merged.
+The clustering methods are modeled after OpenRefine's.
+
+Key collision methods compute a key for every value, and cluster the values with the
+same key. They are fast, and have few false positives:
+  fingerprint  Lowercase, remove punctuation & accents, then sort & dedupe the
+               whitespace-separated tokens. Clusters values that only differ in
+               case, punctuation, accents, token order & repeated tokens.
+  ngram        Like fingerprint, but on the sorted, deduped character n-grams of the
+               value with all whitespace removed (see --ngram-size). Also clusters
+               values that differ in spacing, e.g. "Mc Donald" & "McDonald".
+  phonetic     The Soundex code of each fingerprint token. Clusters values that
+               sound alike in English, e.g. "Smith" & "Smyth".
+
+Nearest neighbor (kNN) methods compare values pairwise, finding more clusters at
+the cost of more false positives & more time:
+  levenshtein  Cluster values within --radius edits of each other.
+  jaro-winkler Cluster values with a Jaro-Winkler similarity of at least --threshold.
+To keep the number of comparisons tractable, only values that share a substring
+of --block-size characters are compared (blocking). Clusters are transitive, so if
+A is near B and B is near C, A, B & C are in the same cluster.
+
+Values are compared case-insensitively by the kNN methods. When multiple columns are
+selected, their values are joined with a space. Empty values are never clustered.
+
+The output is the input with a cluster ID column added. Rows in a cluster of two or
+more rows get the cluster's ID, numbered in order of first appearance. All the other
+rows get an empty cluster ID. Use --summary to also write a summary of each cluster
+with its distinct values, their counts & the most frequent value as a suggested merge.
+
+This command loads the entire CSV into memory.
+
+Examples:
+Cluster vendor names by fingerprint, writing a summary for review:
+    $ qsv fuzzydedup -s vendor --summary clusters.csv vendors.csv > vendors_clustered.csv
+
+Cluster names with up to 2 edits apart, comparing names that share 4 characters:
+    $ qsv fuzzydedup -s first_name,last_name -m levenshtein --radius 2 --block-size 4 people.csv
+
+For more examples, see https://github.com/jqnatividad/qsv/blob/master/tests/test_fuzzydedup.rs.
+
+Usage:
+    qsv fuzzydedup [options] [<input>]
+    qsv fuzzydedup --help
+
+fuzzydedup options:
+    -s, --select <arg>         Select the columns to cluster on.
+                               See 'qsv select --help' for the format details.
+    -m, --method <name>        The clustering method: fingerprint, ngram, phonetic,
+                               levenshtein or jaro-winkler. [default: fingerprint]
+    --ngram-size <n>           The size of the character n-grams of the ngram method.
+                               [default: 2]
+    --radius <n>               The maximum edit distance of the levenshtein method.
+                               [default: 1]
+    --threshold <score>        The minimum similarity (0.0 to 1.0) of the
+                               jaro-winkler method. [default: 0.9]
+    --block-size <n>           The number of characters two values must have in
+                               common to be compared by the kNN methods. Smaller
+                               blocks find more clusters, but are slower.
+                               [default: 6]
+    --cluster-column <name>    The name of the cluster ID column.
+                               [default: cluster_id]
+    --summary <file>           Write a CSV summary of the clusters to <file>, with
+                               the columns cluster_id, rows, distinct_values,
+                               suggested & values.
+    -j, --jobs <arg>           The number of jobs to run in parallel for the kNN methods.
+                               When not set, the number of jobs is set to the
+                               number of CPUs detected.
+
+Common options:
+    -h, --help                 Display this message
+    -o, --output <file>        Write output to <file> instead of stdout.
+    -n, --no-headers           When set, the first row will not be interpreted
+                               as headers.
+    -d, --delimiter <arg>      The field delimiter for reading CSV data.
+                               Must be a single character. (default: ,)
+    --memcheck                 Check if there is enough memory to load the entire
+                               CSV into memory using CONSERVATIVE heuristics.
+"#;
+
+use std::str::FromStr;
+
+use ahash::{AHashMap, AHashSet};
+use rayon::prelude::*;
+use serde::Deserialize;
+use strsim::{jaro_winkler, levenshtein};
+use strum_macros::EnumString;
+use unicode_normalization::{char::is_combining_mark, UnicodeNormalization};
+
+use crate::{
+    config::{Config, Delimiter},
+    select::SelectColumns,
+    util, CliResult,
+};
+
+#[derive(Deserialize)]
+struct Args {
+    arg_input:           Option<String>,
+    flag_select:         SelectColumns,
+    flag_method:         String,
+    flag_ngram_size:     usize,
+    flag_radius:         usize,
+    flag_threshold:      f64,
+    flag_block_size:     usize,
+    flag_cluster_column: String,
+    flag_summary:        Option<String>,
+    flag_jobs:           Option<usize>,
+    flag_output:         Option<String>,
+    flag_no_headers:     bool,
+    flag_delimiter:      Option<Delimiter>,
+    flag_memcheck:       bool,
+}
+
+#[derive(Clone, Copy, EnumString, PartialEq)]
+#[strum(ascii_case_insensitive)]
+enum Method {
+    Fingerprint,
+    Ngram,
+    Phonetic,
+    Levenshtein,
+    #[strum(serialize = "jaro-winkler", serialize = "jarowinkler")]
+    JaroWinkler,
+}
+
+pub fn run(argv: &[&str]) -> CliResult<()> {
+    let args: Args = util::get_args(USAGE, argv)?;
+
+    let Ok(method) = Method::from_str(&args.flag_method) else {
+        return fail_incorrectusage_clierror!(
+            "Invalid --method `{}`. Supported methods are: fingerprint, ngram, phonetic, \
+             levenshtein & jaro-winkler.",
+            args.flag_method
+        );
+    };
+    if args.flag_ngram_size == 0 || args.flag_block_size == 0 {
+        return fail_incorrectusage_clierror!("--ngram-size and --block-size must be at least 1.");
+    }
+    if !(0.0..=1.0).contains(&args.flag_threshold) {
+        return fail_incorrectusage_clierror!("--threshold must be between 0.0 and 1.0.");
+    }
+
+    let rconfig = Config::new(&args.arg_input)
+        .delimiter(args.flag_delimiter)
+        .no_headers(args.flag_no_headers)
+        .select(args.flag_select.clone());
+
+    // we're loading the entire file into memory, we need to check avail mem
+    if let Some(path) = rconfig.path.clone() {
+        util::mem_file_check(&path, false, args.flag_memcheck)?;
+    }
+
+    let mut rdr = rconfig.reader()?;
+    let headers = rdr.byte_headers()?.clone();
+    let sel = rconfig.selection(&headers)?;
+    let all = rdr.byte_records().collect::<Result<Vec<_>, _>>()?;
+
+    // the distinct values of the selected columns, in order of first appearance,
+    // and the distinct value of each row (None for empty values)
+    let mut values: Vec<String> = Vec::new();
+    let mut value_ids: AHashMap<String, usize> = AHashMap::new();
+    let mut row_values: Vec<Option<usize>> = Vec::with_capacity(all.len());
+    for record in &all {
+        let value = sel
+            .select(record)
+            .map(|v| String::from_utf8_lossy(v).trim().to_string())
+            .filter(|v| !v.is_empty())
+            .collect::<Vec<_>>()
+            .join(" ");
+        if value.is_empty() {
+            row_values.push(None);
+            continue;
+        }
+        let next_id = values.len();
+        let id = *value_ids.entry(value.clone()).or_insert(next_id);
+        if id == next_id {
+            values.push(value);
+        }
+        row_values.push(Some(id));
+    }
+
+    // cluster the distinct values, so the values of a cluster share the same root
+    let mut clusters = UnionFind::new(values.len());
+    match method {
+        Method::Fingerprint | Method::Ngram | Method::Phonetic => {
+            let mut keys: AHashMap<String, usize> = AHashMap::new();
+            for (id, value) in values.iter().enumerate() {
+                let key = match method {
+                    Method::Fingerprint => fingerprint(value),
+                    Method::Ngram => ngram_fingerprint(value, args.flag_ngram_size),
+                    _ => phonetic_fingerprint(value),
+                };
+                if key.is_empty() {
+                    continue;
+                }
+                let first = *keys.entry(key).or_insert(id);
+                clusters.union(first, id);
+            }
+        },
+        Method::Levenshtein | Method::JaroWinkler => {
+            // set RAYON_NUM_THREADS
+            util::njobs(args.flag_jobs);
+
+            let radius = args.flag_radius;
+            let threshold = args.flag_threshold;
+            let lowered: Vec<String> = values.iter().map(|v| v.to_lowercase()).collect();
+            let neighbors = knn_pairs(&lowered, args.flag_block_size, |a, b| {
+                if method == Method::Levenshtein {
+                    levenshtein(a, b) <= radius
+                } else {
+                    jaro_winkler(a, b) >= threshold
+                }
+            });
+            for (a, b) in neighbors {
+                clusters.union(a, b);
+            }
+        },
+    }
+
+    // number the clusters with two or more rows in order of first appearance
+    let mut cluster_rows: AHashMap<usize, usize> = AHashMap::new();
+    for id in row_values.iter().flatten() {
+        *cluster_rows.entry(clusters.find(*id)).or_default() += 1;
+    }
+    let mut cluster_ids: AHashMap<usize, usize> = AHashMap::new();
+    let mut row_clusters: Vec<Option<usize>> = Vec::with_capacity(row_values.len());
+    for id in &row_values {
+        let cluster_id = id.and_then(|id| {
+            let root = clusters.find(id);
+            if cluster_rows[&root] < 2 {
+                return None;
+            }
+            let next_id = cluster_ids.len() + 1;
+            Some(*cluster_ids.entry(root).or_insert(next_id))
+        });
+        row_clusters.push(cluster_id);
+    }
+
+    let mut wtr = Config::new(&args.flag_output).writer()?;
+    if !rconfig.no_headers {
+        let mut headers = headers.clone();
+        headers.push_field(args.flag_cluster_column.as_bytes());
+        wtr.write_record(&headers)?;
+    }
+    let mut itoa_buf = itoa::Buffer::new();
+    for (record, cluster_id) in all.iter().zip(row_clusters.iter()) {
+        let mut record = record.clone();
+        match cluster_id {
+            Some(cluster_id) => record.push_field(itoa_buf.format(*cluster_id).as_bytes()),
+            None => record.push_field(b""),
+        }
+        wtr.write_byte_record(&record)?;
+    }
+    wtr.flush()?;
+
+    if let Some(summary) = &args.flag_summary {
+        write_summary(
+            summary,
+            &values,
+            &row_values,
+            &row_clusters,
+            cluster_ids.len(),
+        )?;
+    }
+    Ok(())
+}
+
+/// Write a summary row for each cluster, with its distinct values & their row counts,
+/// most frequent first. The most frequent value is suggested as the merged value.
+fn write_summary(
+    path: &str,
+    values: &[String],
+    row_values: &[Option<usize>],
+    row_clusters: &[Option<usize>],
+    num_clusters: usize,
+) -> CliResult<()> {
+    // the distinct values of each cluster, with their row counts in order of first appearance
+    let mut cluster_values: Vec<Vec<(usize, usize)>> = vec![Vec::new(); num_clusters];
+    for (value, cluster_id) in row_values.iter().zip(row_clusters.iter()) {
+        let (Some(value), Some(cluster_id)) = (value, cluster_id) else {
+            continue;
+        };
+        let counts = &mut cluster_values[cluster_id - 1];
+        match counts.iter_mut().find(|(v, _)| v == value) {
+            Some((_, count)) => *count += 1,
+            None => counts.push((*value, 1)),
+        }
+    }
+
+    let mut wtr = Config::new(&Some(path.to_string())).writer()?;
+    wtr.write_record([
+        "cluster_id",
+        "rows",
+        "distinct_values",
+        "suggested",
+        "values",
+    ])?;
+    for (i, mut counts) in cluster_values.into_iter().enumerate() {
+        // most frequent first. Ties keep the order of first appearance as the sort is stable.
+        counts.sort_by_key(|(_, count)| std::cmp::Reverse(*count));
+        let rows: usize = counts.iter().map(|(_, count)| count).sum();
+        let listing = counts
+            .iter()
+            .map(|(value, count)| format!("{} ({count})", values[*value]))
+            .collect::<Vec<_>>()
+            .join(" | ");
+        wtr.write_record([
+            (i + 1).to_string(),
+            rows.to_string(),
+            counts.len().to_string(),
+            values[counts[0].0].clone(),
+            listing,
+        ])?;
+    }
+    Ok(wtr.flush()?)
+}
+
+/// Find the pairs of values that are near each other, only comparing values that share
+/// a block - a substring of `block_size` characters. The blocks are compared in parallel.
+fn knn_pairs(
+    values: &[String],
+    block_size: usize,
+    is_near: impl Fn(&str, &str) -> bool + Sync,
+) -> Vec<(usize, usize)> {
+    let mut blocks: AHashMap<String, Vec<usize>> = AHashMap::new();
+    for (id, value) in values.iter().enumerate() {
+        let chars: Vec<char> = value.chars().collect();
+        let mut value_blocks = AHashSet::new();
+        if chars.len() <= block_size {
+            value_blocks.insert(value.clone());
+        } else {
+            for window in chars.windows(block_size) {
+                value_blocks.insert(window.iter().collect::<String>());
+            }
+        }
+        for block in value_blocks {
+            blocks.entry(block).or_default().push(id);
+        }
+    }
+
+    let blocks: Vec<Vec<usize>> = blocks
+        .into_values()
+        .filter(|block| block.len() > 1)
+        .collect();
+    let mut pairs: Vec<(usize, usize)> = blocks
+        .par_iter()
+        .flat_map_iter(|block| {
+            let mut near = Vec::new();
+            for (i, &a) in block.iter().enumerate() {
+                for &b in &block[i + 1..] {
+                    if is_near(&values[a], &values[b]) {
+                        near.push((a, b));
+                    }
+                }
+            }
+            near
+        })
+        .collect();
+    // the same pair can be found in multiple blocks
+    pairs.par_sort_unstable();
+    pairs.dedup();
+    pairs
+}
+
+/// lowercase, strip accents, and replace punctuation & control characters with spaces
+fn normalize(value: &str) -> String {
+    value
+        .trim()
+        .to_lowercase()
+        .nfkd()
+        .filter(|c| !is_combining_mark(*c))
+        .map(|c| {
+            if c.is_alphanumeric() || c.is_whitespace() {
+                c
+            } else {
+                ' '
+            }
+        })
+        .collect()
+}
+
+/// OpenRefine's fingerprint key - the sorted, deduped tokens of the normalized value
+fn fingerprint(value: &str) -> String {
+    let mut tokens: Vec<&str> = Vec::new();
+    let normalized = normalize(value);
+    tokens.extend(normalized.split_whitespace());
+    tokens.sort_unstable();
+    tokens.dedup();
+    tokens.join(" ")
+}
+
+/// OpenRefine's n-gram fingerprint key - the sorted, deduped character n-grams of the
+/// normalized value with all whitespace removed
+fn ngram_fingerprint(value: &str, n: usize) -> String {
+    let chars: Vec<char> = normalize(value)
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .collect();
+    if chars.len() <= n {
+        return chars.into_iter().collect();
+    }
+    let mut ngrams: Vec<String> = chars
+        .windows(n)
+        .map(|window| window.iter().collect())
+        .collect();
+    ngrams.sort_unstable();
+    ngrams.dedup();
+    ngrams.concat()
+}
+
+/// the Soundex codes of the fingerprint tokens. Tokens that don't start with an ASCII
+/// letter (e.g. numbers) are kept as is.
+fn phonetic_fingerprint(value: &str) -> String {
+    let fingerprint = fingerprint(value);
+    let mut codes: Vec<String> = fingerprint.split(' ').map(soundex).collect();
+    codes.sort_unstable();
+    codes.dedup();
+    codes.join(" ")
+}
+
+/// American Soundex - the first letter, followed by the digit codes of the consonants,
+/// padded or truncated to four characters
+fn soundex(token: &str) -> String {
+    fn code(c: char) -> Option<char> {
+        match c {
+            'b' | 'f' | 'p' | 'v' => Some('1'),
+            'c' | 'g' | 'j' | 'k' | 'q' | 's' | 'x' | 'z' => Some('2'),
+            'd' | 't' => Some('3'),
+            'l' => Some('4'),
+            'm' | 'n' => Some('5'),
+            'r' => Some('6'),
+            _ => None,
+        }
+    }
+
+    let mut chars = token.chars().filter(char::is_ascii_alphabetic);
+    let Some(first) = chars
+        .next()
+        .filter(|_| token.starts_with(|c: char| c.is_ascii_alphabetic()))
+    else {
+        return token.to_string();
+    };
+    let mut result = String::with_capacity(4);
+    result.push(first.to_ascii_uppercase());
+    let mut last = code(first);
+    for c in chars {
+        let current = code(c);
+        if let Some(digit) = current.filter(|_| current != last) {
+            result.push(digit);
+            if result.len() == 4 {
+                break;
+            }
+        }
+        // h & w don't separate consonants with the same code, vowels do
+        if c != 'h' && c != 'w' {
+            last = current;
+        }
+    }
+    while result.len() < 4 {
+        result.push('0');
+    }
+    result
+}
+
+/// A union-find (disjoint set) with path compression, to build transitive clusters
+struct UnionFind {
+    parents: Vec<usize>,
+}
+
+impl UnionFind {
+    fn new(len: usize) -> Self {
+        UnionFind {
+            parents: (0..len).collect(),
+        }
+    }
+
+    fn find(&mut self, mut i: usize) -> usize {
+        while self.parents[i] != i {
+            self.parents[i] = self.parents[self.parents[i]];
+            i = self.parents[i];
+        }
+        i
+    }
+
+    /// merge the sets of `a` & `b`. The smallest index becomes the root.
+    fn union(&mut self, a: usize, b: usize) {
+        let (root_a, root_b) = (self.find(a), self.find(b));
+        if root_a < root_b {
+            self.parents[root_b] = root_a;
+        } else if root_b < root_a {
+            self.parents[root_a] = root_b;
+        }
+    }
+}
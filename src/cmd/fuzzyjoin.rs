@@ -0,0 +1,377 @@
+static USAGE: &str = r#"
+Joins two sets of CSV data on the approximate similarity of the specified columns.
+
+For every row in <input1>, the key columns are compared against the key columns of
+every candidate row in <input2>, and the best matching row (or the --top N matching
+rows) with a similarity score at or above --threshold is joined to it.
+The similarity score is added as a new column after the joined columns.
+
+The following similarity metrics are supported, all returning a score between
+0.0 (completely different) and 1.0 (identical):
+  jw        Jaro-Winkler similarity. Favors strings that match from the beginning.
+            Good for short strings like person & company names.
+  dl        Normalized Damerau-Levenshtein similarity. The edit distance (insertions,
+            deletions, substitutions & transpositions) relative to the string length.
+            Good for catching typos.
+  tokenset  Token set ratio. The strings are split into lowercase alphanumeric tokens,
+            and the shared and unshared tokens are compared, ignoring token order and
+            duplicate tokens. Good for addresses & names where word order varies,
+            e.g. "Acme Corp Inc." vs "ACME Inc Corp".
+
+When multiple key columns are specified, their values are joined with a space
+before they are compared. Leading & trailing whitespace is always ignored.
+
+Comparing every row of <input1> against every row of <input2> gets expensive fast.
+Use --block to only compare rows that share the exact same values in the given
+blocking columns (e.g. a zip code or a country column). <input2> is loaded into
+memory, and the rows of <input1> are matched in parallel batches.
+
+Examples:
+Match vendors by name with Jaro-Winkler similarity, only comparing vendors in the same zip code:
+    $ qsv fuzzyjoin --block zip name vendors_a.csv name vendors_b.csv
+
+Get the 3 best address matches scoring at least 0.7 using the token set ratio:
+    $ qsv fuzzyjoin --metric tokenset --threshold 0.7 --top 3 addr a.csv address b.csv
+
+For more examples, see https://github.com/jqnatividad/qsv/blob/master/tests/test_fuzzyjoin.rs.
+
+Usage:
+    qsv fuzzyjoin [options] <columns1> <input1> <columns2> <input2>
+    qsv fuzzyjoin --help
+
+input parameters:
+    For <input1> and <input2>, specifying `-` indicates reading from stdin.
+    Only one of the inputs can be stdin.
+
+fuzzyjoin options:
+    -m, --metric <name>       The similarity metric to use: jw, dl or tokenset.
+                              [default: jw]
+    -t, --threshold <score>   The minimum similarity score (0.0 to 1.0) for two rows
+                              to match. [default: 0.8]
+    --top <n>                 Join up to the <n> best matching rows of <input2>,
+                              in descending order of similarity. [default: 1]
+    -b, --block <cols>        Only compare rows with exactly the same values in these
+                              blocking columns. The columns are selected from <input1>,
+                              and from <input2> as well unless --block2 is set.
+    --block2 <cols>           The blocking columns of <input2>, if they are different
+                              from --block. Must select the same number of columns.
+    -i, --ignore-case         Compare the keys case insensitively.
+    --left                    Do a 'left outer' fuzzy join. Rows in <input1> without
+                              a match are also written, padded with empty fields.
+    --score-column <name>     The name of the similarity score column.
+                              [default: fuzzy_score]
+    --round <places>          Round the similarity score to <places> decimal places.
+                              [default: 4]
+    -j, --jobs <arg>          The number of jobs to run in parallel.
+                              When not set, the number of jobs is set to the
+                              number of CPUs detected.
+    --batch <size>            The number of rows of <input1> per batch to load into
+                              memory, before matching them in parallel.
+                              [default: 10000]
+
+Common options:
+    -h, --help                Display this message
+    -o, --output <file>       Write output to <file> instead of stdout.
+    -n, --no-headers          When set, the first row will not be interpreted
+                              as headers. (i.e., They are not searched, analyzed,
+                              sliced, etc.)
+    -d, --delimiter <arg>     The field delimiter for reading CSV data.
+                              Must be a single character. (default: ,)
+"#;
+
+use std::{cmp::Ordering, iter::repeat, str::FromStr};
+
+use ahash::{AHashMap, AHashSet};
+use rayon::prelude::*;
+use serde::Deserialize;
+use strsim::{jaro_winkler, normalized_damerau_levenshtein, normalized_levenshtein};
+use strum_macros::EnumString;
+
+use crate::{
+    config::{Config, Delimiter},
+    select::{SelectColumns, Selection},
+    util, CliResult,
+};
+
+#[derive(Deserialize)]
+struct Args {
+    arg_columns1:      SelectColumns,
+    arg_input1:        String,
+    arg_columns2:      SelectColumns,
+    arg_input2:        String,
+    flag_metric:       String,
+    flag_threshold:    f64,
+    flag_top:          usize,
+    flag_block:        Option<SelectColumns>,
+    flag_block2:       Option<SelectColumns>,
+    flag_ignore_case:  bool,
+    flag_left:         bool,
+    flag_score_column: String,
+    flag_round:        u32,
+    flag_jobs:         Option<usize>,
+    flag_batch:        usize,
+    flag_output:       Option<String>,
+    flag_no_headers:   bool,
+    flag_delimiter:    Option<Delimiter>,
+}
+
+#[derive(Clone, Copy, Debug, EnumString, PartialEq)]
+#[strum(ascii_case_insensitive)]
+enum Metric {
+    Jw,
+    Dl,
+    Tokenset,
+}
+
+impl Metric {
+    #[inline]
+    fn similarity(self, a: &str, b: &str) -> f64 {
+        match self {
+            Metric::Jw => jaro_winkler(a, b),
+            Metric::Dl => normalized_damerau_levenshtein(a, b),
+            Metric::Tokenset => token_set_ratio(a, b),
+        }
+    }
+}
+
+/// the rows of <input2>, with their comparison keys, grouped by blocking key
+struct Candidates {
+    rows:   Vec<csv::ByteRecord>,
+    keys:   Vec<String>,
+    blocks: AHashMap<Vec<Vec<u8>>, Vec<usize>>,
+}
+
+pub fn run(argv: &[&str]) -> CliResult<()> {
+    let args: Args = util::get_args(USAGE, argv)?;
+
+    let Ok(metric) = Metric::from_str(&args.flag_metric) else {
+        return fail_incorrectusage_clierror!(
+            "Invalid --metric `{}`. Supported metrics are: jw, dl & tokenset.",
+            args.flag_metric
+        );
+    };
+    if !(0.0..=1.0).contains(&args.flag_threshold) {
+        return fail_incorrectusage_clierror!("--threshold must be between 0.0 and 1.0.");
+    }
+    if args.flag_top == 0 {
+        return fail_incorrectusage_clierror!("--top must be greater than zero.");
+    }
+    if args.flag_block.is_none() && args.flag_block2.is_some() {
+        return fail_incorrectusage_clierror!("--block2 requires --block.");
+    }
+
+    let rconf1 = Config::new(&Some(args.arg_input1.clone()))
+        .delimiter(args.flag_delimiter)
+        .no_headers(args.flag_no_headers);
+    let rconf2 = Config::new(&Some(args.arg_input2.clone()))
+        .delimiter(args.flag_delimiter)
+        .no_headers(args.flag_no_headers);
+    util::errif_greater_one_stdin(&[rconf1.clone(), rconf2.clone()])?;
+
+    let mut rdr1 = rconf1.reader()?;
+    let mut rdr2 = rconf2.reader()?;
+    let headers1 = rdr1.byte_headers()?.clone();
+    let headers2 = rdr2.byte_headers()?.clone();
+
+    let sel1 = args.arg_columns1.selection(&headers1, !rconf1.no_headers)?;
+    let sel2 = args.arg_columns2.selection(&headers2, !rconf2.no_headers)?;
+    if sel1.len() != sel2.len() {
+        return fail_incorrectusage_clierror!(
+            "Column selections must have the same number of columns, but found column selections \
+             with {} and {} columns.",
+            sel1.len(),
+            sel2.len()
+        );
+    }
+
+    let (block1, block2) = match &args.flag_block {
+        Some(block) => {
+            let block1 = block.selection(&headers1, !rconf1.no_headers)?;
+            let block2 = args
+                .flag_block2
+                .as_ref()
+                .unwrap_or(block)
+                .selection(&headers2, !rconf2.no_headers)?;
+            if block1.len() != block2.len() {
+                return fail_incorrectusage_clierror!(
+                    "Blocking column selections must have the same number of columns, but found \
+                     {} and {} columns.",
+                    block1.len(),
+                    block2.len()
+                );
+            }
+            (Some(block1), Some(block2))
+        },
+        None => (None, None),
+    };
+
+    let casei = args.flag_ignore_case;
+
+    // load <input2> into memory, grouping its rows by blocking key
+    let mut candidates = Candidates {
+        rows:   Vec::new(),
+        keys:   Vec::new(),
+        blocks: AHashMap::new(),
+    };
+    let mut record = csv::ByteRecord::new();
+    while rdr2.read_byte_record(&mut record)? {
+        let idx = candidates.rows.len();
+        candidates
+            .blocks
+            .entry(block_key(block2.as_ref(), &record))
+            .or_default()
+            .push(idx);
+        candidates.keys.push(compare_key(&sel2, &record, casei));
+        candidates.rows.push(record.clone());
+    }
+
+    let mut wtr = Config::new(&args.flag_output).writer()?;
+    if !rconf1.no_headers {
+        let mut headers = headers1.clone();
+        headers.extend(headers2.iter());
+        headers.push_field(args.flag_score_column.as_bytes());
+        wtr.write_record(&headers)?;
+    }
+    let pad2: csv::ByteRecord = repeat(b"").take(headers2.len()).collect();
+
+    // set RAYON_NUM_THREADS
+    util::njobs(args.flag_jobs);
+
+    let batchsize = args.flag_batch.max(1);
+    let mut batch = Vec::with_capacity(batchsize);
+    let mut batch_results = Vec::with_capacity(batchsize);
+    let threshold = args.flag_threshold;
+    let top = args.flag_top;
+
+    'batch_loop: loop {
+        for _ in 0..batchsize {
+            if rdr1.read_byte_record(&mut record)? {
+                batch.push(record.clone());
+            } else {
+                break;
+            }
+        }
+
+        if batch.is_empty() {
+            // break out of infinite loop when at EOF
+            break 'batch_loop;
+        }
+
+        batch
+            .par_iter()
+            .map(|row1| {
+                let Some(block) = candidates.blocks.get(&block_key(block1.as_ref(), row1)) else {
+                    return Vec::new();
+                };
+                let key1 = compare_key(&sel1, row1, casei);
+                let mut matches: Vec<(usize, f64)> = block
+                    .iter()
+                    .filter_map(|&idx2| {
+                        let score = metric.similarity(&key1, &candidates.keys[idx2]);
+                        (score >= threshold).then_some((idx2, score))
+                    })
+                    .collect();
+                // best scores first. Ties are kept in <input2> order as the sort is stable.
+                matches.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(Ordering::Equal));
+                matches.truncate(top);
+                matches
+            })
+            .collect_into_vec(&mut batch_results);
+
+        // rayon collect() guarantees original order, so we can just append results each batch
+        for (row1, matches) in batch.iter().zip(batch_results.iter()) {
+            if matches.is_empty() {
+                if args.flag_left {
+                    let mut output = row1.clone();
+                    output.extend(pad2.iter());
+                    output.push_field(b"");
+                    wtr.write_byte_record(&output)?;
+                }
+                continue;
+            }
+            for &(idx2, score) in matches {
+                let mut output = row1.clone();
+                output.extend(candidates.rows[idx2].iter());
+                output.push_field(util::round_num(score, args.flag_round).as_bytes());
+                wtr.write_byte_record(&output)?;
+            }
+        }
+
+        batch.clear();
+    } // end batch loop
+
+    Ok(wtr.flush()?)
+}
+
+#[inline]
+fn block_key(sel: Option<&Selection>, row: &csv::ByteRecord) -> Vec<Vec<u8>> {
+    match sel {
+        Some(sel) => sel
+            .select(row)
+            .map(|v| String::from_utf8_lossy(v).trim().as_bytes().to_vec())
+            .collect(),
+        None => Vec::new(),
+    }
+}
+
+/// the string to compare - the trimmed values of the key columns joined by a space
+#[inline]
+fn compare_key(sel: &Selection, row: &csv::ByteRecord, casei: bool) -> String {
+    let key = sel
+        .select(row)
+        .map(|v| String::from_utf8_lossy(v).trim().to_string())
+        .collect::<Vec<_>>()
+        .join(" ");
+    if casei {
+        key.to_lowercase()
+    } else {
+        key
+    }
+}
+
+/// Token set ratio, as popularized by the fuzzywuzzy library.
+/// The strings are tokenized into lowercase alphanumeric words. The sorted intersection
+/// of the token sets is then compared with the intersection plus the remaining tokens
+/// of each string, returning the best normalized Levenshtein similarity.
+fn token_set_ratio(a: &str, b: &str) -> f64 {
+    fn tokens(s: &str) -> AHashSet<String> {
+        s.split(|c: char| !c.is_alphanumeric())
+            .filter(|t| !t.is_empty())
+            .map(str::to_lowercase)
+            .collect()
+    }
+    fn sorted_join<'a>(tokens: impl Iterator<Item = &'a String>) -> String {
+        let mut tokens: Vec<&String> = tokens.collect();
+        tokens.sort_unstable();
+        tokens
+            .into_iter()
+            .map(String::as_str)
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    let tokens_a = tokens(a);
+    let tokens_b = tokens(b);
+    if tokens_a.is_empty() || tokens_b.is_empty() {
+        return if tokens_a.is_empty() && tokens_b.is_empty() {
+            1.0
+        } else {
+            0.0
+        };
+    }
+
+    let common = sorted_join(tokens_a.intersection(&tokens_b));
+    let diff_ab = sorted_join(tokens_a.difference(&tokens_b));
+    let diff_ba = sorted_join(tokens_b.difference(&tokens_a));
+
+    let combined_ab = format!("{common} {diff_ab}").trim().to_string();
+    let combined_ba = format!("{common} {diff_ba}").trim().to_string();
+
+    let mut ratio = normalized_levenshtein(&combined_ab, &combined_ba);
+    if !common.is_empty() {
+        ratio = ratio
+            .max(normalized_levenshtein(&common, &combined_ab))
+            .max(normalized_levenshtein(&common, &combined_ba));
+    }
+    ratio
+}
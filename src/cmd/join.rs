@@ -8,6 +8,27 @@ Joins are always done by ignoring leading and trailing whitespace. By default,
 joins are done case sensitively, but this can be disabled with the --ignore-case
 flag.
 
+Join keys can be further normalized on both sides before they are compared with
+the --normalize option, which takes a comma-separated list of transforms that are
+applied in the given order to every key column:
+
+  numeric  Canonicalize numbers, so "007" matches "7", "1.0" matches "1"
+           and "1e3" matches "1000". Non-numeric values are left as is.
+  date     Canonicalize dates recognized by qsv-dateparser to RFC 3339,
+           so "2024-01-31" matches "01/31/2024" and "Jan 31, 2024".
+           Set the QSV_PREFER_DMY environment variable to parse ambiguous
+           dates as day/month/year. Non-date values are left as is.
+  nfkc     Apply Unicode NFKC normalization (compatibility folding), so
+           full-width "ＡＢＣ" matches "ABC" and "ﬁ" matches "fi".
+  squeeze  Collapse runs of whitespace inside the key into a single space.
+  lower    Lowercase the key (the same as --ignore-case).
+
+To apply different transforms to each key column, separate the transform lists of
+the key columns with a semicolon, in the order of the column selections. An empty
+list leaves that key column as is. e.g. with two key columns, "numeric;squeeze,lower"
+canonicalizes numbers in the first key column, and squeezes whitespace & lowercases
+the second key column.
+
 The columns arguments specify the columns to join for each input. Columns can
 be referenced by name or index, starting at 1. Specify multiple columns by
 separating them with a comma. Specify a range of columns with `-`. Both
@@ -26,6 +47,8 @@ input parameters:
 
 join options:
     -i, --ignore-case      When set, joins are done case insensitively.
+    --normalize <spec>     Normalize the join keys of both inputs with the given
+                           transforms before comparing them. See above for details.
     --left                 Do a 'left outer' join. This returns all rows in
                            first CSV data set, including rows with no
                            corresponding row in the second data set. When no
@@ -56,6 +79,19 @@ join options:
                            Otherwise, empty fields are completely ignored.
                            (In fact, any row that has an empty field in the
                            key specified is ignored.)
+                           Key fields matching one of the values set with
+                           the --null-values option are treated as empty.
+    --memory-limit <arg>   When set, switch to a disk-backed 'grace hash join'
+                           if the input that is loaded into memory (<input2>,
+                           or <input1> for a right join) would need more than
+                           this percentage of total memory (capped at 50).
+                           Both inputs are then hash partitioned by join key
+                           into temporary files, and each partition is joined
+                           in memory. The output order is the same as the
+                           in-memory join. Has no effect on cross joins.
+    --tmp-dir <arg>        The directory to use for the temporary partition
+                           files of a disk-backed join. Defaults to the
+                           system temporary directory.
 
 Common options:
     -h, --help             Display this message
@@ -65,16 +101,31 @@ Common options:
                            sliced, etc.)
     -d, --delimiter <arg>  The field delimiter for reading CSV data.
                            Must be a single character. (default: ,)
+    --null-values <list>   A comma-delimited list of case-sensitive values to treat
+                           as NULL, besides the empty string (e.g. NA,NULL,\N).
+                           Overrides the QSV_NULL_VALUES env var.
 "#;
 
-use std::{collections::hash_map::Entry, fmt, io, iter::repeat, str};
+use std::{
+    cmp::Reverse,
+    collections::{hash_map::Entry, BinaryHeap},
+    fmt, fs, io,
+    iter::repeat,
+    path::{Path, PathBuf},
+    str,
+    str::FromStr,
+};
 
-use ahash::AHashMap;
+use ahash::{AHashMap, RandomState};
 use byteorder::{BigEndian, WriteBytesExt};
+use qsv_dateparser::parse_with_preference;
+use rust_decimal::Decimal;
 use serde::Deserialize;
+use sysinfo::System;
+use unicode_normalization::UnicodeNormalization;
 
 use crate::{
-    config::{Config, Delimiter, SeekRead},
+    config::{Config, Delimiter, NullValues, SeekRead},
     index::Indexed,
     select::{SelectColumns, Selection},
     util,
@@ -84,23 +135,32 @@ use crate::{
 
 #[derive(Deserialize)]
 struct Args {
-    arg_columns1:     SelectColumns,
-    arg_input1:       String,
-    arg_columns2:     SelectColumns,
-    arg_input2:       String,
-    flag_left:        bool,
-    flag_left_anti:   bool,
-    flag_left_semi:   bool,
-    flag_right:       bool,
-    flag_full:        bool,
-    flag_cross:       bool,
-    flag_output:      Option<String>,
-    flag_no_headers:  bool,
-    flag_ignore_case: bool,
-    flag_nulls:       bool,
-    flag_delimiter:   Option<Delimiter>,
+    arg_columns1:      SelectColumns,
+    arg_input1:        String,
+    arg_columns2:      SelectColumns,
+    arg_input2:        String,
+    flag_left:         bool,
+    flag_left_anti:    bool,
+    flag_left_semi:    bool,
+    flag_right:        bool,
+    flag_full:         bool,
+    flag_cross:        bool,
+    flag_output:       Option<String>,
+    flag_no_headers:   bool,
+    flag_ignore_case:  bool,
+    flag_normalize:    Option<String>,
+    flag_nulls:        bool,
+    flag_memory_limit: Option<u8>,
+    flag_tmp_dir:      Option<String>,
+    flag_delimiter:    Option<Delimiter>,
+    flag_null_values:  Option<String>,
 }
 
+const MEMORY_LIMITED_BUFFER: u64 = 100 * 1_000_000; // 100 MB
+
+/// the maximum number of partitions of a disk-backed join
+const MAX_PARTITIONS: u64 = 128;
+
 pub fn run(argv: &[&str]) -> CliResult<()> {
     let args: Args = util::get_args(USAGE, argv)?;
     let mut state = args.new_io_state()?;
@@ -151,8 +211,24 @@ struct IoState<R, W: io::Write> {
     rdr2:       csv::Reader<R>,
     sel2:       Selection,
     no_headers: bool,
-    casei:      bool,
+    norm:       KeyNormalizer,
     nulls:      bool,
+    spill:      Option<Spill>,
+}
+
+/// the join operations supported by the disk-backed grace hash join
+#[derive(Clone, Copy, PartialEq)]
+enum GraceJoin {
+    Inner,
+    Outer { right: bool },
+    Full,
+    Left { anti: bool },
+}
+
+/// where & into how many partitions a disk-backed join spills its inputs
+struct Spill {
+    partitions: usize,
+    tmp_dir:    tempfile::TempDir,
 }
 
 impl<R: io::Read + io::Seek, W: io::Write> IoState<R, W> {
@@ -174,12 +250,15 @@ impl<R: io::Read + io::Seek, W: io::Write> IoState<R, W> {
     }
 
     fn inner_join(mut self) -> CliResult<()> {
+        if let Some(spill) = self.spill.take() {
+            return self.grace_join(&spill, GraceJoin::Inner);
+        }
         let mut scratch = csv::ByteRecord::new();
-        let mut validx = ValueIndex::new(self.rdr2, &self.sel2, self.casei, self.nulls)?;
+        let mut validx = ValueIndex::new(self.rdr2, &self.sel2, &self.norm, self.nulls)?;
         let mut row = csv::ByteRecord::new();
         let mut key;
         while self.rdr1.read_byte_record(&mut row)? {
-            key = get_row_key(&self.sel1, &row, self.casei);
+            key = self.norm.row_key(&self.sel1, &row);
             if let Some(rows) = validx.values.get(&key) {
                 for &rowi in rows {
                     validx.idx.seek(rowi as u64)?;
@@ -198,14 +277,17 @@ impl<R: io::Read + io::Seek, W: io::Write> IoState<R, W> {
             ::std::mem::swap(&mut self.rdr1, &mut self.rdr2);
             ::std::mem::swap(&mut self.sel1, &mut self.sel2);
         }
+        if let Some(spill) = self.spill.take() {
+            return self.grace_join(&spill, GraceJoin::Outer { right });
+        }
 
         let mut scratch = csv::ByteRecord::new();
         let (_, pad2) = self.get_padding()?;
-        let mut validx = ValueIndex::new(self.rdr2, &self.sel2, self.casei, self.nulls)?;
+        let mut validx = ValueIndex::new(self.rdr2, &self.sel2, &self.norm, self.nulls)?;
         let mut row = csv::ByteRecord::new();
         let mut key;
         while self.rdr1.read_byte_record(&mut row)? {
-            key = get_row_key(&self.sel1, &row, self.casei);
+            key = self.norm.row_key(&self.sel1, &row);
             if let Some(rows) = validx.values.get(&key) {
                 for &rowi in rows {
                     validx.idx.seek(rowi as u64)?;
@@ -227,11 +309,14 @@ impl<R: io::Read + io::Seek, W: io::Write> IoState<R, W> {
     }
 
     fn left_join(mut self, anti: bool) -> CliResult<()> {
-        let validx = ValueIndex::new(self.rdr2, &self.sel2, self.casei, self.nulls)?;
+        if let Some(spill) = self.spill.take() {
+            return self.grace_join(&spill, GraceJoin::Left { anti });
+        }
+        let validx = ValueIndex::new(self.rdr2, &self.sel2, &self.norm, self.nulls)?;
         let mut row = csv::ByteRecord::new();
         let mut key;
         while self.rdr1.read_byte_record(&mut row)? {
-            key = get_row_key(&self.sel1, &row, self.casei);
+            key = self.norm.row_key(&self.sel1, &row);
             if validx.values.get(&key).is_none() {
                 if anti {
                     self.wtr.write_record(&row)?;
@@ -244,16 +329,19 @@ impl<R: io::Read + io::Seek, W: io::Write> IoState<R, W> {
     }
 
     fn full_outer_join(mut self) -> CliResult<()> {
+        if let Some(spill) = self.spill.take() {
+            return self.grace_join(&spill, GraceJoin::Full);
+        }
         let mut scratch = csv::ByteRecord::new();
         let (pad1, pad2) = self.get_padding()?;
-        let mut validx = ValueIndex::new(self.rdr2, &self.sel2, self.casei, self.nulls)?;
+        let mut validx = ValueIndex::new(self.rdr2, &self.sel2, &self.norm, self.nulls)?;
 
         // Keep track of which rows we've written from rdr2.
         let mut rdr2_written: Vec<_> = repeat(false).take(validx.num_rows).collect();
         let mut row1 = csv::ByteRecord::new();
         let mut key;
         while self.rdr1.read_byte_record(&mut row1)? {
-            key = get_row_key(&self.sel1, &row1, self.casei);
+            key = self.norm.row_key(&self.sel1, &row1);
             if let Some(rows) = validx.values.get(&key) {
                 for &rowi in rows {
                     rdr2_written[rowi] = true;
@@ -299,6 +387,99 @@ impl<R: io::Read + io::Seek, W: io::Write> IoState<R, W> {
         Ok(self.wtr.flush()?)
     }
 
+    /// A disk-backed grace hash join. Both inputs are hash partitioned by join key into
+    /// temporary files, so matching rows always end up in the same partition. Each
+    /// partition of rdr2 is then loaded into memory & joined with the same partition
+    /// of rdr1. Every spilled row is prefixed with its row number, so the joined
+    /// partitions can be k-way merged back into the order of the in-memory join.
+    fn grace_join(mut self, spill: &Spill, kind: GraceJoin) -> CliResult<()> {
+        let (pad1, pad2) = self.get_padding()?;
+        let hasher = RandomState::new();
+        let parts1 = partition_input(&mut self.rdr1, &self.sel1, &self.norm, &hasher, spill, 1)?;
+        let parts2 = partition_input(&mut self.rdr2, &self.sel2, &self.norm, &hasher, spill, 2)?;
+
+        // the joined rows, ordered by rdr1 row number
+        let mut joined_paths = Vec::with_capacity(spill.partitions);
+        // the rows of rdr2 that weren't joined in a full outer join, ordered by rdr2 row number
+        let mut unjoined_paths = Vec::new();
+        let mut prefixed = csv::ByteRecord::new();
+        let mut row = csv::ByteRecord::new();
+        for (p, (part1, part2)) in parts1.iter().zip(parts2.iter()).enumerate() {
+            let mut rows2 = Vec::new();
+            let mut values: AHashMap<Vec<ByteString>, Vec<usize>> = AHashMap::new();
+            if let Some(path) = part2 {
+                let mut rdr = spill_reader(path)?;
+                while rdr.read_byte_record(&mut prefixed)? {
+                    split_prefix(&prefixed, &mut row);
+                    let key = self.norm.row_key(&self.sel2, &row);
+                    if self.nulls || !key.iter().any(std::vec::Vec::is_empty) {
+                        values.entry(key).or_default().push(rows2.len());
+                    }
+                    rows2.push((prefixed[0].to_vec(), row.clone()));
+                }
+                fs::remove_file(path)?;
+            }
+            let mut rows2_written = vec![false; rows2.len()];
+
+            if let Some(path) = part1 {
+                let joined_path = spill.tmp_dir.path().join(format!("joined_{p}.csv"));
+                let mut wtr = spill_writer(&joined_path)?;
+                let mut rdr = spill_reader(path)?;
+                while rdr.read_byte_record(&mut prefixed)? {
+                    split_prefix(&prefixed, &mut row);
+                    let key = self.norm.row_key(&self.sel1, &row);
+                    let rowi = &prefixed[0];
+                    match (kind, values.get(&key)) {
+                        (GraceJoin::Left { anti }, rows) => {
+                            if rows.is_some() != anti {
+                                write_prefixed(&mut wtr, rowi, row.iter())?;
+                            }
+                        },
+                        (_, Some(rows)) => {
+                            for &i in rows {
+                                rows2_written[i] = true;
+                                let row2 = &rows2[i].1;
+                                if kind == (GraceJoin::Outer { right: true }) {
+                                    write_prefixed(&mut wtr, rowi, row2.iter().chain(&row))?;
+                                } else {
+                                    write_prefixed(&mut wtr, rowi, row.iter().chain(row2))?;
+                                }
+                            }
+                        },
+                        (GraceJoin::Inner, None) => {},
+                        (GraceJoin::Outer { right: true }, None) => {
+                            write_prefixed(&mut wtr, rowi, pad2.iter().chain(&row))?;
+                        },
+                        (GraceJoin::Outer { right: false } | GraceJoin::Full, None) => {
+                            write_prefixed(&mut wtr, rowi, row.iter().chain(&pad2))?;
+                        },
+                    }
+                }
+                wtr.flush()?;
+                fs::remove_file(path)?;
+                joined_paths.push(joined_path);
+            }
+
+            if kind == GraceJoin::Full && rows2_written.contains(&false) {
+                let unjoined_path = spill.tmp_dir.path().join(format!("unjoined_{p}.csv"));
+                let mut wtr = spill_writer(&unjoined_path)?;
+                for ((rowi, row2), _) in rows2
+                    .iter()
+                    .zip(rows2_written.iter())
+                    .filter(|(_, &written)| !written)
+                {
+                    write_prefixed(&mut wtr, rowi, pad1.iter().chain(row2))?;
+                }
+                wtr.flush()?;
+                unjoined_paths.push(unjoined_path);
+            }
+        }
+
+        merge_spilled(&joined_paths, &mut self.wtr)?;
+        merge_spilled(&unjoined_paths, &mut self.wtr)?;
+        Ok(self.wtr.flush()?)
+    }
+
     fn get_padding(&mut self) -> CliResult<(csv::ByteRecord, csv::ByteRecord)> {
         let len1 = self.rdr1.byte_headers()?.len();
         let len2 = self.rdr2.byte_headers()?.len();
@@ -316,7 +497,8 @@ impl Args {
         let rconf1 = Config::new(&Some(self.arg_input1.clone()))
             .delimiter(self.flag_delimiter)
             .no_headers(self.flag_no_headers)
-            .select(self.arg_columns1.clone());
+            .select(self.arg_columns1.clone())
+            .null_values(self.flag_null_values.clone());
         let rconf2 = Config::new(&Some(self.arg_input2.clone()))
             .delimiter(self.flag_delimiter)
             .no_headers(self.flag_no_headers)
@@ -325,6 +507,17 @@ impl Args {
         let mut rdr1 = rconf1.reader_file_stdin()?;
         let mut rdr2 = rconf2.reader_file_stdin()?;
         let (sel1, sel2) = self.get_selections(&rconf1, &mut rdr1, &rconf2, &mut rdr2)?;
+        let norm = KeyNormalizer::new(
+            self.flag_normalize.as_deref(),
+            sel1.len(),
+            self.flag_ignore_case,
+            rconf1.get_dmy_preference(),
+            rconf1.get_null_values().clone(),
+        )?;
+        let spill = match self.flag_memory_limit {
+            Some(memory_limit) if !self.flag_cross => self.get_spill(memory_limit)?,
+            _ => None,
+        };
         Ok(IoState {
             wtr: Config::new(&self.flag_output).writer()?,
             rdr1,
@@ -332,11 +525,68 @@ impl Args {
             rdr2,
             sel2,
             no_headers: rconf1.no_headers,
-            casei: self.flag_ignore_case,
+            norm,
             nulls: self.flag_nulls,
+            spill,
         })
     }
 
+    /// Decide if the join should be disk-backed, and if so, into how many partitions
+    /// the inputs should be split so each partition fits in the memory buffer.
+    fn get_spill(&self, memory_limit: u8) -> CliResult<Option<Spill>> {
+        // if we cannot detect the total memory, use a default of 100 MB
+        let mem_limited_buffer = if sysinfo::IS_SUPPORTED_SYSTEM {
+            let mut sys = System::new();
+            sys.refresh_memory();
+            sys.total_memory() / 100 * u64::from(memory_limit.min(50))
+        } else {
+            MEMORY_LIMITED_BUFFER
+        };
+
+        // for a right join, it's <input1> that's loaded into memory
+        let build_input = if self.flag_right {
+            &self.arg_input1
+        } else {
+            &self.arg_input2
+        };
+        // we can't know the size of stdin beforehand, so always spill it
+        let input_size = if build_input == "-" {
+            u64::MAX
+        } else {
+            fs::metadata(build_input)?.len()
+        };
+        // the in-memory hash table takes about three times the size of the CSV data
+        let mem_needed = input_size.saturating_mul(3);
+        if mem_needed <= mem_limited_buffer {
+            log::info!(
+                "{mem_needed} bytes needed for in-memory join, within the {mem_limited_buffer} \
+                 bytes memory buffer..."
+            );
+            return Ok(None);
+        }
+
+        let partitions = (mem_needed / mem_limited_buffer.max(1) + 1).clamp(2, MAX_PARTITIONS);
+        let tmp_dir = match &self.flag_tmp_dir {
+            Some(tmp_dir) => {
+                if !Path::new(tmp_dir).exists() {
+                    return fail_clierror!("tmp-dir '{tmp_dir}' does not exist");
+                }
+                tempfile::Builder::new()
+                    .prefix("qsv-join-")
+                    .tempdir_in(tmp_dir)?
+            },
+            None => tempfile::Builder::new().prefix("qsv-join-").tempdir()?,
+        };
+        log::info!(
+            "disk-backed join with {partitions} partitions in {}...",
+            tmp_dir.path().display()
+        );
+        Ok(Some(Spill {
+            partitions: partitions as usize,
+            tmp_dir,
+        }))
+    }
+
     #[allow(clippy::unused_self)]
     fn get_selections<R: io::Read>(
         &self,
@@ -361,6 +611,108 @@ impl Args {
     }
 }
 
+/// Hash partition the rows of an input by join key into temporary files, each row
+/// prefixed with its row number. Returns the paths of the partition files, with
+/// `None` for partitions that didn't get any rows.
+fn partition_input<R: io::Read>(
+    rdr: &mut csv::Reader<R>,
+    sel: &Selection,
+    norm: &KeyNormalizer,
+    hasher: &RandomState,
+    spill: &Spill,
+    input_num: u8,
+) -> CliResult<Vec<Option<PathBuf>>> {
+    let mut paths = vec![None; spill.partitions];
+    let mut wtrs: Vec<Option<csv::Writer<fs::File>>> =
+        (0..spill.partitions).map(|_| None).collect();
+    let mut itoa_buf = itoa::Buffer::new();
+    let mut row = csv::ByteRecord::new();
+    let mut rowi = 0_u64;
+    while rdr.read_byte_record(&mut row)? {
+        let p = (hasher.hash_one(norm.row_key(sel, &row)) % spill.partitions as u64) as usize;
+        if wtrs[p].is_none() {
+            let path = spill
+                .tmp_dir
+                .path()
+                .join(format!("input{input_num}_{p}.csv"));
+            wtrs[p] = Some(spill_writer(&path)?);
+            paths[p] = Some(path);
+        }
+        let wtr = wtrs[p].as_mut().unwrap();
+        write_prefixed(wtr, itoa_buf.format(rowi).as_bytes(), row.iter())?;
+        rowi += 1;
+    }
+    for wtr in wtrs.iter_mut().flatten() {
+        wtr.flush()?;
+    }
+    Ok(paths)
+}
+
+/// k-way merge the spilled rows of the partitions by their row number prefix,
+/// writing them without the prefix.
+fn merge_spilled<W: io::Write>(paths: &[PathBuf], wtr: &mut csv::Writer<W>) -> CliResult<()> {
+    let mut rdrs = paths
+        .iter()
+        .map(|path| spill_reader(path))
+        .collect::<CliResult<Vec<_>>>()?;
+    let mut records = vec![csv::ByteRecord::new(); rdrs.len()];
+    let mut heap = BinaryHeap::with_capacity(rdrs.len());
+    for (i, rdr) in rdrs.iter_mut().enumerate() {
+        if rdr.read_byte_record(&mut records[i])? {
+            heap.push(Reverse((row_number(&records[i])?, i)));
+        }
+    }
+    // all the rows with the same row number are in the same partition, so they are
+    // written in the order they were joined
+    while let Some(Reverse((_, i))) = heap.pop() {
+        wtr.write_record(records[i].iter().skip(1))?;
+        if rdrs[i].read_byte_record(&mut records[i])? {
+            heap.push(Reverse((row_number(&records[i])?, i)));
+        }
+    }
+    Ok(())
+}
+
+fn spill_writer(path: &Path) -> CliResult<csv::Writer<fs::File>> {
+    Ok(csv::WriterBuilder::new().flexible(true).from_path(path)?)
+}
+
+fn spill_reader(path: &Path) -> CliResult<csv::Reader<fs::File>> {
+    Ok(csv::ReaderBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .from_path(path)?)
+}
+
+#[inline]
+fn write_prefixed<'a, W: io::Write>(
+    wtr: &mut csv::Writer<W>,
+    prefix: &[u8],
+    fields: impl Iterator<Item = &'a [u8]>,
+) -> CliResult<()> {
+    wtr.write_field(prefix)?;
+    for field in fields {
+        wtr.write_field(field)?;
+    }
+    wtr.write_record(None::<&[u8]>)?;
+    Ok(())
+}
+
+/// strip the row number prefix of a spilled row
+#[inline]
+fn split_prefix(prefixed: &csv::ByteRecord, row: &mut csv::ByteRecord) {
+    row.clear();
+    row.extend(prefixed.iter().skip(1));
+}
+
+#[inline]
+fn row_number(prefixed: &csv::ByteRecord) -> CliResult<u64> {
+    match atoi_simd::parse::<u64>(&prefixed[0]) {
+        Ok(rowi) => Ok(rowi),
+        Err(_) => fail_clierror!("Invalid row number in spilled join partition."),
+    }
+}
+
 struct ValueIndex<R> {
     // This maps tuples of values to corresponding rows.
     values:   AHashMap<Vec<ByteString>, Vec<usize>>,
@@ -372,7 +724,7 @@ impl<R: io::Read + io::Seek> ValueIndex<R> {
     fn new(
         mut rdr: csv::Reader<R>,
         sel: &Selection,
-        casei: bool,
+        norm: &KeyNormalizer,
         nulls: bool,
     ) -> CliResult<ValueIndex<R>> {
         let mut val_idx = AHashMap::with_capacity(10000);
@@ -403,10 +755,7 @@ impl<R: io::Read + io::Seek> ValueIndex<R> {
             // indexes in one pass.
             row_idx.write_u64::<BigEndian>(row.position().unwrap().byte())?;
 
-            let fields: Vec<_> = sel
-                .select(&row)
-                .map(|v| util::transform(v, casei))
-                .collect();
+            let fields = norm.row_key(sel, &row);
             if nulls || !fields.iter().any(std::vec::Vec::is_empty) {
                 match val_idx.entry(fields) {
                     Entry::Vacant(v) => {
@@ -450,7 +799,130 @@ impl<R> fmt::Debug for ValueIndex<R> {
     }
 }
 
-#[inline]
-fn get_row_key(sel: &Selection, row: &csv::ByteRecord, casei: bool) -> Vec<ByteString> {
-    sel.select(row).map(|v| util::transform(v, casei)).collect()
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum KeyTransform {
+    Numeric,
+    Date,
+    Nfkc,
+    Squeeze,
+    Lower,
+}
+
+impl FromStr for KeyTransform {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "numeric" => Ok(KeyTransform::Numeric),
+            "date" => Ok(KeyTransform::Date),
+            "nfkc" => Ok(KeyTransform::Nfkc),
+            "squeeze" => Ok(KeyTransform::Squeeze),
+            "lower" => Ok(KeyTransform::Lower),
+            other => fail_format!(
+                "Unknown --normalize transform \"{other}\". Valid transforms are: numeric, date, \
+                 nfkc, squeeze & lower."
+            ),
+        }
+    }
+}
+
+/// Normalizes the join key values of a row. Keys are always trimmed, then each key
+/// column is run through its own pipeline of transforms. NULL values become empty keys.
+#[derive(Debug)]
+struct KeyNormalizer {
+    // one pipeline per key column, empty if no transforms besides trimming
+    pipelines:   Vec<Vec<KeyTransform>>,
+    casei:       bool,
+    prefer_dmy:  bool,
+    null_values: NullValues,
+}
+
+impl KeyNormalizer {
+    fn new(
+        spec: Option<&str>,
+        num_keys: usize,
+        casei: bool,
+        prefer_dmy: bool,
+        null_values: NullValues,
+    ) -> Result<KeyNormalizer, String> {
+        let parse_list = |list: &str| -> Result<Vec<KeyTransform>, String> {
+            list.split(',')
+                .filter(|t| !t.trim().is_empty())
+                .map(KeyTransform::from_str)
+                .collect()
+        };
+
+        let pipelines = match spec {
+            None => vec![vec![]; num_keys],
+            Some(spec) if spec.contains(';') => {
+                let lists: Vec<&str> = spec.split(';').collect();
+                if lists.len() != num_keys {
+                    return fail_format!(
+                        "--normalize has transforms for {} key columns, but {num_keys} key \
+                         columns were selected.",
+                        lists.len()
+                    );
+                }
+                lists
+                    .into_iter()
+                    .map(parse_list)
+                    .collect::<Result<Vec<_>, _>>()?
+            },
+            Some(spec) => vec![parse_list(spec)?; num_keys],
+        };
+
+        Ok(KeyNormalizer {
+            pipelines,
+            casei,
+            prefer_dmy,
+            null_values,
+        })
+    }
+
+    #[inline]
+    fn row_key(&self, sel: &Selection, row: &csv::ByteRecord) -> Vec<ByteString> {
+        sel.select(row)
+            .zip(self.pipelines.iter())
+            .map(|(v, pipeline)| {
+                if self.null_values.is_null(v) {
+                    ByteString::new()
+                } else if pipeline.is_empty() {
+                    util::transform(v, self.casei)
+                } else {
+                    self.normalize(v, pipeline)
+                }
+            })
+            .collect()
+    }
+
+    fn normalize(&self, value: &[u8], pipeline: &[KeyTransform]) -> ByteString {
+        let Ok(s) = simdutf8::basic::from_utf8(value) else {
+            return util::transform(value, self.casei);
+        };
+        let mut work = s.trim().to_string();
+        for transform in pipeline {
+            work = match transform {
+                KeyTransform::Numeric => canonical_number(&work).unwrap_or(work),
+                KeyTransform::Date => match parse_with_preference(&work, self.prefer_dmy) {
+                    Ok(date) => date.to_rfc3339(),
+                    Err(_) => work,
+                },
+                KeyTransform::Nfkc => work.nfkc().collect(),
+                KeyTransform::Squeeze => work.split_whitespace().collect::<Vec<_>>().join(" "),
+                KeyTransform::Lower => work.to_lowercase(),
+            };
+        }
+        if self.casei {
+            work = work.to_lowercase();
+        }
+        work.into_bytes()
+    }
+}
+
+/// returns the canonical representation of a number, or None if its not a number
+fn canonical_number(s: &str) -> Option<String> {
+    let dec = Decimal::from_str(s)
+        .or_else(|_| Decimal::from_scientific(s))
+        .ok()?;
+    Some(dec.normalize().to_string())
 }
@@ -0,0 +1,416 @@
+static USAGE: &str = r#"
+Mask the values of columns using the rules in a rules file, e.g. to share a
+dataset with sensitive values partially hidden or generalised.
+
+The rules file is a CSV with the headers "column", "rule" & "arg". Each rule
+masks the columns selected by "column" (see 'qsv select --help' for the format
+details), using the "rule" below. "arg" is an optional parameter of the rule.
+A column can only be selected by one rule.
+
+  RULE       ARG                    MASKS A VALUE BY
+  keep-last  number of chars [4]    replacing all but the last chars with the mask
+                                    character. Values that are not longer than
+                                    the number of chars are fully masked.
+  digits     replacement char [#]   replacing every digit with the replacement char.
+  email      -                      masking the local part of an email address,
+                                    except its first character.
+  date       month or year [month]  generalising a date to its month (YYYY-MM)
+                                    or year (YYYY).
+  round      multiple [1]           rounding a number to the nearest multiple.
+  bucket     width [10]             replacing a number with the bucket it falls in,
+                                    as "lower-upper", where the lower bound is
+                                    inclusive & the upper bound is exclusive.
+  redact     replacement [REDACTED] replacing the whole value.
+  hash       length [64]            replacing the value with its SHA-256 hash,
+                                    as a hex string of the given length.
+
+Empty values are left empty. Values that cannot be masked by their rule (e.g. an
+invalid date for the date rule, or a non-number for the round rule) are redacted,
+with a count sent to stderr.
+
+Note that unkeyed hashes of values with few possible values (e.g. phone numbers)
+can be reversed by brute force. Use 'qsv pseudo --method hmac' for keyed hashes.
+
+The values are masked in a streaming fashion, so mask can process arbitrarily
+large files.
+
+EXAMPLES:
+
+With the following rules.csv:
+
+    column,rule,arg
+    card_number,keep-last,4
+    email,email,
+    birth_date,date,year
+    salary,round,1000
+    age,bucket,10
+    /^notes/,redact,
+
+    $ qsv mask rules.csv people.csv > masked.csv
+
+will mask these values:
+
+    card_number,email,birth_date,salary,age,notes
+    4111111111111111,jane.doe@example.com,1984-07-23,52875.50,37,allergic to nuts
+
+as:
+
+    card_number,email,birth_date,salary,age,notes
+    ************1111,j*******@example.com,1984,53000,30-40,REDACTED
+
+For more examples, see https://github.com/jqnatividad/qsv/blob/master/tests/test_mask.rs.
+
+Usage:
+    qsv mask [options] <rules> [<input>]
+    qsv mask --help
+
+mask arguments:
+    <rules>                The CSV file with the masking rules.
+    <input>                The CSV file to mask. Reads from stdin if not given.
+
+mask options:
+    --mask-char <char>     The character used to mask values by the keep-last &
+                           email rules. [default: *]
+    --prefer-dmy           Prefer to parse dates in dmy format.
+                           Otherwise, use mdy format.
+
+Common options:
+    -h, --help             Display this message
+    -o, --output <file>    Write output to <file> instead of stdout.
+    -n, --no-headers       When set, the first row will not be interpreted
+                           as headers. Rules must then select columns by index.
+    -d, --delimiter <arg>  The field delimiter for reading CSV data.
+                           Must be a single character. (default: ,)
+"#;
+
+use std::{fmt::Write, str::FromStr};
+
+use qsv_dateparser::parse_with_preference;
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use strum_macros::EnumString;
+
+use crate::{
+    config::{Config, Delimiter},
+    select::SelectColumns,
+    util, CliResult,
+};
+
+#[derive(Deserialize)]
+struct Args {
+    arg_rules:       String,
+    arg_input:       Option<String>,
+    flag_mask_char:  char,
+    flag_prefer_dmy: bool,
+    flag_output:     Option<String>,
+    flag_no_headers: bool,
+    flag_delimiter:  Option<Delimiter>,
+}
+
+/// a row of the rules file
+#[derive(Deserialize)]
+struct RuleRow {
+    column: String,
+    rule:   String,
+    #[serde(default)]
+    arg:    String,
+}
+
+#[derive(Clone, Copy, EnumString)]
+#[strum(ascii_case_insensitive)]
+enum RuleKind {
+    #[strum(serialize = "keep-last")]
+    KeepLast,
+    Digits,
+    Email,
+    Date,
+    Round,
+    Bucket,
+    Redact,
+    Hash,
+}
+
+#[derive(Clone, Copy, EnumString)]
+#[strum(ascii_case_insensitive)]
+enum DatePart {
+    Month,
+    Year,
+}
+
+#[derive(Clone)]
+enum Rule {
+    KeepLast(usize),
+    Digits(char),
+    Email,
+    Date(DatePart),
+    Round { multiple: f64, decimals: usize },
+    Bucket { width: f64, decimals: usize },
+    Redact(String),
+    Hash(usize),
+}
+
+const DEFAULT_REDACTION: &str = "REDACTED";
+
+impl Rule {
+    fn new(kind: RuleKind, arg: &str) -> Result<Self, String> {
+        let arg = arg.trim();
+        Ok(match kind {
+            RuleKind::KeepLast => Rule::KeepLast(parse_arg(arg, 4)?),
+            RuleKind::Digits => {
+                let mut chars = arg.chars();
+                match (chars.next(), chars.next()) {
+                    (None, _) => Rule::Digits('#'),
+                    (Some(c), None) => Rule::Digits(c),
+                    _ => return Err(format!("\"{arg}\" is not a single character")),
+                }
+            },
+            RuleKind::Email => Rule::Email,
+            RuleKind::Date => Rule::Date(if arg.is_empty() {
+                DatePart::Month
+            } else {
+                DatePart::from_str(arg)
+                    .map_err(|_| format!("\"{arg}\" is not one of month or year"))?
+            }),
+            RuleKind::Round => {
+                let multiple = parse_arg(arg, 1.0)?;
+                if multiple <= 0.0 {
+                    return Err("the multiple must be greater than zero".to_string());
+                }
+                Rule::Round {
+                    multiple,
+                    decimals: decimals(arg),
+                }
+            },
+            RuleKind::Bucket => {
+                let width = parse_arg(arg, 10.0)?;
+                if width <= 0.0 {
+                    return Err("the width must be greater than zero".to_string());
+                }
+                Rule::Bucket {
+                    width,
+                    decimals: decimals(arg),
+                }
+            },
+            RuleKind::Redact => Rule::Redact(if arg.is_empty() {
+                DEFAULT_REDACTION.to_string()
+            } else {
+                arg.to_string()
+            }),
+            RuleKind::Hash => {
+                let length = parse_arg(arg, 64)?;
+                if !(1..=64).contains(&length) {
+                    return Err("the length must be between 1 and 64".to_string());
+                }
+                Rule::Hash(length)
+            },
+        })
+    }
+
+    /// mask a non-empty value. Returns None if the value cannot be masked by the rule.
+    fn mask(&self, value: &str, mask_char: char, prefer_dmy: bool) -> Option<String> {
+        match self {
+            Rule::KeepLast(n) => {
+                let len = value.chars().count();
+                if len <= *n {
+                    Some(mask_char.to_string().repeat(len))
+                } else {
+                    let mut masked = mask_char.to_string().repeat(len - n);
+                    masked.extend(value.chars().skip(len - n));
+                    Some(masked)
+                }
+            },
+            Rule::Digits(c) => Some(
+                value
+                    .chars()
+                    .map(|ch| if ch.is_ascii_digit() { *c } else { ch })
+                    .collect(),
+            ),
+            Rule::Email => {
+                let (local, domain) = value.rsplit_once('@')?;
+                let mut chars = local.chars();
+                let first = chars.next()?;
+                let mut masked = String::with_capacity(value.len());
+                masked.push(first);
+                masked.extend(chars.map(|_| mask_char));
+                masked.push('@');
+                masked.push_str(domain);
+                Some(masked)
+            },
+            Rule::Date(part) => {
+                let date = parse_with_preference(value, prefer_dmy).ok()?;
+                Some(
+                    match part {
+                        DatePart::Month => date.format("%Y-%m"),
+                        DatePart::Year => date.format("%Y"),
+                    }
+                    .to_string(),
+                )
+            },
+            Rule::Round { multiple, decimals } => {
+                let number = parse_number(value)?;
+                let rounded = (number / multiple).round() * multiple;
+                Some(format!("{rounded:.decimals$}"))
+            },
+            Rule::Bucket { width, decimals } => {
+                let number = parse_number(value)?;
+                let lower = (number / width).floor() * width;
+                let upper = lower + width;
+                Some(format!("{lower:.decimals$}-{upper:.decimals$}"))
+            },
+            Rule::Redact(replacement) => Some(replacement.clone()),
+            Rule::Hash(length) => {
+                let mut hash = String::with_capacity(64);
+                for byte in Sha256::digest(value.as_bytes()) {
+                    // safety: writing to a String cannot fail
+                    write!(hash, "{byte:02x}").unwrap();
+                }
+                hash.truncate(*length);
+                Some(hash)
+            },
+        }
+    }
+}
+
+/// parse the arg of a rule, or use the default if it's empty
+fn parse_arg<T: FromStr>(arg: &str, default: T) -> Result<T, String> {
+    if arg.is_empty() {
+        Ok(default)
+    } else {
+        arg.parse::<T>()
+            .map_err(|_| format!("\"{arg}\" is not a valid number"))
+    }
+}
+
+/// the number of decimal places of a rule's numeric arg,
+/// so masked numbers are formatted with the same precision
+fn decimals(arg: &str) -> usize {
+    arg.split_once('.')
+        .map_or(0, |(_, fraction)| fraction.len())
+}
+
+fn parse_number(value: &str) -> Option<f64> {
+    value
+        .trim()
+        .parse::<f64>()
+        .ok()
+        .filter(|number| number.is_finite())
+}
+
+pub fn run(argv: &[&str]) -> CliResult<()> {
+    let args: Args = util::get_args(USAGE, argv)?;
+
+    let rconfig = Config::new(&args.arg_input)
+        .delimiter(args.flag_delimiter)
+        .no_headers(args.flag_no_headers);
+
+    let mut rdr = rconfig.reader()?;
+    let mut wtr = Config::new(&args.flag_output).writer()?;
+
+    let headers = rdr.byte_headers()?.clone();
+    let column_rules = read_rules(&args.arg_rules, &headers, !rconfig.no_headers)?;
+
+    let prefer_dmy = args.flag_prefer_dmy || rconfig.get_dmy_preference();
+
+    if !rconfig.no_headers {
+        wtr.write_record(&headers)?;
+    }
+
+    let mut redacted_count = 0_u64;
+    let mut record = csv::StringRecord::new();
+    let mut new_record = csv::StringRecord::new();
+    while rdr.read_record(&mut record)? {
+        new_record.clear();
+        for (i, value) in record.iter().enumerate() {
+            match column_rules.get(i) {
+                Some(Some(rule)) if !value.is_empty() => {
+                    if let Some(masked) = rule.mask(value, args.flag_mask_char, prefer_dmy) {
+                        new_record.push_field(&masked);
+                    } else {
+                        redacted_count += 1;
+                        new_record.push_field(DEFAULT_REDACTION);
+                    }
+                },
+                _ => new_record.push_field(value),
+            }
+        }
+        wtr.write_record(&new_record)?;
+    }
+
+    if redacted_count > 0 {
+        eprintln!("{redacted_count} value/s that could not be masked by their rule were redacted.");
+    }
+
+    Ok(wtr.flush()?)
+}
+
+/// read the rules file, and return the rule for each column of the input, if any
+fn read_rules(
+    path: &str,
+    headers: &csv::ByteRecord,
+    use_names: bool,
+) -> CliResult<Vec<Option<Rule>>> {
+    let mut rules_rdr = Config::new(&Some(path.to_string()))
+        .flexible(true)
+        .reader()?;
+
+    let mut column_rules: Vec<Option<Rule>> = (0..headers.len()).map(|_| None).collect();
+    for (i, row) in rules_rdr.deserialize::<RuleRow>().enumerate() {
+        // the header row of the rules file is line 1
+        let line = i + 2;
+        let row = match row {
+            Ok(row) => row,
+            Err(e) => {
+                return fail_incorrectusage_clierror!(
+                    "Invalid rule on line {line} of the rules file: {e}. The rules file must have \
+                     the headers \"column\", \"rule\" & \"arg\"."
+                )
+            },
+        };
+        let Ok(kind) = RuleKind::from_str(row.rule.trim()) else {
+            return fail_incorrectusage_clierror!(
+                "Invalid rule \"{}\" on line {line} of the rules file. It must be one of \
+                 keep-last, digits, email, date, round, bucket, redact or hash.",
+                row.rule
+            );
+        };
+        let rule = match Rule::new(kind, &row.arg) {
+            Ok(rule) => rule,
+            Err(e) => {
+                return fail_incorrectusage_clierror!(
+                    "Invalid arg for the {} rule on line {line} of the rules file: {e}.",
+                    row.rule.trim()
+                )
+            },
+        };
+        let selection = SelectColumns::parse(row.column.trim())
+            .and_then(|sel| sel.selection(headers, use_names))
+            .map_err(|e| format!("Invalid column on line {line} of the rules file: {e}"));
+        let selection = match selection {
+            Ok(selection) => selection,
+            Err(e) => return fail_incorrectusage_clierror!("{e}"),
+        };
+
+        for &idx in selection.iter() {
+            if column_rules[idx].is_some() {
+                return fail_incorrectusage_clierror!(
+                    "Column {} is selected by more than one rule (line {line} of the rules file).",
+                    column_name(headers, idx, use_names)
+                );
+            }
+            column_rules[idx] = Some(rule.clone());
+        }
+    }
+
+    if column_rules.iter().all(Option::is_none) {
+        return fail_incorrectusage_clierror!("The rules file does not select any columns.");
+    }
+    Ok(column_rules)
+}
+
+fn column_name(headers: &csv::ByteRecord, idx: usize, use_names: bool) -> String {
+    if use_names {
+        format!("\"{}\"", String::from_utf8_lossy(&headers[idx]))
+    } else {
+        (idx + 1).to_string()
+    }
+}
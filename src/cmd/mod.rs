@@ -12,6 +12,8 @@ pub mod dedup;
 pub mod describegpt;
 pub mod diff;
 #[cfg(any(feature = "feature_capable", feature = "lite"))]
+pub mod doctor;
+#[cfg(any(feature = "feature_capable", feature = "lite"))]
 pub mod enumerate;
 pub mod excel;
 pub mod exclude;
@@ -35,6 +37,10 @@ pub mod fmt;
 #[cfg(all(feature = "foreach", target_family = "unix", not(feature = "lite")))]
 pub mod foreach;
 pub mod frequency;
+#[cfg(all(feature = "fuzzy", feature = "feature_capable"))]
+pub mod fuzzydedup;
+#[cfg(all(feature = "fuzzy", feature = "feature_capable"))]
+pub mod fuzzyjoin;
 #[cfg(all(feature = "geocode", feature = "feature_capable"))]
 pub mod geocode;
 pub mod headers;
@@ -49,10 +55,18 @@ pub mod jsonl;
 #[cfg(feature = "luau")]
 pub mod luau;
 #[cfg(any(feature = "feature_capable", feature = "lite"))]
+pub mod mask;
+#[cfg(any(feature = "feature_capable", feature = "lite"))]
 pub mod partition;
+#[cfg(any(feature = "feature_capable", feature = "lite"))]
+pub mod patch;
+#[cfg(all(feature = "piiscan", feature = "feature_capable"))]
+pub mod piiscan;
 pub mod pseudo;
 #[cfg(all(feature = "python", feature = "feature_capable"))]
 pub mod python;
+#[cfg(any(feature = "feature_capable", feature = "lite"))]
+pub mod rangejoin;
 pub mod rename;
 pub mod replace;
 pub mod reverse;
@@ -60,6 +74,8 @@ pub mod safenames;
 pub mod sample;
 #[cfg(any(feature = "feature_capable", feature = "lite"))]
 pub mod schema;
+#[cfg(any(feature = "feature_capable", feature = "lite"))]
+pub mod schemadiff;
 pub mod search;
 pub mod searchset;
 pub mod select;
@@ -82,3 +98,5 @@ pub mod tojsonl;
 #[cfg(any(feature = "feature_capable", feature = "lite"))]
 pub mod transpose;
 pub mod validate;
+#[cfg(any(feature = "feature_capable", feature = "lite"))]
+pub mod window;
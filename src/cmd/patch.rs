@@ -0,0 +1,331 @@
+static USAGE: &str = r#"
+Apply the output of 'qsv diff' to a CSV, to produce the updated CSV.
+
+This lets you ship a small diff file instead of a full snapshot of a changed CSV:
+
+    $ qsv diff --key 0 old.csv new.csv > changes.csv
+    $ qsv patch --key 0 old.csv changes.csv > updated.csv
+
+The diff must be the default CSV output of 'qsv diff', made with the same --key.
+Rows are matched by key, and for each row of the diff:
+
+  * a row marked "-" followed by a row marked "+" with the same key is a modified row.
+    The base row is replaced with the "+" row.
+  * a row marked "-" only is a deleted row. The base row is removed.
+  * a row marked "+" only is an added row. It is appended after the base rows,
+    in the order of the diff.
+
+A change is only applied if the base is in the state the diff expects - a modified or
+deleted row must match the "-" row exactly, and the key of an added row must not be in
+the base yet. Otherwise, the change is a conflict and is not applied - the base row is
+kept as is. Each conflict is reported on stderr, and patch exits with a non-zero exit
+code after writing the patched CSV. Use --rejects to save the conflicting changes
+in the diff format, to review & apply them later.
+
+Note that the whole diff is loaded into memory, but the base CSV is streamed,
+so it can be arbitrarily large.
+
+For more examples, see https://github.com/jqnatividad/qsv/blob/master/tests/test_patch.rs.
+
+Usage:
+    qsv patch [options] <base> <diff>
+    qsv patch --help
+
+patch arguments:
+    <base>                 The CSV to patch. Use "-" to read from stdin.
+    <diff>                 The CSV output of 'qsv diff' to apply.
+
+patch options:
+    -k, --key <arg>        The column indices that uniquely identify a record
+                           as a comma separated list of indices, e.g. 0,1,2.
+                           Must be the same as the --key used by 'qsv diff'.
+                           (default: 0)
+    --rejects <file>       Write the changes that could not be applied because of
+                           conflicts to <file>, in the diff format.
+
+Common options:
+    -h, --help             Display this message
+    -o, --output <file>    Write output to <file> instead of stdout.
+    -n, --no-headers       When set, the first row of the base & of the diff
+                           will not be interpreted as headers.
+    -d, --delimiter <arg>  The field delimiter for reading the base & the diff.
+                           Must be a single character. (default: ,)
+"#;
+
+use std::collections::HashMap;
+
+use serde::Deserialize;
+
+use crate::{
+    clitypes::CliError,
+    config::{Config, Delimiter},
+    util, CliResult,
+};
+
+#[derive(Deserialize)]
+struct Args {
+    arg_base:        String,
+    arg_diff:        String,
+    flag_key:        Option<String>,
+    flag_rejects:    Option<String>,
+    flag_output:     Option<String>,
+    flag_no_headers: bool,
+    flag_delimiter:  Option<Delimiter>,
+}
+
+type Key = Vec<Vec<u8>>;
+
+/// the change to the row with a key, from one or two rows of the diff
+struct Change {
+    /// the row as it should be in the base (the "-" row), if the row is modified or deleted
+    old:     Option<csv::ByteRecord>,
+    /// the row as it should be after patching (the "+" row), if the row is added or modified
+    new:     Option<csv::ByteRecord>,
+    /// the position of the change in the diff
+    order:   usize,
+    handled: bool,
+}
+
+/// a change that could not be applied, with the reason
+struct Conflict {
+    key:    Key,
+    order:  usize,
+    reason: String,
+}
+
+pub fn run(argv: &[&str]) -> CliResult<()> {
+    let args: Args = util::get_args(USAGE, argv)?;
+
+    let key_cols = match args.flag_key {
+        None => vec![0],
+        Some(s) => s
+            .split(',')
+            .map(str::parse::<usize>)
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|err| CliError::Other(err.to_string()))?,
+    };
+
+    let base_config = Config::new(&Some(args.arg_base.clone()))
+        .delimiter(args.flag_delimiter)
+        .no_headers(args.flag_no_headers);
+    let diff_config = Config::new(&Some(args.arg_diff.clone()))
+        .delimiter(args.flag_delimiter)
+        .no_headers(args.flag_no_headers);
+    if diff_config.is_stdin() {
+        return fail_incorrectusage_clierror!(
+            "patch does not support reading the diff from stdin."
+        );
+    }
+
+    let mut base_rdr = base_config.reader()?;
+    let base_headers = base_rdr.byte_headers()?.clone();
+
+    let mut diff_rdr = diff_config.reader()?;
+    let diff_headers = diff_rdr.byte_headers()?.clone();
+    if !args.flag_no_headers && diff_headers.get(0) != Some(&b"diffresult"[..]) {
+        return fail_incorrectusage_clierror!(
+            "{} is not a diff file. Its first column must be \"diffresult\".",
+            args.arg_diff
+        );
+    }
+    if !args.flag_no_headers && diff_headers.len() != base_headers.len() + 1 {
+        return fail_incorrectusage_clierror!(
+            "The diff has {} columns, but the base has {}. The diff must be made from a CSV with \
+             the same columns as the base.",
+            diff_headers.len() - 1,
+            base_headers.len()
+        );
+    }
+    if let Some(&col) = key_cols.iter().find(|&&col| col >= base_headers.len()) {
+        return fail_incorrectusage_clierror!(
+            "Key column {col} is out of range. The base has {} columns.",
+            base_headers.len()
+        );
+    }
+
+    let key_of = |record: &csv::ByteRecord| -> Key {
+        key_cols
+            .iter()
+            .map(|&i| record.get(i).unwrap_or_default().to_vec())
+            .collect()
+    };
+
+    // read the diff
+    let mut changes: HashMap<Key, Change> = HashMap::new();
+    let mut diff_record = csv::ByteRecord::new();
+    let mut order = 0;
+    while diff_rdr.read_byte_record(&mut diff_record)? {
+        let sign = diff_record.get(0).unwrap_or_default().to_vec();
+        let record: csv::ByteRecord = diff_record.iter().skip(1).collect();
+        let key = key_of(&record);
+        match sign.as_slice() {
+            b"-" => {
+                if changes.contains_key(&key) {
+                    return fail_clierror!(
+                        "Invalid diff: more than one \"-\" row with the key {}.",
+                        key_text(&key)
+                    );
+                }
+                changes.insert(
+                    key,
+                    Change {
+                        old: Some(record),
+                        new: None,
+                        order,
+                        handled: false,
+                    },
+                );
+                order += 1;
+            },
+            b"+" => match changes.get_mut(&key) {
+                Some(change) if change.new.is_none() => change.new = Some(record),
+                Some(_) => {
+                    return fail_clierror!(
+                        "Invalid diff: more than one \"+\" row with the key {}.",
+                        key_text(&key)
+                    );
+                },
+                None => {
+                    changes.insert(
+                        key,
+                        Change {
+                            old: None,
+                            new: Some(record),
+                            order,
+                            handled: false,
+                        },
+                    );
+                    order += 1;
+                },
+            },
+            _ => {
+                return fail_clierror!(
+                    "Invalid diff: \"{}\" is not a valid diffresult. It must be \"+\" or \"-\".",
+                    String::from_utf8_lossy(&sign)
+                );
+            },
+        }
+    }
+
+    let mut wtr = Config::new(&args.flag_output).writer()?;
+    if !args.flag_no_headers {
+        wtr.write_byte_record(&base_headers)?;
+    }
+
+    // stream the base, applying the changes to the rows with a change
+    let mut conflicts: Vec<Conflict> = Vec::new();
+    let mut record = csv::ByteRecord::new();
+    while base_rdr.read_byte_record(&mut record)? {
+        let key = key_of(&record);
+        let Some(change) = changes.get_mut(&key) else {
+            wtr.write_byte_record(&record)?;
+            continue;
+        };
+        if change.handled {
+            // a duplicate key in the base. The change was applied to its first row.
+            wtr.write_byte_record(&record)?;
+            continue;
+        }
+        change.handled = true;
+        match &change.old {
+            None => {
+                conflicts.push(Conflict {
+                    key,
+                    order: change.order,
+                    reason: "the row to add is already in the base".to_string(),
+                });
+                wtr.write_byte_record(&record)?;
+            },
+            Some(old) if *old != record => {
+                let columns: Vec<String> = (0..old.len().max(record.len()))
+                    .filter(|&i| old.get(i) != record.get(i))
+                    .map(|i| column_name(&base_headers, i, args.flag_no_headers))
+                    .collect();
+                conflicts.push(Conflict {
+                    key,
+                    order: change.order,
+                    reason: format!(
+                        "the base row does not match the \"-\" row of the diff (columns: {})",
+                        columns.join(", ")
+                    ),
+                });
+                wtr.write_byte_record(&record)?;
+            },
+            Some(_) => {
+                // a modified row is replaced, a deleted row is not written
+                if let Some(new) = &change.new {
+                    wtr.write_byte_record(new)?;
+                }
+            },
+        }
+    }
+
+    // append the added rows, and report the modified & deleted rows not found in the base
+    let mut pending: Vec<(&Key, &Change)> = changes
+        .iter()
+        .filter(|(_, change)| !change.handled)
+        .collect();
+    pending.sort_unstable_by_key(|(_, change)| change.order);
+    for (key, change) in pending {
+        match (&change.old, &change.new) {
+            (None, Some(new)) => wtr.write_byte_record(new)?,
+            _ => conflicts.push(Conflict {
+                key:    key.clone(),
+                order:  change.order,
+                reason: "the row to change is not in the base".to_string(),
+            }),
+        }
+    }
+    wtr.flush()?;
+
+    if conflicts.is_empty() {
+        return Ok(());
+    }
+
+    conflicts.sort_unstable_by_key(|conflict| conflict.order);
+    for conflict in &conflicts {
+        eprintln!(
+            "Conflict for the key {}: {}.",
+            key_text(&conflict.key),
+            conflict.reason
+        );
+    }
+
+    if let Some(rejects) = args.flag_rejects {
+        let mut rejects_wtr = Config::new(&Some(rejects)).writer()?;
+        if !args.flag_no_headers {
+            rejects_wtr.write_byte_record(&diff_headers)?;
+        }
+        for conflict in &conflicts {
+            let change = &changes[&conflict.key];
+            for (sign, row) in [("-", &change.old), ("+", &change.new)] {
+                if let Some(row) = row {
+                    let mut diff_row = csv::ByteRecord::new();
+                    diff_row.push_field(sign.as_bytes());
+                    diff_row.extend(row);
+                    rejects_wtr.write_byte_record(&diff_row)?;
+                }
+            }
+        }
+        rejects_wtr.flush()?;
+    }
+
+    fail_clierror!(
+        "{} conflict/s - the conflicting changes were not applied.",
+        conflicts.len()
+    )
+}
+
+fn key_text(key: &Key) -> String {
+    key.iter()
+        .map(|value| String::from_utf8_lossy(value))
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+fn column_name(headers: &csv::ByteRecord, idx: usize, no_headers: bool) -> String {
+    match headers.get(idx) {
+        Some(name) if !no_headers => String::from_utf8_lossy(name).to_string(),
+        _ => idx.to_string(),
+    }
+}
@@ -0,0 +1,476 @@
+static USAGE: &str = r#"
+Scan CSV columns for personally identifiable information (PII), e.g. to check
+which columns contain personal data before publishing a dataset.
+
+Each non-empty value of the scanned columns is checked for these PII types:
+
+  email        an email address.
+  phone        a formatted phone number with 10 to 15 digits, e.g. +1 555-123-4567.
+  ssn          a US Social Security Number in AAA-GG-SSSS format, excluding
+               invalid area, group & serial numbers.
+  credit_card  a 13 to 19 digit card number with a valid Luhn check digit.
+  iban         an International Bank Account Number with valid check digits.
+  ip_address   an IPv4 or IPv6 address.
+  dob          a date in the last 120 years, i.e. a possible date of birth.
+  name         a value with one to four words, one of which is a known first name.
+
+For each column & PII type with at least one hit, a report row is returned with:
+
+  column       the column name (or its 1-based index with --no-headers).
+  pii_type     the PII type.
+  scanned      the number of non-empty values scanned.
+  hits         the number of values detected as the PII type.
+  hit_rate     hits / scanned.
+  header_hint  true if the column name suggests the PII type, e.g. "phone_number".
+  confidence   how confident piiscan is that the column holds the PII type,
+               from 0 to 1. It is the hit rate weighted by the precision of the
+               detector, as a date or a first name is weaker evidence of PII than
+               a valid credit card number. A header hint halves the remaining
+               uncertainty.
+
+The report is sorted by column, and then by descending confidence.
+
+For CI pipelines, use --fail-on to exit with a non-zero exit code if a column
+has a PII type with a confidence at or above the threshold. The report is still
+written before exiting.
+
+Examples:
+
+Scan all columns of data.csv:
+
+    $ qsv piiscan data.csv
+
+Only scan the first 10,000 rows of the "notes" & "contact" columns, as JSON:
+
+    $ qsv piiscan --select notes,contact --sample 10000 --json data.csv
+
+Fail a release if any column is likely to contain PII:
+
+    $ qsv piiscan --fail-on 0.5 data.csv
+
+For more examples, see https://github.com/jqnatividad/qsv/blob/master/tests/test_piiscan.rs.
+
+Usage:
+    qsv piiscan [options] [<input>]
+    qsv piiscan --help
+
+piiscan options:
+    -s, --select <arg>     Select the columns to scan. See 'qsv select --help'
+                           for the format details. By default, all columns
+                           are scanned.
+    --sample <n>           Only scan the first <n> rows. By default, all rows
+                           are scanned.
+    --json                 Return the report as a JSON array instead of CSV.
+    --fail-on <threshold>  Exit with a non-zero exit code if the confidence of a
+                           PII type of a column is at or above <threshold>.
+                           Must be between 0 and 1.
+    --prefer-dmy           Prefer to parse dates in dmy format.
+                           Otherwise, use mdy format.
+
+Common options:
+    -h, --help             Display this message
+    -o, --output <file>    Write output to <file> instead of stdout.
+    -n, --no-headers       When set, the first row will not be interpreted
+                           as headers.
+    -d, --delimiter <arg>  The field delimiter for reading CSV data.
+                           Must be a single character. (default: ,)
+"#;
+
+use std::{fs, net::IpAddr};
+
+use chrono::{Datelike, Utc};
+use gender_guesser::{Detector, Gender};
+use qsv_dateparser::parse_with_preference;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    config::{Config, Delimiter},
+    select::SelectColumns,
+    util, CliResult,
+};
+
+#[derive(Deserialize)]
+struct Args {
+    arg_input:       Option<String>,
+    flag_select:     Option<SelectColumns>,
+    flag_sample:     Option<u64>,
+    flag_json:       bool,
+    flag_fail_on:    Option<f64>,
+    flag_prefer_dmy: bool,
+    flag_output:     Option<String>,
+    flag_no_headers: bool,
+    flag_delimiter:  Option<Delimiter>,
+}
+
+#[derive(Clone, Copy)]
+enum PiiType {
+    Email,
+    Phone,
+    Ssn,
+    CreditCard,
+    Iban,
+    IpAddress,
+    Dob,
+    Name,
+}
+
+const PII_TYPES: [PiiType; 8] = [
+    PiiType::Email,
+    PiiType::Phone,
+    PiiType::Ssn,
+    PiiType::CreditCard,
+    PiiType::Iban,
+    PiiType::IpAddress,
+    PiiType::Dob,
+    PiiType::Name,
+];
+
+impl PiiType {
+    const fn name(self) -> &'static str {
+        match self {
+            PiiType::Email => "email",
+            PiiType::Phone => "phone",
+            PiiType::Ssn => "ssn",
+            PiiType::CreditCard => "credit_card",
+            PiiType::Iban => "iban",
+            PiiType::IpAddress => "ip_address",
+            PiiType::Dob => "dob",
+            PiiType::Name => "name",
+        }
+    }
+
+    /// the assumed precision of the detector, i.e. how likely a hit is really PII
+    const fn precision(self) -> f64 {
+        match self {
+            PiiType::Email | PiiType::CreditCard | PiiType::Iban => 1.0,
+            PiiType::Ssn | PiiType::IpAddress => 0.9,
+            PiiType::Phone => 0.8,
+            PiiType::Name => 0.6,
+            // most dates are not dates of birth
+            PiiType::Dob => 0.4,
+        }
+    }
+
+    /// the words in a column name that suggest the PII type. Words of up to three
+    /// characters must match a whole word of the column name, longer ones its start.
+    const fn header_keywords(self) -> &'static [&'static str] {
+        match self {
+            PiiType::Email => &["email", "e-mail", "mail"],
+            PiiType::Phone => &["phone", "tel", "mobile", "cell", "fax"],
+            PiiType::Ssn => &["ssn", "social"],
+            PiiType::CreditCard => &["card", "credit", "cc", "pan"],
+            PiiType::Iban => &["iban", "bank", "account"],
+            PiiType::IpAddress => &["ip", "ipv4", "ipv6"],
+            PiiType::Dob => &["dob", "birth", "born"],
+            PiiType::Name => &["name", "first", "last", "surname", "given", "fullname"],
+        }
+    }
+}
+
+/// a row of the report
+#[derive(Serialize)]
+struct Finding {
+    column:      String,
+    pii_type:    &'static str,
+    scanned:     u64,
+    hits:        u64,
+    hit_rate:    f64,
+    header_hint: bool,
+    confidence:  f64,
+}
+
+struct Detectors {
+    email:      Regex,
+    phone:      Regex,
+    ssn:        Regex,
+    iban:       Regex,
+    date:       Regex,
+    names:      Detector,
+    prefer_dmy: bool,
+    this_year:  i32,
+}
+
+impl Detectors {
+    fn new(prefer_dmy: bool) -> Self {
+        Self {
+            // safety: the regexes are valid
+            email: Regex::new(r"^[A-Za-z0-9._%+-]+@[A-Za-z0-9-]+(\.[A-Za-z0-9-]+)*\.[A-Za-z]{2,}$")
+                .unwrap(),
+            phone: Regex::new(r"^\+?[0-9 ().-]+$").unwrap(),
+            ssn: Regex::new(r"^([0-9]{3})-([0-9]{2})-([0-9]{4})$").unwrap(),
+            iban: Regex::new(r"^[A-Z]{2}[0-9]{2}[A-Z0-9]{11,30}$").unwrap(),
+            date: Regex::new(r"^[0-9]{1,4}[-/.][0-9]{1,2}[-/.][0-9]{1,4}([ T].*)?$").unwrap(),
+            names: Detector::new(),
+            prefer_dmy,
+            this_year: Utc::now().year(),
+        }
+    }
+
+    fn detect(&self, pii_type: PiiType, value: &str) -> bool {
+        match pii_type {
+            PiiType::Email => self.email.is_match(value),
+            PiiType::Phone => {
+                if !self.phone.is_match(value) {
+                    return false;
+                }
+                let digits = value.bytes().filter(u8::is_ascii_digit).count();
+                // a plain number is more likely an ID or an amount than a phone number
+                let formatted = value.starts_with('+') || value.contains([' ', '-', '.', '(']);
+                (10..=15).contains(&digits) && formatted
+            },
+            PiiType::Ssn => self.ssn.captures(value).is_some_and(|caps| {
+                let area = &caps[1];
+                area != "000"
+                    && area != "666"
+                    && !area.starts_with('9')
+                    && &caps[2] != "00"
+                    && &caps[3] != "0000"
+            }),
+            PiiType::CreditCard => {
+                if !value
+                    .bytes()
+                    .all(|b| b.is_ascii_digit() || b == b' ' || b == b'-')
+                {
+                    return false;
+                }
+                let digits: Vec<u8> = value
+                    .bytes()
+                    .filter(u8::is_ascii_digit)
+                    .map(|b| b - b'0')
+                    .collect();
+                // card numbers start with 2 (Mastercard), 3 (Amex, JCB, Diners),
+                // 4 (Visa), 5 (Mastercard) or 6 (Discover, UnionPay)
+                (13..=19).contains(&digits.len())
+                    && (2..=6).contains(&digits[0])
+                    && luhn_valid(&digits)
+            },
+            PiiType::Iban => {
+                let iban: String = value
+                    .chars()
+                    .filter(|c| *c != ' ')
+                    .map(|c| c.to_ascii_uppercase())
+                    .collect();
+                self.iban.is_match(&iban) && iban_valid(&iban)
+            },
+            PiiType::IpAddress => value.parse::<IpAddr>().is_ok(),
+            PiiType::Dob => {
+                self.date.is_match(value)
+                    && parse_with_preference(value, self.prefer_dmy).is_ok_and(|date| {
+                        let year = date.year();
+                        year <= self.this_year && year > self.this_year - 120 && date <= Utc::now()
+                    })
+            },
+            PiiType::Name => {
+                let words: Vec<&str> = value
+                    .split(|c: char| c.is_whitespace() || c == ',')
+                    .filter(|word| !word.is_empty())
+                    .collect();
+                (1..=4).contains(&words.len())
+                    && words.iter().any(|word| {
+                        word.chars().count() > 1
+                            && word.chars().all(char::is_alphabetic)
+                            && self.names.get_gender(&capitalize(word)) != Gender::NotFound
+                    })
+            },
+        }
+    }
+}
+
+/// check the Luhn check digit of a card number
+fn luhn_valid(digits: &[u8]) -> bool {
+    let sum: u32 = digits
+        .iter()
+        .rev()
+        .enumerate()
+        .map(|(i, &d)| {
+            let d = u32::from(d);
+            if i % 2 == 1 {
+                let doubled = d * 2;
+                if doubled > 9 {
+                    doubled - 9
+                } else {
+                    doubled
+                }
+            } else {
+                d
+            }
+        })
+        .sum();
+    sum % 10 == 0
+}
+
+/// check the ISO 7064 mod 97-10 check digits of an IBAN
+fn iban_valid(iban: &str) -> bool {
+    let (head, tail) = iban.split_at(4);
+    let mut remainder = 0_u32;
+    for c in tail.chars().chain(head.chars()) {
+        // digits are themselves, letters are 10 (A) to 35 (Z)
+        let Some(value) = c.to_digit(36) else {
+            return false;
+        };
+        remainder = if value < 10 {
+            (remainder * 10 + value) % 97
+        } else {
+            (remainder * 100 + value) % 97
+        };
+    }
+    remainder == 1
+}
+
+/// the name list is capitalized, e.g. "Mary"
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    chars.next().map_or_else(String::new, |first| {
+        first
+            .to_uppercase()
+            .chain(chars.flat_map(char::to_lowercase))
+            .collect()
+    })
+}
+
+fn header_hint(header: &str, pii_type: PiiType) -> bool {
+    let header = header.to_lowercase();
+    let words: Vec<&str> = header
+        .split(|c: char| !c.is_alphanumeric() && c != '-')
+        .filter(|word| !word.is_empty())
+        .collect();
+    pii_type.header_keywords().iter().any(|keyword| {
+        words.iter().any(|word| {
+            if keyword.len() <= 3 {
+                word == keyword
+            } else {
+                word.starts_with(keyword)
+            }
+        })
+    })
+}
+
+pub fn run(argv: &[&str]) -> CliResult<()> {
+    let args: Args = util::get_args(USAGE, argv)?;
+
+    if let Some(threshold) = args.flag_fail_on {
+        if !(0.0..=1.0).contains(&threshold) {
+            return fail_incorrectusage_clierror!("--fail-on must be between 0 and 1.");
+        }
+    }
+
+    let rconfig = Config::new(&args.arg_input)
+        .delimiter(args.flag_delimiter)
+        .no_headers(args.flag_no_headers)
+        .select(
+            args.flag_select
+                .clone()
+                .unwrap_or_else(|| SelectColumns::parse("").unwrap()),
+        );
+
+    let mut rdr = rconfig.reader()?;
+    let headers = rdr.byte_headers()?.clone();
+    let sel = match rconfig.selection(&headers) {
+        Ok(sel) => sel,
+        Err(e) => return fail_clierror!("{e}"),
+    };
+    let column_indices: Vec<usize> = sel.iter().copied().collect();
+
+    let prefer_dmy = args.flag_prefer_dmy || rconfig.get_dmy_preference();
+    let detectors = Detectors::new(prefer_dmy);
+
+    // the number of values scanned & the hits of each PII type, for each selected column
+    let mut scanned = vec![0_u64; column_indices.len()];
+    let mut hits = vec![[0_u64; PII_TYPES.len()]; column_indices.len()];
+
+    let sample_size = args.flag_sample.unwrap_or(u64::MAX);
+    let mut record = csv::StringRecord::new();
+    let mut row_count = 0_u64;
+    while row_count < sample_size && rdr.read_record(&mut record)? {
+        row_count += 1;
+        for (col, &idx) in column_indices.iter().enumerate() {
+            let value = record.get(idx).unwrap_or_default().trim();
+            if value.is_empty() {
+                continue;
+            }
+            scanned[col] += 1;
+            for (t, pii_type) in PII_TYPES.iter().enumerate() {
+                if detectors.detect(*pii_type, value) {
+                    hits[col][t] += 1;
+                }
+            }
+        }
+    }
+
+    // round the rates to 4 decimal places, keeping them as numbers in the JSON report
+    let round4 = |num: f64| util::round_num(num, 4).parse().unwrap_or(num);
+    let mut findings = Vec::new();
+    for (col, &idx) in column_indices.iter().enumerate() {
+        let column = if rconfig.no_headers {
+            (idx + 1).to_string()
+        } else {
+            String::from_utf8_lossy(&headers[idx]).to_string()
+        };
+        let mut column_findings = Vec::new();
+        for (t, pii_type) in PII_TYPES.iter().enumerate() {
+            if hits[col][t] == 0 {
+                continue;
+            }
+            #[allow(clippy::cast_precision_loss)]
+            let hit_rate = hits[col][t] as f64 / scanned[col] as f64;
+            let hint = !rconfig.no_headers && header_hint(&column, *pii_type);
+            let mut confidence = hit_rate * pii_type.precision();
+            if hint {
+                confidence += (1.0 - confidence) / 2.0;
+            }
+            column_findings.push(Finding {
+                column:      column.clone(),
+                pii_type:    pii_type.name(),
+                scanned:     scanned[col],
+                hits:        hits[col][t],
+                hit_rate:    round4(hit_rate),
+                header_hint: hint,
+                confidence:  round4(confidence),
+            });
+        }
+        column_findings.sort_by(|a, b| b.confidence.total_cmp(&a.confidence));
+        findings.extend(column_findings);
+    }
+
+    if args.flag_json {
+        let json = serde_json::to_string_pretty(&findings)?;
+        match args.flag_output {
+            Some(output) => fs::write(output, format!("{json}\n"))?,
+            None => println!("{json}"),
+        }
+    } else {
+        let mut wtr = Config::new(&args.flag_output).writer()?;
+        if findings.is_empty() {
+            wtr.write_record([
+                "column",
+                "pii_type",
+                "scanned",
+                "hits",
+                "hit_rate",
+                "header_hint",
+                "confidence",
+            ])?;
+        }
+        for finding in &findings {
+            wtr.serialize(finding)?;
+        }
+        wtr.flush()?;
+    }
+
+    if let Some(threshold) = args.flag_fail_on {
+        let failed: Vec<String> = findings
+            .iter()
+            .filter(|finding| finding.confidence >= threshold)
+            .map(|finding| format!("{} ({})", finding.column, finding.pii_type))
+            .collect();
+        if !failed.is_empty() {
+            return fail_clierror!(
+                "PII detected at or above the --fail-on threshold of {threshold}: {}",
+                failed.join(", ")
+            );
+        }
+    }
+
+    Ok(())
+}
@@ -1,20 +1,48 @@
 static USAGE: &str = r#"
-Pseudonymise the value of a given column by replacing it with an
-incremental identifier. See https://en.wikipedia.org/wiki/Pseudonymization
+Pseudonymise the values of the given columns. See https://en.wikipedia.org/wiki/Pseudonymization
 
 Once a value is pseudonymised, it will always be replaced with the same
 identifier. This means that the same value will always be replaced with
-the same identifier, even if it appears in different rows.
+the same identifier, even if it appears in different rows or in different
+selected columns.
 
-The incremental identifier is generated by using the given format string
-and the starting number and increment.
+There are three pseudonymisation methods:
 
-EXAMPLE:
+  incremental  Replace each value with an incremental identifier, generated using the
+               given format string, starting number and increment. The identifiers
+               depend on the order of the rows, and are only consistent within a run.
+  hmac         Replace each value with its keyed HMAC-SHA256 hash, as a hex string.
+               As the hash only depends on the value & the secret key, the same value
+               gets the same pseudonym across runs & files, so pseudonymised files can
+               still be joined. Without the key, the values cannot be recovered.
+  fpe          Format-preserving encryption of numeric IDs. Each value is encrypted
+               into a number with the same number of digits, so it still fits in the
+               same database column. Values must be at least 6 digits long.
+               Encrypted values can be decrypted with --reverse and the same key.
+               NOTE: this uses a qsv-specific Feistel cipher keyed with HMAC-SHA256.
+               It is not a standardised FPE mode (e.g. NIST FF1), so the encrypted
+               values can only be decrypted by qsv.
+
+The hmac & fpe methods use a secret key read from the environment variable named
+by the --key-env option (QSV_PSEUDO_KEY by default). Keep the key secret - anyone
+with the key can pseudonymise a known value to find it, and decrypt fpe pseudonyms.
+Empty values are left empty by the hmac & fpe methods.
+
+To be able to re-identify pseudonyms that cannot be decrypted (incremental & hmac),
+use --vault to also write the mapping to a vault file. The vault is a CSV with the
+pseudonym & the original value encrypted with AES-256-GCM, using a key derived from
+the secret key. New mappings are appended to an existing vault, which must have been
+created with the same secret key. A pseudonym that is already in the vault for another
+value is an error. As incremental identifiers restart at --start on every run, the
+incremental method requires a new or empty vault. Authorised users with the secret key
+can then re-identify the pseudonyms with --reverse.
+
+EXAMPLES:
 
 Pseudonymise the value of the "Name" column by replacing it with an
 incremental identifier starting at 1000 and incrementing by 5:
 
-    $ qsv pseudo Name --start 1000 --increment 5 --fmtstr "ID-{}" data.csv
+    $ qsv pseudo Name --start 1000 --increment 5 --formatstr "ID-{}" data.csv
 
 If run on the following CSV data:
 
@@ -36,22 +64,59 @@ If run on the following CSV data:
     ID-1005,magenta
     ID-1000,cyan
 
+Consistently pseudonymise the buyer & seller columns of several files with a keyed hash,
+keeping an encrypted vault to re-identify them later:
+
+    $ export QSV_PSEUDO_KEY="a long random secret"
+    $ qsv pseudo buyer,seller --method hmac --length 16 --vault people.vault jan.csv > jan_pseudo.csv
+    $ qsv pseudo buyer,seller --method hmac --length 16 --vault people.vault feb.csv > feb_pseudo.csv
+
+Re-identify them:
+
+    $ qsv pseudo buyer,seller --reverse --vault people.vault jan_pseudo.csv
+
+Encrypt a numeric customer ID, and decrypt it again:
+
+    $ qsv pseudo customer_id --method fpe data.csv > encrypted.csv
+    $ qsv pseudo customer_id --method fpe --reverse encrypted.csv
+
 For more examples, see https://github.com/jqnatividad/qsv/blob/master/tests/test_pseudo.rs.
 
 Usage:
     qsv pseudo [options] <column> [<input>]
     qsv pseudo --help
 
-Common options:
-    -h, --help              Display this message
+pseudo arguments:
+    <column>                The column/s to pseudonymise.
+                            See 'qsv select --help' for the format details.
+
+pseudo options:
+    -m, --method <arg>      The pseudonymisation method - incremental, hmac or fpe.
+                            [default: incremental]
     --start <number>        The starting number for the incremental identifier.
                             [default: 0]
     --increment <number>    The increment for the incremental identifier.
                             [default: 1]
-    --formatstr <template>  The format string for the incremental identifier.
-                            The format string must contain a single "{}" which
-                            will be replaced with the incremental identifier.
+    --formatstr <template>  The format string for the incremental identifier
+                            or the hmac hash. The format string must contain a
+                            single "{}" which will be replaced with the identifier.
                             [default: {}]
+    --length <n>            The number of hex characters of the hmac hash to keep.
+                            Shorter hashes are easier to read, but more likely to
+                            collide. Must be between 8 and 64. [default: 64]
+    --key-env <var>         The name of the environment variable with the secret key
+                            for the hmac & fpe methods & the vault.
+                            [default: QSV_PSEUDO_KEY]
+    --vault <file>          Append the mapping of the pseudonyms to their encrypted
+                            original values to <file>. With --reverse, use <file>
+                            to re-identify the pseudonyms.
+    --reverse               Re-identify the pseudonyms of the selected columns.
+                            fpe pseudonyms are decrypted. Other pseudonyms are
+                            looked up in the --vault. Pseudonyms that are not in the
+                            vault are left as is, with a count sent to stderr.
+
+Common options:
+    -h, --help              Display this message
     -o, --output <file>     Write output to <file> instead of stdout.
     -n, --no-headers        When set, the first row will not be interpreted
                             as headers.
@@ -59,132 +124,554 @@ Common options:
                             Must be a single character. (default: ,)
 "#;
 
+use std::{env, fs, io, path::Path, str::FromStr};
+
+use aes_gcm::{
+    aead::{Aead, KeyInit},
+    Aes256Gcm, Nonce,
+};
 use ahash::AHashMap;
 use dynfmt::Format;
+use hmac::{Hmac, Mac};
 use serde::Deserialize;
+use sha2::Sha256;
+use strum_macros::EnumString;
 
 use crate::{
     config::{Config, Delimiter},
     select::SelectColumns,
-    util,
-    util::replace_column_value,
-    CliResult,
+    util, CliError, CliResult,
 };
 
 #[derive(Deserialize)]
 struct Args {
     arg_column:      SelectColumns,
     arg_input:       Option<String>,
+    flag_method:     String,
     flag_start:      u64,
     flag_increment:  u64,
     flag_formatstr:  String,
+    flag_length:     usize,
+    flag_key_env:    String,
+    flag_vault:      Option<String>,
+    flag_reverse:    bool,
     flag_output:     Option<String>,
     flag_no_headers: bool,
     flag_delimiter:  Option<Delimiter>,
 }
 
-type Values = AHashMap<String, String>;
-type ValuesNum = AHashMap<String, u64>;
+#[derive(Clone, Copy, EnumString, PartialEq)]
+#[strum(ascii_case_insensitive)]
+enum Method {
+    Incremental,
+    Hmac,
+    Fpe,
+}
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// the minimum number of digits of a value to encrypt with fpe,
+/// as smaller domains are too easy to enumerate
+const FPE_MIN_DIGITS: usize = 6;
+/// the maximum number of digits, so each half of the value fits in a u64
+const FPE_MAX_DIGITS: usize = 36;
+const FPE_ROUNDS: u8 = 10;
 
 pub fn run(argv: &[&str]) -> CliResult<()> {
     let args: Args = util::get_args(USAGE, argv)?;
+
+    let Ok(method) = Method::from_str(&args.flag_method) else {
+        return fail_incorrectusage_clierror!(
+            "Invalid --method `{}`. It must be one of incremental, hmac or fpe.",
+            args.flag_method
+        );
+    };
+    if !(8..=64).contains(&args.flag_length) {
+        return fail_incorrectusage_clierror!("--length must be between 8 and 64.");
+    }
+    if args.flag_formatstr != "{}"
+        && (!args.flag_formatstr.contains("{}")
+            || dynfmt::SimpleCurlyFormat
+                .format(&args.flag_formatstr, [0])
+                .is_err())
+    {
+        return fail_incorrectusage_clierror!(
+            "Invalid format string: \"{}\". The format string must contain a single \"{{}}\" \
+             which will be replaced with the incremental identifier.",
+            args.flag_formatstr
+        );
+    }
+    if args.flag_reverse && method != Method::Fpe && args.flag_vault.is_none() {
+        return fail_incorrectusage_clierror!(
+            "--reverse requires a --vault, unless the values were pseudonymised with the fpe \
+             method."
+        );
+    }
+
     let rconfig = Config::new(&args.arg_input)
         .delimiter(args.flag_delimiter)
         .no_headers(args.flag_no_headers)
-        .select(args.arg_column);
+        .select(args.arg_column.clone());
 
     let mut rdr = rconfig.reader()?;
     let mut wtr = Config::new(&args.flag_output).writer()?;
 
     let headers = rdr.byte_headers()?.clone();
-    let column_index = match rconfig.selection(&headers) {
-        Ok(sel) => {
-            let sel_len = sel.len();
-            if sel_len > 1 {
-                return fail_incorrectusage_clierror!(
-                    "{sel_len} columns selected. Only one column can be selected for \
-                     pseudonymisation."
-                );
-            }
-            // safety: we checked that sel.len() == 1
-            *sel.iter().next().unwrap()
-        },
+    let sel = match rconfig.selection(&headers) {
+        Ok(sel) => sel,
         Err(e) => return fail_clierror!("{e}"),
     };
+    let column_indices: Vec<usize> = sel.iter().copied().collect();
+
+    // the secret key is only needed by the keyed methods & the vault
+    let key = if method != Method::Incremental || args.flag_vault.is_some() {
+        Some(secret_key(&args.flag_key_env)?)
+    } else {
+        None
+    };
+
+    let mut pseudonymiser = if args.flag_reverse {
+        if method == Method::Fpe {
+            // safety: the key is always set for the fpe method
+            Pseudonymiser::FpeDecrypt(Fpe::new(key.as_deref().unwrap()))
+        } else {
+            // safety: --reverse without fpe requires a vault, so the key is set
+            let vault = Vault::new(args.flag_vault.as_deref().unwrap(), key.as_deref().unwrap());
+            Pseudonymiser::Vault {
+                originals: vault.read()?,
+                not_found: 0,
+            }
+        }
+    } else {
+        let vault = match &args.flag_vault {
+            // safety: the key is always set when there's a vault
+            Some(path) => Some(Vault::new(path, key.as_deref().unwrap()).writer()?),
+            None => None,
+        };
+        if method == Method::Incremental && vault.as_ref().is_some_and(|v| !v.is_empty()) {
+            return fail_incorrectusage_clierror!(
+                "The incremental method requires a new or empty --vault, as its identifiers \
+                 restart at --start on every run. Use the hmac method for pseudonyms that are \
+                 consistent across runs."
+            );
+        }
+        let formatstr = if args.flag_formatstr == "{}" {
+            None
+        } else {
+            Some(args.flag_formatstr.clone())
+        };
+        match method {
+            Method::Incremental => Pseudonymiser::Incremental {
+                values: AHashMap::with_capacity(1000),
+                counter: args.flag_start,
+                increment: args.flag_increment,
+                formatstr,
+                vault,
+            },
+            Method::Hmac => Pseudonymiser::Hmac {
+                // safety: the key is always set for the hmac method,
+                // and HMAC accepts keys of any length
+                mac: HmacSha256::new_from_slice(&derive_key(
+                    key.as_deref().unwrap(),
+                    "qsv-pseudo-hmac",
+                ))
+                .unwrap(),
+                length: args.flag_length,
+                formatstr,
+                vault,
+            },
+            Method::Fpe => Pseudonymiser::FpeEncrypt {
+                fpe: Fpe::new(key.as_deref().unwrap()),
+                vault,
+            },
+        }
+    };
 
     if !rconfig.no_headers {
         wtr.write_record(&headers)?;
     }
 
     let mut record = csv::StringRecord::new();
-    let mut counter: u64 = args.flag_start;
-    let increment = args.flag_increment;
-    let mut curr_counter: u64 = 0;
-    let mut overflowed = false;
-
-    if args.flag_formatstr == "{}" {
-        // we don't need to use dynfmt::SimpleCurlyFormat if the format string is "{}"
-        let mut values_num = ValuesNum::with_capacity(1000);
-
-        while rdr.read_record(&mut record)? {
-            let value = record[column_index].to_owned();
-            let new_value = values_num.entry(value.clone()).or_insert_with(|| {
-                curr_counter = counter;
-                (counter, overflowed) = counter.overflowing_add(increment);
-                curr_counter
-            });
-            if overflowed {
-                return fail_incorrectusage_clierror!(
-                    "Overflowed. The counter is larger than u64::MAX {}. The last valid counter \
-                     is {curr_counter}.",
-                    u64::MAX
-                );
+    let mut new_record = csv::StringRecord::new();
+    while rdr.read_record(&mut record)? {
+        new_record.clear();
+        for (i, value) in record.iter().enumerate() {
+            if column_indices.contains(&i) {
+                new_record.push_field(&pseudonymiser.pseudonymise(value)?);
+            } else {
+                new_record.push_field(value);
             }
-            record = replace_column_value(&record, column_index, &new_value.to_string());
+        }
+        wtr.write_record(&new_record)?;
+    }
+
+    match pseudonymiser {
+        Pseudonymiser::Incremental { vault, .. }
+        | Pseudonymiser::Hmac { vault, .. }
+        | Pseudonymiser::FpeEncrypt { vault, .. } => {
+            if let Some(mut vault) = vault {
+                vault.flush()?;
+            }
+        },
+        Pseudonymiser::Vault { not_found, .. } => {
+            if not_found > 0 {
+                eprintln!("{not_found} pseudonym/s not found in the vault were left as is.");
+            }
+        },
+        Pseudonymiser::FpeDecrypt(_) => {},
+    }
+
+    Ok(wtr.flush()?)
+}
+
+/// read the secret key from the environment variable `key_env`
+fn secret_key(key_env: &str) -> CliResult<Vec<u8>> {
+    match env::var(key_env) {
+        Ok(key) if !key.is_empty() => Ok(key.into_bytes()),
+        _ => fail_incorrectusage_clierror!(
+            "The {key_env} environment variable with the secret key is not set or is empty."
+        ),
+    }
+}
+
+/// derive a purpose-specific 32-byte key from the secret key,
+/// so the hmac, fpe & vault keys are independent of each other
+fn derive_key(secret: &[u8], purpose: &str) -> [u8; 32] {
+    // safety: HMAC accepts keys of any length
+    let mut mac = HmacSha256::new_from_slice(secret).unwrap();
+    mac.update(purpose.as_bytes());
+    let mut key = [0_u8; 32];
+    key.copy_from_slice(&mac.finalize().into_bytes()[..32]);
+    key
+}
+
+enum Pseudonymiser {
+    Incremental {
+        values:    AHashMap<String, String>,
+        counter:   u64,
+        increment: u64,
+        formatstr: Option<String>,
+        vault:     Option<VaultWriter>,
+    },
+    Hmac {
+        mac:       HmacSha256,
+        length:    usize,
+        formatstr: Option<String>,
+        vault:     Option<VaultWriter>,
+    },
+    FpeEncrypt {
+        fpe:   Fpe,
+        vault: Option<VaultWriter>,
+    },
+    FpeDecrypt(Fpe),
+    Vault {
+        originals: AHashMap<String, String>,
+        not_found: u64,
+    },
+}
+
+impl Pseudonymiser {
+    fn pseudonymise(&mut self, value: &str) -> CliResult<String> {
+        let (pseudonym, vault) = match self {
+            Pseudonymiser::Incremental {
+                values,
+                counter,
+                increment,
+                formatstr,
+                vault,
+            } => {
+                if let Some(pseudonym) = values.get(value) {
+                    return Ok(pseudonym.clone());
+                }
+                let curr_counter = *counter;
+                let (next_counter, overflowed) = counter.overflowing_add(*increment);
+                if overflowed {
+                    return fail_incorrectusage_clierror!(
+                        "Overflowed. The counter is larger than u64::MAX({}). The last valid \
+                         counter is {curr_counter}.",
+                        u64::MAX
+                    );
+                }
+                *counter = next_counter;
+                let pseudonym = format_pseudonym(formatstr.as_deref(), curr_counter);
+                values.insert(value.to_owned(), pseudonym.clone());
+                (pseudonym, vault)
+            },
+            Pseudonymiser::Hmac {
+                mac,
+                length,
+                formatstr,
+                vault,
+            } => {
+                if value.is_empty() {
+                    return Ok(String::new());
+                }
+                let mut mac = mac.clone();
+                mac.update(value.as_bytes());
+                let mut hash = hex_encode(&mac.finalize().into_bytes()[..32]);
+                hash.truncate(*length);
+                (format_pseudonym(formatstr.as_deref(), hash), vault)
+            },
+            Pseudonymiser::FpeEncrypt { fpe, vault } => {
+                if value.is_empty() {
+                    return Ok(String::new());
+                }
+                (fpe.encrypt(value)?, vault)
+            },
+            Pseudonymiser::FpeDecrypt(fpe) => {
+                if value.is_empty() {
+                    return Ok(String::new());
+                }
+                return Ok(fpe.decrypt(value)?);
+            },
+            Pseudonymiser::Vault {
+                originals,
+                not_found,
+            } => {
+                if value.is_empty() {
+                    return Ok(String::new());
+                }
+                return Ok(match originals.get(value) {
+                    Some(original) => original.clone(),
+                    None => {
+                        *not_found += 1;
+                        value.to_owned()
+                    },
+                });
+            },
+        };
 
-            wtr.write_record(&record)?;
+        if let Some(vault) = vault {
+            vault.add(&pseudonym, value)?;
         }
-    } else {
-        // we need to use dynfmt::SimpleCurlyFormat if the format string is not "{}"
+        Ok(pseudonym)
+    }
+}
 
-        // first, validate the format string
-        if !args.flag_formatstr.contains("{}")
-            || dynfmt::SimpleCurlyFormat
-                .format(&args.flag_formatstr, [0])
-                .is_err()
-        {
-            return fail_incorrectusage_clierror!(
-                "Invalid format string: \"{}\". The format string must contain a single \"{{}}\" \
-                 which will be replaced with the incremental identifier.",
-                args.flag_formatstr
-            );
+fn format_pseudonym(formatstr: Option<&str>, id: impl ToString + serde::Serialize) -> String {
+    match formatstr {
+        // safety: the format string was validated
+        Some(formatstr) => dynfmt::SimpleCurlyFormat
+            .format(formatstr, [id])
+            .unwrap()
+            .to_string(),
+        None => id.to_string(),
+    }
+}
+
+/// Format-preserving encryption of decimal numbers of FPE_MIN_DIGITS to FPE_MAX_DIGITS
+/// digits, using a balanced 10-round Feistel network over the two halves of the
+/// digits, with an HMAC-SHA256 round function and modular addition.
+/// This is a qsv-specific scheme - it is NOT NIST SP 800-38G FF1/FF3-1, so its
+/// output is not interoperable with standard FPE implementations.
+/// The number of digits is used as a tweak, so values of different lengths are
+/// encrypted independently. Leading zeros are preserved.
+struct Fpe {
+    mac: HmacSha256,
+}
+
+impl Fpe {
+    fn new(secret: &[u8]) -> Self {
+        // safety: HMAC accepts keys of any length
+        Fpe {
+            mac: HmacSha256::new_from_slice(&derive_key(secret, "qsv-pseudo-fpe")).unwrap(),
+        }
+    }
+
+    /// split `value` into its two halves, checking it can be encrypted
+    fn split(value: &str) -> Result<(u64, u64, u32, u32), String> {
+        let len = value.len();
+        if !value.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(format!(
+                "\"{value}\" cannot be encrypted with fpe as it is not numeric."
+            ));
         }
+        if !(FPE_MIN_DIGITS..=FPE_MAX_DIGITS).contains(&len) {
+            return Err(format!(
+                "\"{value}\" cannot be encrypted with fpe. Values must have {FPE_MIN_DIGITS} to \
+                 {FPE_MAX_DIGITS} digits."
+            ));
+        }
+        let u = len / 2;
+        // safety: we checked the value only has digits, and each half fits in a u64
+        let a = value[..u].parse::<u64>().unwrap();
+        let b = value[u..].parse::<u64>().unwrap();
+        Ok((a, b, u as u32, (len - u) as u32))
+    }
+
+    /// the round function - a pseudorandom number below 10^digits
+    fn round(&self, len: usize, round: u8, half: u64, digits: u32) -> u64 {
+        let mut mac = self.mac.clone();
+        mac.update(&[len as u8, round]);
+        mac.update(&half.to_le_bytes());
+        let hash = mac.finalize().into_bytes();
+        let mut y = [0_u8; 16];
+        y.copy_from_slice(&hash[..16]);
+        (u128::from_le_bytes(y) % 10_u128.pow(digits)) as u64
+    }
+
+    fn encrypt(&self, value: &str) -> Result<String, String> {
+        let (mut a, mut b, u, v) = Fpe::split(value)?;
+        for i in 0..FPE_ROUNDS {
+            let m = if i % 2 == 0 { u } else { v };
+            let modulus = 10_u64.pow(m);
+            let c = (a % modulus + self.round(value.len(), i, b, m)) % modulus;
+            a = b;
+            b = c;
+        }
+        Ok(format!("{a:0u$}{b:0v$}", u = u as usize, v = v as usize))
+    }
+
+    fn decrypt(&self, value: &str) -> Result<String, String> {
+        let (mut a, mut b, u, v) = Fpe::split(value)?;
+        for i in (0..FPE_ROUNDS).rev() {
+            let m = if i % 2 == 0 { u } else { v };
+            let modulus = 10_u64.pow(m);
+            let c = b;
+            b = a;
+            a = (c + modulus - self.round(value.len(), i, b, m)) % modulus;
+        }
+        Ok(format!("{a:0u$}{b:0v$}", u = u as usize, v = v as usize))
+    }
+}
 
-        let mut values = Values::with_capacity(1000);
-        while rdr.read_record(&mut record)? {
-            let value = record[column_index].to_owned();
-
-            // safety: we checked that the format string contains "{}"
-            let new_value = values.entry(value.clone()).or_insert_with(|| {
-                curr_counter = counter;
-                (counter, overflowed) = counter.overflowing_add(increment);
-                dynfmt::SimpleCurlyFormat
-                    .format(&args.flag_formatstr, [curr_counter])
-                    .unwrap()
-                    .to_string()
-            });
-            if overflowed {
-                return fail_incorrectusage_clierror!(
-                    "Overflowed. The counter is larger than u64::MAX({}). The last valid counter \
-                     is {curr_counter}.",
-                    u64::MAX
+/// A CSV file mapping pseudonyms to their original values,
+/// encrypted with AES-256-GCM & a random nonce per value.
+struct Vault<'a> {
+    path:   &'a Path,
+    cipher: Aes256Gcm,
+}
+
+const VAULT_HEADERS: [&str; 2] = ["pseudonym", "encrypted_value"];
+const NONCE_LEN: usize = 12;
+
+impl<'a> Vault<'a> {
+    fn new(path: &'a str, secret: &[u8]) -> Self {
+        // safety: the derived key is always 32 bytes long
+        let cipher = Aes256Gcm::new_from_slice(&derive_key(secret, "qsv-pseudo-vault")).unwrap();
+        Vault {
+            path: Path::new(path),
+            cipher,
+        }
+    }
+
+    /// decrypt the vault into a map of the pseudonyms to their original values
+    fn read(&self) -> CliResult<AHashMap<String, String>> {
+        let mut rdr = csv::Reader::from_path(self.path)?;
+        let mut originals = AHashMap::new();
+        for record in rdr.records() {
+            let record = record?;
+            let (Some(pseudonym), Some(encrypted)) = (record.get(0), record.get(1)) else {
+                return fail_clierror!("Invalid vault {}.", self.path.display());
+            };
+            let Some(original) = hex_decode(encrypted)
+                .filter(|bytes| bytes.len() > NONCE_LEN)
+                .and_then(|bytes| {
+                    let (nonce, ciphertext) = bytes.split_at(NONCE_LEN);
+                    self.cipher
+                        .decrypt(Nonce::from_slice(nonce), ciphertext)
+                        .ok()
+                })
+                .and_then(|plaintext| String::from_utf8(plaintext).ok())
+            else {
+                return fail_clierror!(
+                    "Cannot decrypt the vault {}. Is the secret key correct?",
+                    self.path.display()
                 );
-            }
+            };
+            originals.insert(pseudonym.to_owned(), original);
+        }
+        Ok(originals)
+    }
+
+    /// open the vault for appending, creating it if it doesn't exist
+    fn writer(self) -> CliResult<VaultWriter> {
+        // the mappings already in the vault, so they're not added again. Decrypting them
+        // also checks the vault was created with the same secret key.
+        let exists = self.path.exists() && fs::metadata(self.path)?.len() > 0;
+        let originals = if exists {
+            self.read()?
+        } else {
+            AHashMap::new()
+        };
 
-            record = replace_column_value(&record, column_index, new_value);
-            wtr.write_record(&record)?;
+        let file = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(self.path)?;
+        let mut wtr = csv::Writer::from_writer(io::BufWriter::new(file));
+        if !exists {
+            wtr.write_record(VAULT_HEADERS)?;
         }
+        Ok(VaultWriter {
+            path: self.path.display().to_string(),
+            cipher: self.cipher,
+            originals,
+            wtr,
+        })
     }
+}
 
-    Ok(wtr.flush()?)
+struct VaultWriter {
+    path:      String,
+    cipher:    Aes256Gcm,
+    originals: AHashMap<String, String>,
+    wtr:       csv::Writer<io::BufWriter<fs::File>>,
+}
+
+impl VaultWriter {
+    /// true if the vault has no mappings yet
+    fn is_empty(&self) -> bool {
+        self.originals.is_empty()
+    }
+
+    fn add(&mut self, pseudonym: &str, value: &str) -> CliResult<()> {
+        if let Some(original) = self.originals.get(pseudonym) {
+            if original == value {
+                return Ok(());
+            }
+            return fail_clierror!(
+                "The pseudonym \"{pseudonym}\" is already in the vault {} for another value, so \
+                 it could not be re-identified. If using hmac, increase --length.",
+                self.path
+            );
+        }
+        let nonce: [u8; NONCE_LEN] = rand::random();
+        let ciphertext = self
+            .cipher
+            .encrypt(Nonce::from_slice(&nonce), value.as_bytes())
+            .map_err(|e| CliError::Other(format!("Cannot encrypt into the vault: {e}")))?;
+        let mut encrypted = hex_encode(&nonce);
+        encrypted.push_str(&hex_encode(&ciphertext));
+        self.wtr.write_record([pseudonym, &encrypted])?;
+        self.originals
+            .insert(pseudonym.to_owned(), value.to_owned());
+        Ok(())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.wtr.flush()?;
+        self.wtr.get_ref().get_ref().sync_all()
+    }
+}
+
+fn hex_encode(bytes: &[u8]) -> String {
+    use std::fmt::Write as _;
+
+    let mut hex = String::with_capacity(bytes.len() * 2);
+    for b in bytes {
+        // safety: writing to a String cannot fail
+        write!(hex, "{b:02x}").unwrap();
+    }
+    hex
+}
+
+fn hex_decode(hex: &str) -> Option<Vec<u8>> {
+    if hex.len() % 2 != 0 {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+        .collect()
 }
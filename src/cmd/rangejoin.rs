@@ -0,0 +1,349 @@
+static USAGE: &str = r#"
+Joins the rows of <input1> to the rows of <input2> whose range contains a value.
+
+For every row in <input1>, the <value-column> is matched against the ranges of
+<input2> formed by the <start-column> and <end-column>. Every row of <input2> whose
+range contains the value is joined to the row. This is useful to attach a row to the
+interval it falls in, e.g. an IP address to the IP block it belongs to, a timestamp
+to a shift or billing period, or a date to date-effective reference data.
+
+The values and the range bounds are compared as the given --type:
+  numeric  Integers & floats.
+  date     Dates & datetimes recognized by qsv-dateparser.
+           Set the QSV_PREFER_DMY environment variable to parse ambiguous dates
+           as day/month/year.
+  ip       IPv4 & IPv6 addresses. IPv4 addresses are compared as IPv4-mapped
+           IPv6 addresses, so IPv4 values can also be matched against IPv6 ranges.
+
+By default, ranges include their start & exclude their end - i.e. [start, end).
+Use --closed to change which bounds are inclusive. An empty start or end is
+unbounded, e.g. a date-effective row without an end date matches every date after
+its start date. Rows of <input2> whose bounds cannot be parsed, or whose start is
+after their end, are ignored. Rows of <input1> with an empty or unparseable value
+never match.
+
+<input2> is loaded into memory as an interval tree, so lookups stay fast even with
+many overlapping ranges. <input1> is streamed. When a value is in multiple ranges,
+the matching rows of <input2> are joined in the order they appear in <input2>.
+
+Examples:
+Attach the network block & owner to each IPv4 address in a log:
+    $ qsv rangejoin --type ip client_ip access_log.csv block_start block_end ipblocks.csv
+
+Get the tax rate in effect on each invoice date, keeping invoices without a rate:
+    $ qsv rangejoin --type date --left --closed both invoice_date invoices.csv \
+        effective_from effective_to taxrates.csv
+
+For more examples, see https://github.com/jqnatividad/qsv/blob/master/tests/test_rangejoin.rs.
+
+Usage:
+    qsv rangejoin [options] <value-column> <input1> <start-column> <end-column> <input2>
+    qsv rangejoin --help
+
+input parameters:
+    For <input1> and <input2>, specifying `-` indicates reading from stdin.
+    Only one of the inputs can be stdin.
+
+rangejoin options:
+    -t, --type <arg>       The type of the values & range bounds: numeric, date or ip.
+                           [default: numeric]
+    --closed <arg>         Which bounds of the ranges are inclusive:
+                           left (start <= value < end), right (start < value <= end),
+                           both (start <= value <= end) or
+                           neither (start < value < end). [default: left]
+    --left                 Do a 'left outer' join. Rows in <input1> without a
+                           matching range are also written, padded with empty fields.
+
+Common options:
+    -h, --help             Display this message
+    -o, --output <file>    Write output to <file> instead of stdout.
+    -n, --no-headers       When set, the first row will not be interpreted
+                           as headers. (i.e., They are not searched, analyzed,
+                           sliced, etc.)
+    -d, --delimiter <arg>  The field delimiter for reading CSV data.
+                           Must be a single character. (default: ,)
+"#;
+
+use std::{iter::repeat, net::IpAddr, str, str::FromStr};
+
+use qsv_dateparser::parse_with_preference;
+use serde::Deserialize;
+use strum_macros::EnumString;
+
+use crate::{
+    config::{Config, Delimiter},
+    select::SelectColumns,
+    util, CliResult,
+};
+
+#[derive(Deserialize)]
+struct Args {
+    arg_value_column: SelectColumns,
+    arg_input1:       String,
+    arg_start_column: SelectColumns,
+    arg_end_column:   SelectColumns,
+    arg_input2:       String,
+    flag_type:        String,
+    flag_closed:      String,
+    flag_left:        bool,
+    flag_output:      Option<String>,
+    flag_no_headers:  bool,
+    flag_delimiter:   Option<Delimiter>,
+}
+
+#[derive(Clone, Copy, EnumString)]
+#[strum(ascii_case_insensitive)]
+enum RangeType {
+    Numeric,
+    Date,
+    Ip,
+}
+
+#[derive(Clone, Copy, EnumString)]
+#[strum(ascii_case_insensitive)]
+enum Closed {
+    Left,
+    Right,
+    Both,
+    Neither,
+}
+
+impl Closed {
+    #[inline]
+    fn after_start<T: PartialOrd>(self, start: &T, value: &T) -> bool {
+        match self {
+            Closed::Left | Closed::Both => start <= value,
+            Closed::Right | Closed::Neither => start < value,
+        }
+    }
+
+    #[inline]
+    fn before_end<T: PartialOrd>(self, end: &T, value: &T) -> bool {
+        match self {
+            Closed::Right | Closed::Both => value <= end,
+            Closed::Left | Closed::Neither => value < end,
+        }
+    }
+}
+
+/// A range bound. Empty bounds are unbounded, and sort before & after every value.
+#[derive(Clone, Copy, PartialEq, PartialOrd)]
+enum Bound<T> {
+    Min,
+    Value(T),
+    Max,
+}
+
+/// A static, augmented interval tree. The intervals are sorted by start, and form
+/// an implicit balanced binary search tree where the node of the subrange `lo..hi`
+/// is at its midpoint. Each node stores the maximum end of its subtree, so whole
+/// subtrees that end before a value can be skipped.
+struct IntervalTree<T> {
+    intervals: Vec<(Bound<T>, Bound<T>, usize)>,
+    max_end:   Vec<Bound<T>>,
+    closed:    Closed,
+}
+
+impl<T: PartialOrd + Copy> IntervalTree<T> {
+    fn new(mut intervals: Vec<(Bound<T>, Bound<T>, usize)>, closed: Closed) -> Self {
+        intervals.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(std::cmp::Ordering::Equal));
+        let mut max_end: Vec<Bound<T>> = intervals.iter().map(|iv| iv.1).collect();
+        Self::build_max_end(&intervals, &mut max_end, 0, intervals.len());
+        IntervalTree {
+            intervals,
+            max_end,
+            closed,
+        }
+    }
+
+    fn build_max_end(
+        intervals: &[(Bound<T>, Bound<T>, usize)],
+        max_end: &mut [Bound<T>],
+        lo: usize,
+        hi: usize,
+    ) -> Option<Bound<T>> {
+        if lo >= hi {
+            return None;
+        }
+        let mid = lo + (hi - lo) / 2;
+        let mut max = intervals[mid].1;
+        for child in [
+            Self::build_max_end(intervals, max_end, lo, mid),
+            Self::build_max_end(intervals, max_end, mid + 1, hi),
+        ]
+        .into_iter()
+        .flatten()
+        {
+            if child > max {
+                max = child;
+            }
+        }
+        max_end[mid] = max;
+        Some(max)
+    }
+
+    /// the rows of all the intervals containing `value`, in row order
+    fn query(&self, value: T, matches: &mut Vec<usize>) {
+        matches.clear();
+        self.query_range(&Bound::Value(value), 0, self.intervals.len(), matches);
+        matches.sort_unstable();
+    }
+
+    fn query_range(&self, value: &Bound<T>, lo: usize, hi: usize, matches: &mut Vec<usize>) {
+        if lo >= hi {
+            return;
+        }
+        let mid = lo + (hi - lo) / 2;
+        // every interval in this subtree ends before the value
+        if !self.closed.before_end(&self.max_end[mid], value) {
+            return;
+        }
+        self.query_range(value, lo, mid, matches);
+        let (start, end, row) = &self.intervals[mid];
+        // the intervals are sorted by start, so if this interval starts after the value,
+        // so do all the intervals after it
+        if self.closed.after_start(start, value) {
+            if self.closed.before_end(end, value) {
+                matches.push(*row);
+            }
+            self.query_range(value, mid + 1, hi, matches);
+        }
+    }
+}
+
+pub fn run(argv: &[&str]) -> CliResult<()> {
+    let args: Args = util::get_args(USAGE, argv)?;
+
+    let Ok(range_type) = RangeType::from_str(&args.flag_type) else {
+        return fail_incorrectusage_clierror!(
+            "Invalid --type `{}`. Supported types are: numeric, date & ip.",
+            args.flag_type
+        );
+    };
+    let Ok(closed) = Closed::from_str(&args.flag_closed) else {
+        return fail_incorrectusage_clierror!(
+            "Invalid --closed `{}`. It must be one of: left, right, both or neither.",
+            args.flag_closed
+        );
+    };
+
+    let rconf1 = Config::new(&Some(args.arg_input1.clone()))
+        .delimiter(args.flag_delimiter)
+        .no_headers(args.flag_no_headers);
+    let prefer_dmy = rconf1.get_dmy_preference();
+
+    match range_type {
+        RangeType::Numeric => range_join(&args, closed, |s| s.parse::<f64>().ok()),
+        RangeType::Date => range_join(&args, closed, |s| {
+            parse_with_preference(s, prefer_dmy)
+                .ok()
+                .map(|dt| dt.timestamp_micros())
+        }),
+        RangeType::Ip => range_join(&args, closed, |s| match s.parse::<IpAddr>() {
+            Ok(IpAddr::V4(ip)) => Some(u128::from(ip.to_ipv6_mapped())),
+            Ok(IpAddr::V6(ip)) => Some(u128::from(ip)),
+            Err(_) => None,
+        }),
+    }
+}
+
+fn range_join<T: PartialOrd + Copy>(
+    args: &Args,
+    closed: Closed,
+    parse: impl Fn(&str) -> Option<T>,
+) -> CliResult<()> {
+    let rconf1 = Config::new(&Some(args.arg_input1.clone()))
+        .delimiter(args.flag_delimiter)
+        .no_headers(args.flag_no_headers)
+        .select(args.arg_value_column.clone());
+    let rconf2 = Config::new(&Some(args.arg_input2.clone()))
+        .delimiter(args.flag_delimiter)
+        .no_headers(args.flag_no_headers);
+    util::errif_greater_one_stdin(&[rconf1.clone(), rconf2.clone()])?;
+
+    let mut rdr1 = rconf1.reader()?;
+    let mut rdr2 = rconf2.reader()?;
+    let headers1 = rdr1.byte_headers()?.clone();
+    let headers2 = rdr2.byte_headers()?.clone();
+
+    let value_sel = rconf1.selection(&headers1)?;
+    if value_sel.len() != 1 {
+        return fail_incorrectusage_clierror!("<value-column> must select exactly one column.");
+    }
+    let value_idx = value_sel[0];
+    let start_idx = single_column(&args.arg_start_column, &headers2, &rconf2, "<start-column>")?;
+    let end_idx = single_column(&args.arg_end_column, &headers2, &rconf2, "<end-column>")?;
+
+    let parse_bound = |field: &[u8], unbounded: Bound<T>| -> Option<Bound<T>> {
+        let field = str::from_utf8(field).ok()?.trim();
+        if field.is_empty() {
+            Some(unbounded)
+        } else {
+            parse(field).map(Bound::Value)
+        }
+    };
+
+    // load the ranges of <input2> into memory
+    let mut rows2 = Vec::new();
+    let mut intervals = Vec::new();
+    let mut ignored = 0_u64;
+    let mut row = csv::ByteRecord::new();
+    while rdr2.read_byte_record(&mut row)? {
+        let start = parse_bound(row.get(start_idx).unwrap_or_default(), Bound::Min);
+        let end = parse_bound(row.get(end_idx).unwrap_or_default(), Bound::Max);
+        match (start, end) {
+            (Some(start), Some(end)) if start <= end => {
+                intervals.push((start, end, rows2.len()));
+            },
+            _ => ignored += 1,
+        }
+        rows2.push(row.clone());
+    }
+    if ignored > 0 {
+        log::warn!("{ignored} rows of <input2> with invalid ranges were ignored.");
+    }
+    let tree = IntervalTree::new(intervals, closed);
+
+    let mut wtr = Config::new(&args.flag_output).writer()?;
+    if !rconf1.no_headers {
+        wtr.write_record(headers1.iter().chain(&headers2))?;
+    }
+    let pad2: csv::ByteRecord = repeat(b"").take(headers2.len()).collect();
+
+    let mut matches = Vec::new();
+    while rdr1.read_byte_record(&mut row)? {
+        let value = str::from_utf8(row.get(value_idx).unwrap_or_default())
+            .ok()
+            .map(str::trim)
+            .filter(|v| !v.is_empty())
+            .and_then(&parse);
+        match value {
+            Some(value) => tree.query(value, &mut matches),
+            None => matches.clear(),
+        }
+
+        if matches.is_empty() {
+            if args.flag_left {
+                wtr.write_record(row.iter().chain(&pad2))?;
+            }
+            continue;
+        }
+        for &rowi in &matches {
+            wtr.write_record(row.iter().chain(&rows2[rowi]))?;
+        }
+    }
+    Ok(wtr.flush()?)
+}
+
+fn single_column(
+    column: &SelectColumns,
+    headers: &csv::ByteRecord,
+    rconf: &Config,
+    name: &str,
+) -> CliResult<usize> {
+    let sel = column.selection(headers, !rconf.no_headers)?;
+    if sel.len() != 1 {
+        return fail_incorrectusage_clierror!("{name} must select exactly one column.");
+    }
+    Ok(sel[0])
+}
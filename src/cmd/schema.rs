@@ -1,5 +1,5 @@
 static USAGE: &str = r#"
-Generate JSON Schema from CSV data.
+Generate JSON Schema (and other schema formats) from CSV data.
 
 This command derives a JSON Schema Validation (Draft 7) file from CSV data, 
 including validation rules based on data type and input data domain/range.
@@ -20,6 +20,40 @@ a `stdin.csv` file will created with stdin's contents as well.
 
 Note that `stdin.csv` will be overwritten if it already exists.
 
+The same stats & frequency pass can also be used to derive other schema formats
+with the --format option:
+  jsonschema    JSON Schema Validation (Draft 7) - `.schema.json` suffix (default)
+  postgres      PostgreSQL CREATE TABLE DDL - `.postgres.sql` suffix
+  sqlite        SQLite CREATE TABLE DDL - `.sqlite.sql` suffix
+  duckdb        DuckDB CREATE TABLE DDL - `.duckdb.sql` suffix
+  frictionless  Frictionless Table Schema - `.tableschema.json` suffix
+  avro          Avro schema - `.avsc` suffix
+  polars        Polars schema JSON - `.pschema.json` suffix
+
+For the SQL DDL formats, column types are narrowed using the stats - e.g. Integer
+columns are typed SMALLINT, INTEGER or BIGINT based on their min/max values, Float
+columns are typed NUMERIC(p,s) based on their min/max values & max_precision, and
+String columns are typed VARCHAR(n) based on their max_length. Columns without
+nulls are declared NOT NULL. The table is named after the input's file stem.
+
+Date & DateTime columns are typed as dates in the SQL DDL, Frictionless & Avro formats.
+For the JSON Schema & Polars formats, they are only typed as dates with --strict-dates,
+as polars may not parse non RFC-3339 dates when reading CSVs.
+
+Multiple inputs of the same dataset (e.g. monthly extracts) can be given to infer a
+schema that covers all of them. Each input is profiled separately, and its inferred
+schema is merged into the schema so far, widening the constraints as needed:
+  * minimum/maximum and minLength/maxLength are widened
+  * enum lists are unioned, and dropped if they exceed --enum-threshold
+  * types are relaxed (e.g. integer to number, number to string)
+  * columns missing from some inputs are no longer required
+  * pattern & format constraints that differ are dropped
+An existing JSON Schema can also be evolved with --base. It's the starting point of the
+merge, and its title, description and custom keywords are kept.
+The schema is named after the first input. When merging, a change log of what widened
+is sent to stderr, and unless --stdout is set, written to a `.schema-changes.tsv` file
+as well (e.g. `jan.csv.schema-changes.tsv`).
+
 Schema generation can be a compute-intensive process, especially for large CSV files.
 To speed up generation, the `schema` command will reuse a `stats.csv.bin.sz` file if it
 exists and is current (i.e. stats generated with --cardinality and --infer-dates options).
@@ -29,10 +63,13 @@ and then use that to generate the schema file.
 For examples, see https://github.com/jqnatividad/qsv/blob/master/tests/test_schema.rs.
 
 Usage:
-    qsv schema [options] [<input>]
+    qsv schema [options] [<input>...]
     qsv schema --help
 
 Schema options:
+    --format <arg>             The schema format to generate. One of jsonschema, postgres,
+                               sqlite, duckdb, frictionless, avro or polars.
+                               [default: jsonschema]
     --enum-threshold <num>     Cardinality threshold for adding enum constraints.
                                Enum constraints are compiled for String & Integer types.
                                [default: 50]
@@ -58,9 +95,12 @@ Schema options:
                                [default: date,time,due,open,close,created]
     --prefer-dmy               Prefer to parse dates in dmy format.
                                Otherwise, use mdy format.
+    --base <file>              An existing JSON Schema file to merge the inferred schema
+                               into, widening its constraints to cover the input/s.
+                               Only valid with --format jsonschema.
     --force                    Force recomputing cardinality and unique values
                                even if stats cache file exists and is current.
-    --stdout                   Send generated JSON schema file to stdout instead.
+    --stdout                   Send generated schema file to stdout instead.
     -j, --jobs <arg>           The number of jobs to run in parallel.
                                When not set, the number of jobs is set to the
                                number of CPUs detected.
@@ -73,14 +113,22 @@ Common options:
                                appear as the header row in the output.
     -d, --delimiter <arg>      The field delimiter for reading CSV data.
                                Must be a single character. [default: ,]
+    --null-values <list>       A comma-delimited list of case-sensitive values to treat
+                               as NULL, besides the empty string (e.g. NA,NULL,\N).
+                               They are left out of enums and don't make a field required,
+                               and are listed in the schema's "missingValues", so
+                               "qsv validate" also treats them as empty values.
+                               Overrides the QSV_NULL_VALUES env var.
     --memcheck                 Check if there is enough memory to load the entire
                                CSV into memory using CONSERVATIVE heuristics.
 "#;
 
 use std::{
+    fmt,
     fs::File,
-    io::{BufReader, Write},
+    io::{BufReader, BufWriter, Write},
     path::Path,
+    str::FromStr,
 };
 
 use ahash::{AHashMap, AHashSet};
@@ -92,16 +140,18 @@ use rayon::slice::ParallelSliceMut;
 use serde::Deserialize;
 use serde_json::{json, value::Number, Map, Value};
 use stats::Frequencies;
+use strum_macros::EnumString;
 
 use crate::{
     cmd::stats::Stats,
-    config::{Config, Delimiter, DEFAULT_RDR_BUFFER_CAPACITY},
+    config::{Config, Delimiter, NullValues, DEFAULT_RDR_BUFFER_CAPACITY},
     select::SelectColumns,
     util, CliResult,
 };
 
 #[derive(Deserialize, Clone)]
 pub struct Args {
+    pub flag_format:          String,
     pub flag_enum_threshold:  usize,
     pub flag_ignore_case:     bool,
     pub flag_strict_dates:    bool,
@@ -113,43 +163,147 @@ pub struct Args {
     pub flag_jobs:            Option<usize>,
     pub flag_no_headers:      bool,
     pub flag_delimiter:       Option<Delimiter>,
-    pub arg_input:            Option<String>,
+    pub arg_input:            Vec<String>,
+    pub flag_base:            Option<String>,
+    pub flag_null_values:     Option<String>,
     pub flag_memcheck:        bool,
 }
 
+impl Args {
+    /// the input being profiled. With multiple inputs,
+    /// each input is profiled with its own copy of Args.
+    fn input(&self) -> Option<String> {
+        self.arg_input.first().cloned()
+    }
+
+    /// the values treated as NULL, from --null-values or the QSV_NULL_VALUES env var
+    fn null_values(&self) -> NullValues {
+        Config::new(&self.input())
+            .null_values(self.flag_null_values.clone())
+            .get_null_values()
+            .clone()
+    }
+
+    /// the "missingValues" of the schema - the empty string & the NULL values
+    fn missing_values(&self) -> Vec<String> {
+        let null_values = self.null_values().to_list();
+        std::iter::once("")
+            .chain(null_values.split(',').filter(|v| !v.is_empty()))
+            .map(String::from)
+            .collect()
+    }
+}
+
+#[derive(Clone, Copy, EnumString, PartialEq)]
+#[strum(ascii_case_insensitive)]
+enum SchemaFormat {
+    JsonSchema,
+    Postgres,
+    Sqlite,
+    Duckdb,
+    Frictionless,
+    Avro,
+    Polars,
+}
+
+impl SchemaFormat {
+    const fn file_suffix(self) -> &'static str {
+        match self {
+            SchemaFormat::JsonSchema => ".schema.json",
+            SchemaFormat::Postgres => ".postgres.sql",
+            SchemaFormat::Sqlite => ".sqlite.sql",
+            SchemaFormat::Duckdb => ".duckdb.sql",
+            SchemaFormat::Frictionless => ".tableschema.json",
+            SchemaFormat::Avro => ".avsc",
+            SchemaFormat::Polars => ".pschema.json",
+        }
+    }
+}
+
+/// the stats of a column used to derive the non-JSON Schema formats
+struct ColumnStats {
+    name:          String,
+    col_type:      String,
+    nullcount:     usize,
+    min:           Option<String>,
+    max:           Option<String>,
+    max_length:    usize,
+    max_precision: usize,
+}
+
+/// a constraint of the schema that was widened to cover an input
+struct SchemaChange {
+    source:  String,
+    field:   String,
+    keyword: String,
+    from:    String,
+    to:      String,
+}
+
+impl fmt::Display for SchemaChange {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let source = &self.source;
+        let field = &self.field;
+        let keyword = &self.keyword;
+        if keyword == "column" {
+            write!(f, "{source}: \"{field}\" column added")
+        } else if self.from.is_empty() {
+            write!(f, "{source}: \"{field}\" {keyword} added: {}", self.to)
+        } else if self.to.is_empty() {
+            write!(f, "{source}: \"{field}\" {keyword} dropped: {}", self.from)
+        } else {
+            write!(
+                f,
+                "{source}: \"{field}\" {keyword} widened: {} -> {}",
+                self.from, self.to
+            )
+        }
+    }
+}
+
 const STDIN_CSV: &str = "stdin.csv";
 
 pub fn run(argv: &[&str]) -> CliResult<()> {
     let mut args: Args = util::get_args(USAGE, argv)?;
 
+    let Ok(schema_format) = SchemaFormat::from_str(&args.flag_format) else {
+        return fail_incorrectusage_clierror!(
+            "Invalid --format \"{}\". Must be one of jsonschema, postgres, sqlite, duckdb, \
+             frictionless, avro or polars.",
+            args.flag_format
+        );
+    };
+
+    if args.flag_base.is_some() && schema_format != SchemaFormat::JsonSchema {
+        return fail_incorrectusage_clierror!("--base can only be used with --format jsonschema.");
+    }
+
     // if using stdin, we create a stdin.csv file as stdin is not seekable and we need to
     // open the file multiple times to compile stats/unique values, etc.
     // We use a fixed "stdin.csv" filename instead of a temporary file with random characters
     // so the name of the generated schema.json file is readable and predictable
     // (stdin.csv.schema.json)
-    let (input_path, input_filename) = if args.arg_input.is_none() {
+    if args.arg_input.is_empty() {
         let mut stdin_file = File::create(STDIN_CSV)?;
         let stdin = std::io::stdin();
         let mut stdin_handle = stdin.lock();
         std::io::copy(&mut stdin_handle, &mut stdin_file)?;
         drop(stdin_handle);
-        args.arg_input = Some(STDIN_CSV.to_string());
-        (STDIN_CSV.to_string(), STDIN_CSV.to_string())
-    } else {
-        let filename = Path::new(args.arg_input.as_ref().unwrap())
-            .file_name()
-            .unwrap()
-            .to_string_lossy()
-            .to_string();
-        (args.arg_input.clone().unwrap(), filename)
-    };
-
-    // we're loading the entire file into memory, we need to check avail mem
-    util::mem_file_check(
-        &std::path::PathBuf::from(&input_path),
-        false,
-        args.flag_memcheck,
-    )?;
+        args.arg_input = vec![STDIN_CSV.to_string()];
+    }
+    // the schema file is named after the first input
+    let input_path = args.arg_input[0].clone();
+    let input_filenames: Vec<String> = args
+        .arg_input
+        .iter()
+        .map(|input| {
+            Path::new(input)
+                .file_name()
+                .unwrap()
+                .to_string_lossy()
+                .to_string()
+        })
+        .collect();
 
     // we can do this directly here, since args is mutable and
     // Config has not been created yet at this point
@@ -158,62 +312,133 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
         winfo!("Prefer DMY set.");
     }
 
-    // build schema for each field by their inferred type, min/max value/length, and unique values
-    let mut properties_map: Map<String, Value> =
-        match infer_schema_from_stats(&args, &input_filename) {
-            Ok(map) => map,
-            Err(e) => {
-                return fail_clierror!(
-                    "Failed to infer schema via stats and frequency from {input_filename}: {e}"
-                );
+    // when evolving an existing schema, it's the starting point of the merge
+    let base_schema: Option<Value> = if let Some(ref base_path) = args.flag_base {
+        let base_json = std::fs::read_to_string(base_path)?;
+        match serde_json::from_str::<Value>(&base_json) {
+            Ok(base) if base.get("properties").is_some_and(Value::is_object) => Some(base),
+            Ok(_) => {
+                return fail_clierror!("--base {base_path} is not a JSON Schema with properties.")
             },
-        };
+            Err(e) => return fail_clierror!("Cannot parse --base {base_path}: {e}"),
+        }
+    } else {
+        None
+    };
+    let merging = base_schema.is_some() || args.arg_input.len() > 1;
 
-    // generate regex pattern for selected String columns
-    let pattern_map = generate_string_patterns(&args, &properties_map)?;
+    let mut properties_map: Map<String, Value> = Map::new();
+    let mut required_fields: Vec<Value> = Vec::new();
+    let mut column_stats: Vec<ColumnStats> = Vec::new();
+    let mut schema_changes: Vec<SchemaChange> = Vec::new();
 
-    // enrich properties map with pattern constraint for String fields
-    for (field_name, field_def) in &mut properties_map {
-        // dbg!(&field_name, &field_def);
-        if pattern_map.contains_key(field_name) && should_emit_pattern_constraint(field_def) {
-            let field_def_map = field_def.as_object_mut().unwrap();
-            let pattern = Value::String(pattern_map[field_name].clone());
-            field_def_map.insert("pattern".to_string(), pattern.clone());
-            winfo!("Added regex pattern constraint for field: {field_name} -> {pattern}");
+    if let Some(ref base) = base_schema {
+        properties_map.clone_from(base["properties"].as_object().unwrap());
+        if let Some(Value::Array(base_required)) = base.get("required") {
+            required_fields.clone_from(base_required);
         }
     }
 
-    // generate list of required fields
-    let required_fields = get_required_fields(&properties_map);
+    // profile each input separately, and merge its inferred schema into the schema so far
+    for (i, (input, input_filename)) in args.arg_input.iter().zip(&input_filenames).enumerate() {
+        let mut input_args = args.clone();
+        input_args.arg_input = vec![input.clone()];
 
-    // create final JSON object for output
-    let schema = json!({
-        "$schema": "https://json-schema.org/draft-07/schema",
-        "title": format!("JSON Schema for {input_filename}"),
-        "description": "Inferred JSON Schema from QSV schema command",
-        "type": "object",
-        "properties": Value::Object(properties_map),
-        "required": Value::Array(required_fields)
-    });
+        let (input_properties, input_column_stats) =
+            infer_input_schema(&input_args, input, input_filename)?;
+
+        if i == 0 && base_schema.is_none() {
+            required_fields = get_required_fields(&input_properties);
+            properties_map = input_properties;
+        } else {
+            merge_properties(
+                &mut properties_map,
+                &mut required_fields,
+                &input_properties,
+                input_filename,
+                args.flag_enum_threshold,
+                &mut schema_changes,
+            );
+        }
+        if i == 0 {
+            column_stats = input_column_stats;
+        } else {
+            merge_column_stats(&mut column_stats, input_column_stats);
+        }
+    }
 
-    let schema_pretty = match serde_json::to_string_pretty(&schema) {
-        Ok(s) => s,
-        Err(e) => return fail_clierror!("Cannot prettify schema json: {e}"),
+    let input_filename = input_filenames.join(", ");
+    let schema_output = match schema_format {
+        SchemaFormat::JsonSchema => {
+            let mut schema = if let Some(mut base) = base_schema {
+                // keep the base schema's title, description & other keywords
+                base["properties"] = Value::Object(properties_map);
+                base["required"] = Value::Array(required_fields);
+                base
+            } else {
+                // create final JSON object for output
+                json!({
+                    "$schema": "https://json-schema.org/draft-07/schema",
+                    "title": format!("JSON Schema for {input_filename}"),
+                    "description": "Inferred JSON Schema from QSV schema command",
+                    "type": "object",
+                    "properties": Value::Object(properties_map),
+                    "required": Value::Array(required_fields)
+                })
+            };
+            // list the NULL values, so validate treats them as empty values
+            if !args.null_values().is_empty() {
+                schema["missingValues"] = json!(args.missing_values());
+            }
+            to_pretty_json(&schema)?
+        },
+        SchemaFormat::Postgres | SchemaFormat::Sqlite | SchemaFormat::Duckdb => {
+            let table_name = Path::new(&input_filenames[0])
+                .file_stem()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .to_string();
+            create_table_ddl(schema_format, &table_name, &column_stats)
+        },
+        SchemaFormat::Frictionless => to_pretty_json(&frictionless_table_schema(
+            &args,
+            &input_filename,
+            &column_stats,
+            &properties_map,
+        ))?,
+        SchemaFormat::Avro => to_pretty_json(&avro_schema(&input_filenames[0], &column_stats))?,
+        SchemaFormat::Polars => to_pretty_json(&polars_schema(&args, &column_stats))?,
     };
 
+    if merging {
+        for change in &schema_changes {
+            winfo!("{change}");
+        }
+        if schema_changes.is_empty() {
+            winfo!("No schema changes - all inputs conform to the schema.");
+        } else if !args.flag_stdout {
+            let changes_filename = input_path.clone() + ".schema-changes.tsv";
+            write_schema_changes(&changes_filename, &schema_changes)?;
+            winfo!(
+                "{} schema change/s written to {changes_filename}",
+                schema_changes.len()
+            );
+        }
+    }
+
     if args.flag_stdout {
         let stdout = std::io::stdout();
         let mut handle = stdout.lock();
 
-        handle.write_all(schema_pretty.as_bytes())?;
+        handle.write_all(schema_output.as_bytes())?;
         handle.flush()?;
 
         info!("Schema written to stdout");
     } else {
-        let schema_output_filename = input_path + ".schema.json";
+        let schema_output_filename = input_path + schema_format.file_suffix();
         let mut schema_output_file = File::create(&schema_output_filename)?;
 
-        schema_output_file.write_all(schema_pretty.as_bytes())?;
+        schema_output_file.write_all(schema_output.as_bytes())?;
         schema_output_file.flush()?;
 
         woutinfo!("Schema written to {schema_output_filename}");
@@ -222,6 +447,59 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
     Ok(())
 }
 
+/// infer the JSON Schema properties & the column stats of a single input
+fn infer_input_schema(
+    args: &Args,
+    input_path: &str,
+    input_filename: &str,
+) -> CliResult<(Map<String, Value>, Vec<ColumnStats>)> {
+    // we're loading the entire file into memory, we need to check avail mem
+    util::mem_file_check(
+        &std::path::PathBuf::from(input_path),
+        false,
+        args.flag_memcheck,
+    )?;
+
+    // build schema for each field by their inferred type, min/max value/length, and unique values
+    // also keep the stats of each column to derive the other schema formats
+    let (mut properties_map, column_stats) =
+        match get_stats_records(args).and_then(|(csv_fields, csv_stats, stats_col_index_map)| {
+            Ok((
+                build_properties_map(
+                    args,
+                    input_filename,
+                    &csv_fields,
+                    &csv_stats,
+                    &stats_col_index_map,
+                )?,
+                get_column_stats(&csv_fields, &csv_stats, &stats_col_index_map)?,
+            ))
+        }) {
+            Ok(schema_stats) => schema_stats,
+            Err(e) => {
+                return fail_clierror!(
+                    "Failed to infer schema via stats and frequency from {input_filename}: {e}"
+                );
+            },
+        };
+
+    // generate regex pattern for selected String columns
+    let pattern_map = generate_string_patterns(args, &properties_map)?;
+
+    // enrich properties map with pattern constraint for String fields
+    for (field_name, field_def) in &mut properties_map {
+        // dbg!(&field_name, &field_def);
+        if pattern_map.contains_key(field_name) && should_emit_pattern_constraint(field_def) {
+            let field_def_map = field_def.as_object_mut().unwrap();
+            let pattern = Value::String(pattern_map[field_name].clone());
+            field_def_map.insert("pattern".to_string(), pattern.clone());
+            winfo!("Added regex pattern constraint for field: {field_name} -> {pattern}");
+        }
+    }
+
+    Ok((properties_map, column_stats))
+}
+
 /// Builds JSON MAP object that corresponds to the "properties" object of JSON Schema (Draft 7) by
 /// looking at CSV value stats Supported JSON Schema validation vocabularies:
 ///  * type
@@ -234,6 +512,24 @@ pub fn infer_schema_from_stats(args: &Args, input_filename: &str) -> CliResult<M
     // invoke cmd::stats
     let (csv_fields, csv_stats, stats_col_index_map) = get_stats_records(args)?;
 
+    build_properties_map(
+        args,
+        input_filename,
+        &csv_fields,
+        &csv_stats,
+        &stats_col_index_map,
+    )
+}
+
+/// Builds the "properties" object of JSON Schema from the stats records
+/// returned by `get_stats_records`
+fn build_properties_map(
+    args: &Args,
+    input_filename: &str,
+    csv_fields: &ByteRecord,
+    csv_stats: &[Stats],
+    stats_col_index_map: &AHashMap<String, usize>,
+) -> CliResult<Map<String, Value>> {
     // amortize memory allocation
     let mut low_cardinality_column_indices: Vec<usize> =
         Vec::with_capacity(args.flag_enum_threshold);
@@ -242,9 +538,9 @@ pub fn infer_schema_from_stats(args: &Args, input_filename: &str) -> CliResult<M
     let column_select_arg: String = build_low_cardinality_column_selector_arg(
         &mut low_cardinality_column_indices,
         args.flag_enum_threshold,
-        &csv_fields,
-        &csv_stats,
-        &stats_col_index_map,
+        csv_fields,
+        csv_stats,
+        stats_col_index_map,
     );
 
     // invoke cmd::frequency to get unique values for each field
@@ -428,7 +724,7 @@ pub fn infer_schema_from_stats(args: &Args, input_filename: &str) -> CliResult<M
 /// returns tuple (`csv_fields`, `csv_stats`, `stats_col_index_map`)
 fn get_stats_records(args: &Args) -> CliResult<(ByteRecord, Vec<Stats>, AHashMap<String, usize>)> {
     let stats_args = crate::cmd::stats::Args {
-        arg_input:            args.arg_input.clone(),
+        arg_input:            args.input(),
         flag_select:          crate::select::SelectColumns::parse("").unwrap(),
         flag_everything:      false,
         flag_typesonly:       false,
@@ -450,23 +746,39 @@ fn get_stats_records(args: &Args) -> CliResult<(ByteRecord, Vec<Stats>, AHashMap
         flag_output:          None,
         flag_no_headers:      args.flag_no_headers,
         flag_delimiter:       args.flag_delimiter,
+        flag_null_values:     args.flag_null_values.clone(),
         flag_memcheck:        args.flag_memcheck,
     };
 
-    let canonical_input_path = Path::new(&args.arg_input.clone().unwrap()).canonicalize()?;
+    let canonical_input_path = Path::new(&args.input().unwrap()).canonicalize()?;
     let stats_binary_encoded_path = canonical_input_path.with_extension("stats.csv.bin.sz");
 
     let stats_bin_current = if stats_binary_encoded_path.exists() {
         let stats_bin_metadata = std::fs::metadata(&stats_binary_encoded_path)?;
 
-        let input_metadata = std::fs::metadata(args.arg_input.clone().unwrap())?;
+        let input_metadata = std::fs::metadata(args.input().unwrap())?;
 
-        if stats_bin_metadata.modified()? > input_metadata.modified()? {
-            info!("Valid stats.csv.bin.sz file found!");
-            true
-        } else {
+        // the stats args are saved in stats.csv.json, check if the cached stats
+        // were computed with the same NULL values
+        let cached_null_values =
+            std::fs::read_to_string(canonical_input_path.with_extension("stats.csv.json"))
+                .ok()
+                .and_then(|json| serde_json::from_str::<Value>(&json).ok())
+                .and_then(|json| json["flag_null_values"].as_str().map(String::from))
+                .unwrap_or_default();
+
+        if stats_bin_metadata.modified()? <= input_metadata.modified()? {
             info!("stats.csv.bin.sz file is older than input file. Regenerating stats.bin file.");
             false
+        } else if cached_null_values != args.null_values().to_list() {
+            info!(
+                "stats.csv.bin.sz file was computed with other NULL values. Regenerating \
+                 stats.bin file."
+            );
+            false
+        } else {
+            info!("Valid stats.csv.bin.sz file found!");
+            true
         }
     } else {
         info!("stats.csv.bin.sz file does not exist: {stats_binary_encoded_path:?}");
@@ -545,6 +857,9 @@ fn get_stats_records(args: &Args) -> CliResult<(ByteRecord, Vec<Stats>, AHashMap
         let qsv_bin = std::env::current_exe().unwrap();
         let mut stats_cmd = std::process::Command::new(qsv_bin);
         stats_cmd.args(stats_args_vec);
+        if let Some(ref null_values) = args.flag_null_values {
+            stats_cmd.args(["--null-values", null_values]);
+        }
         let _stats_output = stats_cmd.output()?;
 
         let bin_file =
@@ -564,7 +879,7 @@ fn get_stats_records(args: &Args) -> CliResult<(ByteRecord, Vec<Stats>, AHashMap
     };
 
     // get the headers from the input file
-    let mut rdr = csv::Reader::from_path(args.arg_input.clone().unwrap()).unwrap();
+    let mut rdr = csv::Reader::from_path(args.input().unwrap()).unwrap();
     let csv_fields = rdr.byte_headers()?.clone();
     drop(rdr);
 
@@ -639,7 +954,7 @@ fn get_unique_values(
 ) -> CliResult<AHashMap<String, Vec<String>>> {
     // prepare arg for invoking cmd::frequency
     let freq_args = crate::cmd::frequency::Args {
-        arg_input:           args.arg_input.clone(),
+        arg_input:           args.input(),
         flag_select:         crate::select::SelectColumns::parse(column_select_arg).unwrap(),
         flag_limit:          args.flag_enum_threshold as isize,
         flag_unq_limit:      args.flag_enum_threshold,
@@ -654,6 +969,7 @@ fn get_unique_values(
         flag_output:         None,
         flag_no_headers:     args.flag_no_headers,
         flag_delimiter:      args.flag_delimiter,
+        flag_null_values:    args.flag_null_values.clone(),
         flag_memcheck:       args.flag_memcheck,
     };
 
@@ -735,7 +1051,7 @@ fn generate_string_patterns(
 ) -> CliResult<AHashMap<String, String>> {
     // standard boiler-plate for reading CSV
 
-    let rconfig = Config::new(&args.arg_input)
+    let rconfig = Config::new(&args.input())
         .delimiter(args.flag_delimiter)
         .no_headers(args.flag_no_headers)
         .select(args.flag_pattern_columns.clone());
@@ -815,3 +1131,600 @@ fn should_emit_pattern_constraint(field_def: &Value) -> bool {
 
     type_list.contains(&Value::String("string".to_string())) && !has_enum
 }
+
+/// prettify JSON schema for output
+fn to_pretty_json(schema: &Value) -> CliResult<String> {
+    match serde_json::to_string_pretty(schema) {
+        Ok(s) => Ok(s),
+        Err(e) => fail_clierror!("Cannot prettify schema json: {e}"),
+    }
+}
+
+/// get the stats of each column needed to derive the non-JSON Schema formats
+fn get_column_stats(
+    csv_fields: &ByteRecord,
+    csv_stats: &[Stats],
+    stats_col_index_map: &AHashMap<String, usize>,
+) -> CliResult<Vec<ColumnStats>> {
+    let mut column_stats = Vec::with_capacity(csv_fields.len());
+
+    for (i, header_byte_slice) in csv_fields.iter().enumerate() {
+        let stats_record = csv_stats.get(i).unwrap().clone().to_record(4, false);
+
+        // empty stats are not applicable to the column's type
+        let get_stat = |stat: &str| {
+            stats_col_index_map
+                .get(stat)
+                .and_then(|idx| stats_record.get(*idx))
+                .filter(|s| !s.is_empty())
+                .map(ToString::to_string)
+        };
+
+        column_stats.push(ColumnStats {
+            name:          convert_to_string(header_byte_slice)?,
+            col_type:      get_stat("type").unwrap_or_else(|| "String".to_string()),
+            nullcount:     get_stat("nullcount")
+                .and_then(|s| s.parse::<usize>().ok())
+                .unwrap_or_default(),
+            min:           get_stat("min"),
+            max:           get_stat("max"),
+            max_length:    get_stat("max_length")
+                .and_then(|s| s.parse::<usize>().ok())
+                .unwrap_or_default(),
+            max_precision: get_stat("max_precision")
+                .and_then(|s| s.parse::<usize>().ok())
+                .unwrap_or_default(),
+        });
+    }
+
+    Ok(column_stats)
+}
+
+/// the smallest integer width (16, 32 or 64 bits) that can hold
+/// all the values of an Integer column, based on its min/max
+fn integer_width(col: &ColumnStats) -> u8 {
+    let bounds = [col.min.as_deref(), col.max.as_deref()]
+        .iter()
+        .map(|v| v.and_then(|s| atoi_simd::parse::<i64>(s.as_bytes()).ok()))
+        .collect::<Option<Vec<i64>>>();
+
+    match bounds {
+        Some(bounds) if bounds.iter().all(|b| i16::try_from(*b).is_ok()) => 16,
+        Some(bounds) if bounds.iter().all(|b| i32::try_from(*b).is_ok()) => 32,
+        _ => 64,
+    }
+}
+
+/// the (precision, scale) of a Float column, where scale is its max_precision and
+/// precision is the number of integer digits of its largest magnitude bound plus scale
+fn numeric_precision_scale(col: &ColumnStats) -> (usize, usize) {
+    let integer_digits = [col.min.as_deref(), col.max.as_deref()]
+        .iter()
+        .filter_map(|v| v.and_then(|s| s.parse::<f64>().ok()))
+        .map(|f| format!("{:.0}", f.abs().trunc()).len())
+        .max()
+        .unwrap_or(1);
+
+    (integer_digits + col.max_precision, col.max_precision)
+}
+
+/// quote SQL identifier, escaping embedded double quotes
+fn quote_sql_identifier(identifier: &str) -> String {
+    format!("\"{}\"", identifier.replace('"', "\"\""))
+}
+
+/// the SQL column type of a column for the given SQL dialect
+fn sql_column_type(schema_format: SchemaFormat, col: &ColumnStats) -> String {
+    match (schema_format, col.col_type.as_str()) {
+        (SchemaFormat::Sqlite, "Integer") => "INTEGER".to_string(),
+        (SchemaFormat::Sqlite, "Float") => "REAL".to_string(),
+        // SQLite has no date types, dates are stored as ISO-8601 TEXT
+        (SchemaFormat::Sqlite, _) => "TEXT".to_string(),
+        (_, "Integer") => match integer_width(col) {
+            16 => "SMALLINT".to_string(),
+            32 => "INTEGER".to_string(),
+            _ => "BIGINT".to_string(),
+        },
+        (SchemaFormat::Postgres, "Float") => {
+            let (precision, scale) = numeric_precision_scale(col);
+            // PostgreSQL NUMERIC supports up to 1000 digits of precision
+            if precision > 1000 {
+                "DOUBLE PRECISION".to_string()
+            } else {
+                format!("NUMERIC({precision},{scale})")
+            }
+        },
+        (_, "Float") => {
+            let (precision, scale) = numeric_precision_scale(col);
+            // DuckDB DECIMAL supports up to 38 digits of precision
+            if precision > 38 {
+                "DOUBLE".to_string()
+            } else {
+                format!("DECIMAL({precision},{scale})")
+            }
+        },
+        (_, "Date") => "DATE".to_string(),
+        (_, "DateTime") => "TIMESTAMP".to_string(),
+        (SchemaFormat::Postgres, "String") if col.max_length > 0 => {
+            format!("VARCHAR({})", col.max_length)
+        },
+        (SchemaFormat::Postgres, _) => "TEXT".to_string(),
+        // DuckDB VARCHAR is variable-length, a max length is accepted but not enforced
+        (_, _) => "VARCHAR".to_string(),
+    }
+}
+
+/// generate CREATE TABLE DDL for the given SQL dialect
+fn create_table_ddl(
+    schema_format: SchemaFormat,
+    table_name: &str,
+    column_stats: &[ColumnStats],
+) -> String {
+    let column_defs = column_stats
+        .iter()
+        .map(|col| {
+            let not_null = if col.nullcount == 0 && col.col_type != "NULL" {
+                " NOT NULL"
+            } else {
+                ""
+            };
+            format!(
+                "    {} {}{not_null}",
+                quote_sql_identifier(&col.name),
+                sql_column_type(schema_format, col)
+            )
+        })
+        .join(",\n");
+
+    format!(
+        "CREATE TABLE {} (\n{column_defs}\n);\n",
+        quote_sql_identifier(table_name)
+    )
+}
+
+/// generate Frictionless Table Schema, with constraints taken from the JSON Schema properties
+fn frictionless_table_schema(
+    args: &Args,
+    input_filename: &str,
+    column_stats: &[ColumnStats],
+    properties_map: &Map<String, Value>,
+) -> Value {
+    let mut fields: Vec<Value> = Vec::with_capacity(column_stats.len());
+
+    for col in column_stats {
+        let mut field = Map::new();
+        field.insert("name".to_string(), Value::String(col.name.clone()));
+
+        let (field_type, field_format) = match col.col_type.as_str() {
+            "Integer" => ("integer", None),
+            "Float" => ("number", None),
+            // the default Frictionless date formats are RFC-3339 dates,
+            // otherwise, let the consumer parse the date in any format
+            "Date" if args.flag_strict_dates => ("date", None),
+            "Date" => ("date", Some("any")),
+            "DateTime" if args.flag_strict_dates => ("datetime", None),
+            "DateTime" => ("datetime", Some("any")),
+            "NULL" => ("any", None),
+            _ => ("string", None),
+        };
+        field.insert("type".to_string(), Value::String(field_type.to_string()));
+        if let Some(field_format) = field_format {
+            field.insert(
+                "format".to_string(),
+                Value::String(field_format.to_string()),
+            );
+        }
+        field.insert(
+            "description".to_string(),
+            Value::String(format!("{} column from {input_filename}", col.name)),
+        );
+
+        let mut constraints = Map::new();
+        if col.nullcount == 0 && col.col_type != "NULL" {
+            constraints.insert("required".to_string(), Value::Bool(true));
+        }
+        if let Some(Value::Object(field_def)) = properties_map.get(&col.name) {
+            for key in ["minimum", "maximum", "minLength", "maxLength", "pattern"] {
+                if let Some(value) = field_def.get(key) {
+                    constraints.insert(key.to_string(), value.clone());
+                }
+            }
+            if let Some(Value::Array(enum_list)) = field_def.get("enum") {
+                // missing values are handled by the required constraint
+                let enum_list: Vec<Value> =
+                    enum_list.iter().filter(|v| !v.is_null()).cloned().collect();
+                constraints.insert("enum".to_string(), Value::Array(enum_list));
+            }
+        }
+        if !constraints.is_empty() {
+            field.insert("constraints".to_string(), Value::Object(constraints));
+        }
+
+        fields.push(Value::Object(field));
+    }
+
+    json!({
+        "fields": fields,
+        "missingValues": args.missing_values()
+    })
+}
+
+/// generate Avro record schema. As Avro names are restricted to [A-Za-z_][A-Za-z0-9_]*,
+/// unsafe column names are renamed, with the original name kept in the field's doc.
+fn avro_schema(input_filename: &str, column_stats: &[ColumnStats]) -> Value {
+    let headers: csv::StringRecord = column_stats.iter().map(|col| col.name.as_str()).collect();
+    let (safe_names, _) = util::safe_header_names(&headers, true, false, None, "_", true);
+
+    let record_stem = Path::new(input_filename)
+        .file_stem()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .to_string();
+    let (record_name, _) = util::safe_header_names(
+        &csv::StringRecord::from(vec![record_stem]),
+        true,
+        false,
+        None,
+        "_",
+        true,
+    );
+
+    let mut fields: Vec<Value> = Vec::with_capacity(column_stats.len());
+    for (col, safe_name) in column_stats.iter().zip(safe_names) {
+        let avro_type = match col.col_type.as_str() {
+            "Integer" if integer_width(col) <= 32 => json!("int"),
+            "Integer" => json!("long"),
+            "Float" => json!("double"),
+            "Date" => json!({"type": "int", "logicalType": "date"}),
+            "DateTime" => json!({"type": "long", "logicalType": "timestamp-millis"}),
+            "NULL" => json!("null"),
+            _ => json!("string"),
+        };
+
+        let mut field = json!({
+            "name": safe_name,
+            "doc": format!("{} column from {input_filename}", col.name),
+        });
+        if col.nullcount > 0 && col.col_type != "NULL" {
+            // optional fields are unions with "null" listed first, so "null" can be the default
+            field["type"] = json!(["null", avro_type]);
+            field["default"] = Value::Null;
+        } else {
+            field["type"] = avro_type;
+        }
+        fields.push(field);
+    }
+
+    json!({
+        "type": "record",
+        "name": record_name[0],
+        "doc": format!("Inferred Avro schema for {input_filename} from QSV schema command"),
+        "fields": fields
+    })
+}
+
+/// generate Polars schema JSON, mapping each column to its Polars data type
+fn polars_schema(args: &Args, column_stats: &[ColumnStats]) -> Value {
+    let mut fields = Map::with_capacity(column_stats.len());
+
+    for col in column_stats {
+        let dtype = match col.col_type.as_str() {
+            "Integer" => json!("Int64"),
+            "Float" => json!("Float64"),
+            "Date" if args.flag_strict_dates => json!("Date"),
+            "DateTime" if args.flag_strict_dates => json!({"Datetime": ["Milliseconds", null]}),
+            // polars reads all-empty columns as String as well
+            _ => json!("String"),
+        };
+        fields.insert(col.name.clone(), dtype);
+    }
+
+    json!({ "fields": fields })
+}
+
+/// the JSON types of a field definition, as a list
+fn field_types(field_def: &Map<String, Value>) -> Vec<String> {
+    match field_def.get("type") {
+        Some(Value::String(t)) => vec![t.clone()],
+        Some(Value::Array(types)) => types
+            .iter()
+            .filter_map(|t| t.as_str().map(ToString::to_string))
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// the relaxed JSON type that covers both non-null types
+fn relax_type(type1: &str, type2: &str) -> String {
+    match (type1, type2) {
+        (t1, t2) if t1 == t2 => t1,
+        ("integer", "number") | ("number", "integer") => "number",
+        // every CSV value is a valid string
+        _ => "string",
+    }
+    .to_string()
+}
+
+/// render a constraint for the change log, with an empty string for a missing constraint
+fn constraint_text(constraint: Option<&Value>) -> String {
+    constraint.map_or_else(String::new, ToString::to_string)
+}
+
+/// merge the JSON Schema properties inferred from an input into the schema so far,
+/// widening its constraints to cover the input. Changes are added to the change log.
+fn merge_properties(
+    properties_map: &mut Map<String, Value>,
+    required_fields: &mut Vec<Value>,
+    input_properties: &Map<String, Value>,
+    source: &str,
+    enum_threshold: usize,
+    schema_changes: &mut Vec<SchemaChange>,
+) {
+    let mut log_change = |field: &str, keyword: &str, from: String, to: String| {
+        schema_changes.push(SchemaChange {
+            source: source.to_string(),
+            field: field.to_string(),
+            keyword: keyword.to_string(),
+            from,
+            to,
+        });
+    };
+
+    for (field_name, input_def) in input_properties {
+        let Some(input_def) = input_def.as_object() else {
+            continue;
+        };
+        let Some(Value::Object(field_def)) = properties_map.get_mut(field_name) else {
+            // columns that are not in the schema so far are not required,
+            // as they're missing from the earlier inputs
+            properties_map.insert(field_name.clone(), Value::Object(input_def.clone()));
+            log_change(field_name, "column", String::new(), "added".to_string());
+            continue;
+        };
+
+        let types = field_types(field_def);
+        let input_types = field_types(input_def);
+        if types.is_empty() {
+            // the field is unconstrained, there's nothing to widen
+            continue;
+        }
+
+        let value_type = types.iter().find(|t| *t != "null").map(String::as_str);
+        let input_value_type = input_types
+            .iter()
+            .find(|t| *t != "null")
+            .map(String::as_str);
+        let nullable = types.iter().chain(&input_types).any(|t| t == "null");
+
+        let merged_type = match (value_type, input_value_type) {
+            (Some(t), Some(input_t)) => Some(relax_type(t, input_t)),
+            (Some(t), None) => Some(t.to_string()),
+            (None, Some(input_t)) => Some(input_t.to_string()),
+            (None, None) => None,
+        };
+        let mut merged_types: Vec<String> = merged_type.iter().cloned().collect();
+        if nullable {
+            merged_types.push("null".to_string());
+        }
+        // compare the types as sets, as a schema can use the scalar form of "type"
+        // or list its types in any order
+        let sorted_types = |mut types: Vec<String>| {
+            types.sort_unstable();
+            types.dedup();
+            types
+        };
+        if sorted_types(types.clone()) != sorted_types(merged_types.clone()) {
+            let merged_types = json!(merged_types);
+            log_change(
+                field_name,
+                "type",
+                constraint_text(field_def.get("type")),
+                merged_types.to_string(),
+            );
+            field_def.insert("type".to_string(), merged_types);
+        }
+
+        if input_value_type.is_none() {
+            // the input column is empty, so it has no value constraints to merge,
+            // though its enum may have to admit nulls now
+            if let Some(Value::Array(enum_list)) = field_def.get_mut("enum") {
+                if nullable && !enum_list.contains(&Value::Null) {
+                    enum_list.push(Value::Null);
+                }
+            }
+            continue;
+        }
+        if value_type.is_none() {
+            // the column was empty in the schema so far, take the input's value constraints
+            for (keyword, constraint) in input_def {
+                if keyword == "type" || keyword == "description" {
+                    continue;
+                }
+                let mut constraint = constraint.clone();
+                if let Value::Array(ref mut enum_list) = constraint {
+                    if keyword == "enum" && !enum_list.contains(&Value::Null) {
+                        enum_list.push(Value::Null);
+                    }
+                }
+                log_change(field_name, keyword, String::new(), constraint.to_string());
+                field_def.insert(keyword.clone(), constraint);
+            }
+            continue;
+        }
+
+        let is_numeric = matches!(merged_type.as_deref(), Some("integer" | "number"));
+        for keyword in ["minimum", "maximum", "minLength", "maxLength"] {
+            let Some(constraint) = field_def.get(keyword).cloned() else {
+                continue;
+            };
+            // value ranges don't apply to columns relaxed to string
+            let applies = is_numeric || keyword.ends_with("Length");
+            let widened = match (constraint.as_f64(), input_def.get(keyword)) {
+                (Some(current), Some(input_constraint)) if applies => {
+                    let input_value = input_constraint.as_f64().unwrap_or(current);
+                    let widen = if keyword.starts_with("min") {
+                        input_value < current
+                    } else {
+                        input_value > current
+                    };
+                    Some(if widen {
+                        input_constraint.clone()
+                    } else {
+                        constraint.clone()
+                    })
+                },
+                // the input is unbounded for this constraint
+                _ => None,
+            };
+            if widened.as_ref() != Some(&constraint) {
+                log_change(
+                    field_name,
+                    keyword,
+                    constraint.to_string(),
+                    constraint_text(widened.as_ref()),
+                );
+                match widened {
+                    Some(widened) => field_def.insert(keyword.to_string(), widened),
+                    None => field_def.remove(keyword),
+                };
+            }
+        }
+
+        if let Some(Value::Array(enum_list)) = field_def.get("enum").cloned() {
+            let merged_enum = match input_def.get("enum") {
+                Some(Value::Array(input_enum_list)) => {
+                    let mut merged_enum: Vec<Value> = Vec::with_capacity(enum_list.len());
+                    for value in enum_list.iter().chain(input_enum_list) {
+                        // enum values of a column relaxed to string are strings
+                        let value = match value {
+                            Value::Number(n) if !is_numeric => Value::String(n.to_string()),
+                            Value::Null => continue,
+                            _ => value.clone(),
+                        };
+                        if !merged_enum.contains(&value) {
+                            merged_enum.push(value);
+                        }
+                    }
+                    if merged_enum.len() > enum_threshold {
+                        None
+                    } else {
+                        if nullable {
+                            merged_enum.push(Value::Null);
+                        }
+                        Some(Value::Array(merged_enum))
+                    }
+                },
+                // the input has too many distinct values for an enum
+                _ => None,
+            };
+            if merged_enum.as_ref() != Some(&Value::Array(enum_list.clone())) {
+                log_change(
+                    field_name,
+                    "enum",
+                    Value::Array(enum_list).to_string(),
+                    constraint_text(merged_enum.as_ref()),
+                );
+                match merged_enum {
+                    Some(merged_enum) => field_def.insert("enum".to_string(), merged_enum),
+                    None => field_def.remove("enum"),
+                };
+            }
+        }
+
+        for keyword in ["pattern", "format"] {
+            if let Some(constraint) = field_def.get(keyword).cloned() {
+                if input_def.get(keyword) != Some(&constraint) {
+                    log_change(field_name, keyword, constraint.to_string(), String::new());
+                    field_def.remove(keyword);
+                }
+            }
+        }
+    }
+
+    // columns missing from the input are no longer required
+    required_fields.retain(|field| {
+        let field_name = field.as_str().unwrap_or_default();
+        if input_properties.contains_key(field_name) {
+            true
+        } else {
+            log_change(
+                field_name,
+                "required",
+                "true".to_string(),
+                "false".to_string(),
+            );
+            false
+        }
+    });
+}
+
+/// merge the column stats of an input into the column stats so far
+fn merge_column_stats(column_stats: &mut Vec<ColumnStats>, input_column_stats: Vec<ColumnStats>) {
+    // columns missing from some inputs are nullable
+    for col in column_stats.iter_mut() {
+        if !input_column_stats
+            .iter()
+            .any(|input_col| input_col.name == col.name)
+        {
+            col.nullcount = col.nullcount.max(1);
+        }
+    }
+
+    for mut input_col in input_column_stats {
+        let Some(col) = column_stats
+            .iter_mut()
+            .find(|col| col.name == input_col.name)
+        else {
+            input_col.nullcount = input_col.nullcount.max(1);
+            column_stats.push(input_col);
+            continue;
+        };
+
+        col.col_type = match (col.col_type.as_str(), input_col.col_type.as_str()) {
+            (t, input_t) if t == input_t => col.col_type.clone(),
+            ("NULL", _) => input_col.col_type.clone(),
+            (_, "NULL") => col.col_type.clone(),
+            ("Integer", "Float") | ("Float", "Integer") => "Float".to_string(),
+            ("Date", "DateTime") | ("DateTime", "Date") => "DateTime".to_string(),
+            _ => "String".to_string(),
+        };
+
+        let parse_bound = |bound: Option<&str>| bound.and_then(|b| b.parse::<f64>().ok());
+        match (
+            parse_bound(col.min.as_deref()),
+            parse_bound(input_col.min.as_deref()),
+        ) {
+            (Some(min), Some(input_min)) if input_min < min => col.min = input_col.min,
+            (None, Some(_)) => col.min = input_col.min,
+            _ => {},
+        }
+        match (
+            parse_bound(col.max.as_deref()),
+            parse_bound(input_col.max.as_deref()),
+        ) {
+            (Some(max), Some(input_max)) if input_max > max => col.max = input_col.max,
+            (None, Some(_)) => col.max = input_col.max,
+            _ => {},
+        }
+        col.nullcount += input_col.nullcount;
+        col.max_length = col.max_length.max(input_col.max_length);
+        col.max_precision = col.max_precision.max(input_col.max_precision);
+    }
+}
+
+/// write the schema change log as a TSV file
+fn write_schema_changes(changes_filename: &str, schema_changes: &[SchemaChange]) -> CliResult<()> {
+    let mut output_writer = BufWriter::new(File::create(changes_filename)?);
+
+    output_writer.write_all(b"source\tfield\tkeyword\tfrom\tto\n")?;
+    for change in schema_changes {
+        writeln!(
+            output_writer,
+            "{}\t{}\t{}\t{}\t{}",
+            change.source, change.field, change.keyword, change.from, change.to
+        )?;
+    }
+    output_writer.flush()?;
+
+    Ok(())
+}
@@ -0,0 +1,381 @@
+static USAGE: &str = r#"
+Compares the structure & statistics of two versions of a dataset to detect drift.
+
+Catches upstream changes before they break loads, by comparing the stats of a
+<baseline> and a <current> version of a dataset. It reports:
+  * added & removed columns
+  * renamed columns - a removed & an added column whose headers are similar
+    (requires the fuzzy feature - not available in qsvlite)
+  * type changes
+  * null rate (sparsity) changes
+  * cardinality shifts
+  * mean & percentile (q1, median, q3) drift
+beyond the configurable thresholds below.
+
+<baseline> and <current> can either be CSV files, or stats files produced by the
+`stats` command (files ending in `.stats.csv`), e.g. a baseline stats file committed
+to a repo. For CSV files, the stats are computed with
+`qsv stats --infer-dates --cardinality --quartiles`. Use the same options when saving a
+baseline stats file, as checks for stats that are missing from either file are skipped.
+
+The report is a CSV with the following columns:
+  field     the column's name in <baseline> (or in <current> for added columns)
+  change    added, removed, renamed, type, null_rate, cardinality, mean, q1, median or q3
+  baseline  the baseline value (the column's type for removed columns,
+            its baseline name for renamed columns)
+  current   the current value (the column's type for added columns,
+            its current name for renamed columns)
+  delta     the absolute change for null_rate, the relative change for cardinality,
+            mean & percentiles, and the header similarity for renamed columns
+
+If any difference is found, schemadiff exits with exit code 1 after writing the report,
+so it can be used to fail CI pipelines. Otherwise, only the report headers are written.
+
+Examples:
+Compare this month's extract against last month's:
+    $ qsv schemadiff sales-2024-01.csv sales-2024-02.csv
+
+Compare against a committed baseline, flagging null rate changes above 1%:
+    $ qsv stats --infer-dates --cardinality --quartiles sales.csv --output baseline.stats.csv
+    $ qsv schemadiff --null-threshold 0.01 baseline.stats.csv sales.csv
+
+For more examples, see https://github.com/jqnatividad/qsv/blob/master/tests/test_schemadiff.rs.
+
+Usage:
+    qsv schemadiff [options] <baseline> <current>
+    qsv schemadiff --help
+
+schemadiff options:
+    --rename-threshold <num>       The minimum header similarity (0 to 1) for a removed
+                                   & an added column to be reported as a renamed
+                                   column. Similarity is the normalized
+                                   Damerau-Levenshtein similarity of the lowercased
+                                   headers. Set above 1 to disable rename detection.
+                                   [default: 0.8]
+    --null-threshold <num>         The maximum absolute change in a column's null rate
+                                   (e.g. 0.05 is 5 percentage points). [default: 0.05]
+    --cardinality-threshold <num>  The maximum relative change in a column's cardinality
+                                   (e.g. 0.2 is 20%). [default: 0.2]
+    --drift-threshold <num>        The maximum relative change in a column's mean &
+                                   percentiles. [default: 0.1]
+
+Common options:
+    -h, --help                     Display this message
+    -o, --output <file>            Write the report to <file> instead of stdout.
+    -d, --delimiter <arg>          The field delimiter for reading CSV data.
+                                   Must be a single character. (default: ,)
+"#;
+
+use std::path::Path;
+
+use serde::Deserialize;
+
+use crate::{
+    config::{Config, Delimiter},
+    util, CliResult,
+};
+
+#[derive(Deserialize)]
+struct Args {
+    arg_baseline:               String,
+    arg_current:                String,
+    flag_rename_threshold:      f64,
+    flag_null_threshold:        f64,
+    flag_cardinality_threshold: f64,
+    flag_drift_threshold:       f64,
+    flag_output:                Option<String>,
+    flag_delimiter:             Option<Delimiter>,
+}
+
+/// the stats of a column that are compared
+struct ColumnProfile {
+    field:       String,
+    typ:         String,
+    sparsity:    Option<f64>,
+    cardinality: Option<f64>,
+    // mean, q1, median & q3
+    measures:    [Option<f64>; 4],
+}
+
+const MEASURES: [&str; 4] = ["mean", "q1", "median", "q3"];
+
+pub fn run(argv: &[&str]) -> CliResult<()> {
+    let args: Args = util::get_args(USAGE, argv)?;
+
+    for (threshold, option) in [
+        (args.flag_rename_threshold, "--rename-threshold"),
+        (args.flag_null_threshold, "--null-threshold"),
+        (args.flag_cardinality_threshold, "--cardinality-threshold"),
+        (args.flag_drift_threshold, "--drift-threshold"),
+    ] {
+        if threshold.is_nan() || threshold < 0.0 {
+            return fail_incorrectusage_clierror!("{option} must be a non-negative number.");
+        }
+    }
+
+    let baseline = load_profiles(&args, &args.arg_baseline)?;
+    let current = load_profiles(&args, &args.arg_current)?;
+
+    let mut wtr = Config::new(&args.flag_output).writer()?;
+    wtr.write_record(["field", "change", "baseline", "current", "delta"])?;
+
+    // match columns by name first
+    let mut matched: Vec<(usize, usize)> = Vec::with_capacity(baseline.len());
+    let mut removed: Vec<usize> = Vec::new();
+    for (i, baseline_col) in baseline.iter().enumerate() {
+        match current
+            .iter()
+            .position(|col| col.field == baseline_col.field)
+        {
+            Some(j) => matched.push((i, j)),
+            None => removed.push(i),
+        }
+    }
+    let mut added: Vec<usize> = (0..current.len())
+        .filter(|j| !matched.iter().any(|(_, matched_j)| matched_j == j))
+        .collect();
+
+    let renamed = pair_renamed(
+        &baseline,
+        &current,
+        &mut removed,
+        &mut added,
+        args.flag_rename_threshold,
+    );
+
+    let mut difference_count = 0_u64;
+    let mut write_difference = |record: [&str; 5]| -> CliResult<()> {
+        difference_count += 1;
+        Ok(wtr.write_record(record)?)
+    };
+
+    for (i, baseline_col) in baseline.iter().enumerate() {
+        if removed.contains(&i) {
+            write_difference([&baseline_col.field, "removed", &baseline_col.typ, "", ""])?;
+            continue;
+        }
+        let current_col = if let Some(&(_, j, similarity)) = renamed.iter().find(|r| r.0 == i) {
+            write_difference([
+                &baseline_col.field,
+                "renamed",
+                &baseline_col.field,
+                &current[j].field,
+                &util::round_num(similarity, 4),
+            ])?;
+            &current[j]
+        } else {
+            let (_, j) = matched.iter().find(|m| m.0 == i).unwrap();
+            &current[*j]
+        };
+
+        if baseline_col.typ != current_col.typ {
+            write_difference([
+                &baseline_col.field,
+                "type",
+                &baseline_col.typ,
+                &current_col.typ,
+                "",
+            ])?;
+        }
+
+        if let (Some(baseline_sparsity), Some(current_sparsity)) =
+            (baseline_col.sparsity, current_col.sparsity)
+        {
+            let delta = current_sparsity - baseline_sparsity;
+            if delta.abs() > args.flag_null_threshold {
+                write_difference([
+                    &baseline_col.field,
+                    "null_rate",
+                    &util::round_num(baseline_sparsity, 4),
+                    &util::round_num(current_sparsity, 4),
+                    &util::round_num(delta, 4),
+                ])?;
+            }
+        }
+
+        if let (Some(baseline_cardinality), Some(current_cardinality)) =
+            (baseline_col.cardinality, current_col.cardinality)
+        {
+            let delta = relative_change(baseline_cardinality, current_cardinality);
+            if delta.abs() > args.flag_cardinality_threshold {
+                write_difference([
+                    &baseline_col.field,
+                    "cardinality",
+                    &baseline_cardinality.to_string(),
+                    &current_cardinality.to_string(),
+                    &util::round_num(delta, 4),
+                ])?;
+            }
+        }
+
+        // measures are only comparable if the column is still numeric
+        if baseline_col.typ == current_col.typ
+            || is_numeric(&baseline_col.typ) && is_numeric(&current_col.typ)
+        {
+            for (k, measure) in MEASURES.iter().enumerate() {
+                if let (Some(baseline_value), Some(current_value)) =
+                    (baseline_col.measures[k], current_col.measures[k])
+                {
+                    let delta = relative_change(baseline_value, current_value);
+                    if delta.abs() > args.flag_drift_threshold {
+                        write_difference([
+                            &baseline_col.field,
+                            measure,
+                            &baseline_value.to_string(),
+                            &current_value.to_string(),
+                            &util::round_num(delta, 4),
+                        ])?;
+                    }
+                }
+            }
+        }
+    }
+
+    for j in added {
+        write_difference([&current[j].field, "added", "", &current[j].typ, ""])?;
+    }
+
+    wtr.flush()?;
+
+    if difference_count > 0 {
+        return fail_clierror!(
+            "{difference_count} difference/s found between {} and {}.",
+            args.arg_baseline,
+            args.arg_current
+        );
+    }
+    winfo!("No differences found.");
+    Ok(())
+}
+
+/// pair up removed & added columns with similar headers as renamed columns,
+/// most similar pairs first. Returns (baseline index, current index, similarity) triples.
+#[cfg(feature = "fuzzy")]
+fn pair_renamed(
+    baseline: &[ColumnProfile],
+    current: &[ColumnProfile],
+    removed: &mut Vec<usize>,
+    added: &mut Vec<usize>,
+    threshold: f64,
+) -> Vec<(usize, usize, f64)> {
+    let mut rename_candidates: Vec<(f64, usize, usize)> = Vec::new();
+    for &i in removed.iter() {
+        for &j in added.iter() {
+            let similarity = strsim::normalized_damerau_levenshtein(
+                &baseline[i].field.to_lowercase(),
+                &current[j].field.to_lowercase(),
+            );
+            if similarity >= threshold {
+                rename_candidates.push((similarity, i, j));
+            }
+        }
+    }
+    rename_candidates.sort_by(|a, b| b.0.total_cmp(&a.0));
+    let mut renamed: Vec<(usize, usize, f64)> = Vec::new();
+    for (similarity, i, j) in rename_candidates {
+        if removed.contains(&i) && added.contains(&j) {
+            removed.retain(|&r| r != i);
+            added.retain(|&a| a != j);
+            renamed.push((i, j, similarity));
+        }
+    }
+    renamed
+}
+
+/// without the fuzzy feature, removed & added columns are never paired up as renamed
+#[cfg(not(feature = "fuzzy"))]
+fn pair_renamed(
+    _baseline: &[ColumnProfile],
+    _current: &[ColumnProfile],
+    _removed: &mut Vec<usize>,
+    _added: &mut Vec<usize>,
+    _threshold: f64,
+) -> Vec<(usize, usize, f64)> {
+    Vec::new()
+}
+
+#[inline]
+fn is_numeric(typ: &str) -> bool {
+    typ == "Integer" || typ == "Float"
+}
+
+/// the change relative to the baseline. If the baseline is zero, the absolute change.
+#[inline]
+fn relative_change(baseline: f64, current: f64) -> f64 {
+    if baseline == 0.0 {
+        current
+    } else {
+        (current - baseline) / baseline.abs()
+    }
+}
+
+/// load the column profiles from a stats file, or compute the stats of a CSV file first
+fn load_profiles(args: &Args, path: &str) -> CliResult<Vec<ColumnProfile>> {
+    if !Path::new(path).exists() {
+        return fail_incorrectusage_clierror!("{path} does not exist.");
+    }
+
+    let stats_tempfile;
+    let stats_path = if path.to_lowercase().ends_with(".stats.csv") {
+        path.to_string()
+    } else {
+        stats_tempfile = tempfile::Builder::new().suffix(".stats.csv").tempfile()?;
+        let stats_path = stats_tempfile.path().to_string_lossy().to_string();
+
+        let mut stats_cmd = std::process::Command::new(std::env::current_exe()?);
+        stats_cmd.args([
+            "stats",
+            path,
+            "--infer-dates",
+            "--cardinality",
+            "--quartiles",
+            "--output",
+            &stats_path,
+        ]);
+        if let Some(delimiter) = args.flag_delimiter {
+            stats_cmd.args(["--delimiter", &(delimiter.as_byte() as char).to_string()]);
+        }
+        let stats_output = stats_cmd.output()?;
+        if !stats_output.status.success() {
+            return fail_clierror!(
+                "Cannot compute the stats of {path}: {}",
+                String::from_utf8_lossy(&stats_output.stderr).trim()
+            );
+        }
+        stats_path
+    };
+
+    let mut rdr = Config::new(&Some(stats_path))
+        .delimiter(Some(Delimiter(b',')))
+        .reader()?;
+    let headers = rdr.headers()?.clone();
+    let col_index = |name: &str| headers.iter().position(|h| h == name);
+    let (Some(field_idx), Some(type_idx)) = (col_index("field"), col_index("type")) else {
+        return fail_incorrectusage_clierror!(
+            "{path} is not a stats file. It needs \"field\" & \"type\" columns."
+        );
+    };
+    let sparsity_idx = col_index("sparsity");
+    let cardinality_idx = col_index("cardinality");
+    let measure_idxs = [
+        col_index("mean"),
+        col_index("q1"),
+        col_index("q2_median").or_else(|| col_index("median")),
+        col_index("q3"),
+    ];
+
+    let mut profiles = Vec::new();
+    for record in rdr.records() {
+        let record = record?;
+        let get_number = |idx: Option<usize>| idx.and_then(|i| record.get(i)?.parse::<f64>().ok());
+        profiles.push(ColumnProfile {
+            field:       record.get(field_idx).unwrap_or_default().to_string(),
+            typ:         record.get(type_idx).unwrap_or_default().to_string(),
+            sparsity:    get_number(sparsity_idx),
+            cardinality: get_number(cardinality_idx),
+            measures:    measure_idxs.map(get_number),
+        });
+    }
+
+    Ok(profiles)
+}
@@ -5,6 +5,32 @@ reverse, unique or random (with optional seed) order.
 The sort is done in lexicographical order.
 https://en.wikipedia.org/wiki/Lexicographic_order
 
+For more control, the --keys option takes a comma-separated list of sort keys, each
+with its own type, direction & NULL placement, in the form:
+
+    column[:type][:asc|desc][:nullsfirst|nullslast]
+
+  column  The column to sort on, by name or index (starting at 1).
+  type    How the values of the column are compared:
+            str      lexicographically (the default)
+            nocase   lexicographically, disregarding case
+            num      numerically
+            natural  "naturally", comparing runs of digits numerically, so
+                     "file2" < "file10" and version "1.9" < "1.10"
+            date     as dates & datetimes recognized by qsv-dateparser. Set the
+                     QSV_PREFER_DMY environment variable to parse ambiguous dates
+                     as day/month/year.
+          With num & date keys, values that cannot be parsed sort after all the
+          parsed values.
+  asc|desc  The sort direction. (default: asc)
+  nullsfirst|nullslast  Where empty values sort, regardless of the direction.
+                        (default: nullslast)
+
+e.g. "date:date:desc,region:natural,amount:num:desc:nullsfirst"
+
+The same key specs are accepted by the sortcheck & dedup commands, so pipelines
+that need sorted data can check & dedupe using the exact same ordering.
+
 Note that this requires reading all of the CSV data into memory. If
 you need to sort a large file that may not fit into memory, use the
 extsort command instead.
@@ -18,9 +44,14 @@ Usage:
 sort options:
     -s, --select <arg>      Select a subset of columns to sort.
                             See 'qsv select --help' for the format details.
+    -k, --keys <spec>       Sort on a list of sort keys, each with its own type,
+                            direction & NULL placement. See above for details.
+                            Cannot be used with --select, --numeric, --reverse
+                            or --random.
     -N, --numeric           Compare according to string numerical value
     -R, --reverse           Reverse order
-    -i, --ignore-case       Compare strings disregarding case
+    -i, --ignore-case       Compare strings disregarding case. With --keys, str &
+                            natural keys are compared disregarding case.
     -u, --unique            When set, identical consecutive lines will be dropped
                             to keep only one line per sorted value.
 
@@ -63,9 +94,10 @@ Common options:
                             CSV into memory using CONSERVATIVE heuristics.
 "#;
 
-use std::{cmp, str::FromStr};
+use std::{cmp, str, str::FromStr};
 
 // use fastrand; //DevSkim: ignore DS148264
+use qsv_dateparser::parse_with_preference;
 use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
 use rand_hc::Hc128Rng;
 use rand_xoshiro::Xoshiro256Plus;
@@ -85,7 +117,8 @@ use crate::{
 #[derive(Deserialize)]
 struct Args {
     arg_input:        Option<String>,
-    flag_select:      SelectColumns,
+    flag_select:      Option<SelectColumns>,
+    flag_keys:        Option<String>,
     flag_numeric:     bool,
     flag_reverse:     bool,
     flag_ignore_case: bool,
@@ -115,10 +148,18 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
     let reverse = args.flag_reverse;
     let random = args.flag_random;
     let faster = args.flag_faster;
+    if args.flag_keys.is_some() && (args.flag_select.is_some() || numeric || reverse || random) {
+        return fail_incorrectusage_clierror!(
+            "--keys cannot be used with --select, --numeric, --reverse or --random."
+        );
+    }
     let rconfig = Config::new(&args.arg_input)
         .delimiter(args.flag_delimiter)
         .no_headers(args.flag_no_headers)
-        .select(args.flag_select);
+        .select(
+            args.flag_select
+                .unwrap_or_else(|| SelectColumns::parse("").unwrap()),
+        );
 
     let Ok(rng_kind) = RngKind::from_str(&args.flag_rng) else {
         return fail_incorrectusage_clierror!(
@@ -150,7 +191,33 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
     let ignore_case = args.flag_ignore_case;
 
     let mut all = rdr.byte_records().collect::<Result<Vec<_>, _>>()?;
+    let sort_keys = match &args.flag_keys {
+        Some(spec) => Some(SortKeys::new(
+            spec,
+            &headers,
+            rconfig.no_headers,
+            ignore_case,
+            rconfig.get_dmy_preference(),
+        )?),
+        None => None,
+    };
     match (numeric, reverse, random, faster) {
+        // --keys sort
+        _ if sort_keys.is_some() => {
+            let sort_keys = sort_keys.as_ref().unwrap();
+            // parse the sort keys of each record once, instead of on every comparison
+            let mut keyed: Vec<_> = all
+                .drain(..)
+                .map(|r| (sort_keys.key_values(&r), r))
+                .collect();
+            if faster {
+                keyed.par_sort_unstable_by(|(k1, _), (k2, _)| sort_keys.compare(k1, k2));
+            } else {
+                keyed.par_sort_by(|(k1, _), (k2, _)| sort_keys.compare(k1, k2));
+            }
+            all.extend(keyed.into_iter().map(|(_, r)| r));
+        },
+
         // --random sort
         (_, _, true, _) => {
             match rng_kind {
@@ -265,11 +332,14 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
     for r in all {
         if args.flag_unique {
             match prev {
-                Some(other_r) => match iter_cmp(sel.select(&r), sel.select(&other_r)) {
-                    cmp::Ordering::Equal => (),
-                    _ => {
+                Some(other_r) => {
+                    let comparison = match &sort_keys {
+                        Some(sort_keys) => sort_keys.compare_records(&r, &other_r),
+                        None => iter_cmp(sel.select(&r), sel.select(&other_r)),
+                    };
+                    if comparison != cmp::Ordering::Equal {
                         wtr.write_byte_record(&r)?;
-                    },
+                    }
                 },
                 None => {
                     wtr.write_byte_record(&r)?;
@@ -369,3 +439,209 @@ where
         None => None,
     }
 }
+
+/// The value type of a sort key, as set in a sort key spec.
+#[derive(Clone, Copy, Debug, EnumString, PartialEq)]
+#[strum(ascii_case_insensitive)]
+enum KeyType {
+    Str,
+    NoCase,
+    Num,
+    Natural,
+    Date,
+}
+
+#[derive(Debug)]
+struct SortKey {
+    col:         usize,
+    key_type:    KeyType,
+    descending:  bool,
+    nulls_first: bool,
+}
+
+/// A parsed sort key value. Empty values are `Null`, and values of num & date
+/// keys that cannot be parsed are `Invalid`.
+#[derive(Clone, Debug)]
+pub enum KeyValue {
+    Null,
+    Bytes(Vec<u8>),
+    Num(f64),
+    Date(i64),
+    Invalid(Vec<u8>),
+}
+
+/// A list of sort keys parsed from a `--keys` spec, shared by the sort, sortcheck
+/// & dedup commands so they all agree on the same ordering.
+#[derive(Debug)]
+pub struct SortKeys {
+    keys:        Vec<SortKey>,
+    ignore_case: bool,
+    prefer_dmy:  bool,
+}
+
+impl SortKeys {
+    /// Parse a comma-separated list of `column[:type][:asc|desc][:nullsfirst|nullslast]`
+    /// sort key specs.
+    pub fn new(
+        spec: &str,
+        headers: &csv::ByteRecord,
+        no_headers: bool,
+        ignore_case: bool,
+        prefer_dmy: bool,
+    ) -> Result<SortKeys, String> {
+        let mut keys = Vec::new();
+        for key_spec in spec.split(',') {
+            let mut parts: Vec<&str> = key_spec.split(':').collect();
+            let (mut key_type, mut descending, mut nulls_first) = (None, None, None);
+            // parse the options from the right, so column names can contain colons
+            while parts.len() > 1 {
+                let part = parts[parts.len() - 1].trim();
+                match part.to_ascii_lowercase().as_str() {
+                    "asc" if descending.is_none() => descending = Some(false),
+                    "desc" if descending.is_none() => descending = Some(true),
+                    "nullsfirst" if nulls_first.is_none() => nulls_first = Some(true),
+                    "nullslast" if nulls_first.is_none() => nulls_first = Some(false),
+                    _ => match KeyType::from_str(part) {
+                        Ok(kt) if key_type.is_none() => key_type = Some(kt),
+                        _ => break,
+                    },
+                }
+                parts.pop();
+            }
+            let column = parts.join(":");
+            let sel = SelectColumns::parse(column.trim())?.selection(headers, !no_headers)?;
+            if sel.len() != 1 {
+                return fail_format!(
+                    "Invalid sort key `{key_spec}`. Each sort key must select exactly one column."
+                );
+            }
+            keys.push(SortKey {
+                col:         sel[0],
+                key_type:    key_type.unwrap_or(KeyType::Str),
+                descending:  descending.unwrap_or(false),
+                nulls_first: nulls_first.unwrap_or(false),
+            });
+        }
+        Ok(SortKeys {
+            keys,
+            ignore_case,
+            prefer_dmy,
+        })
+    }
+
+    /// Parse the sort key values of a record, so they can be compared repeatedly.
+    pub fn key_values(&self, record: &csv::ByteRecord) -> Vec<KeyValue> {
+        self.keys
+            .iter()
+            .map(|key| {
+                let field = record.get(key.col).unwrap_or_default();
+                if field.iter().all(u8::is_ascii_whitespace) {
+                    return KeyValue::Null;
+                }
+                let value = from_utf8(field).map_or("", str::trim);
+                match key.key_type {
+                    KeyType::Str | KeyType::Natural if !self.ignore_case => {
+                        KeyValue::Bytes(field.to_vec())
+                    },
+                    KeyType::Str | KeyType::NoCase | KeyType::Natural => {
+                        KeyValue::Bytes(String::from_utf8_lossy(field).to_lowercase().into_bytes())
+                    },
+                    KeyType::Num => match value.parse::<f64>() {
+                        Ok(n) if !n.is_nan() => KeyValue::Num(n),
+                        _ => KeyValue::Invalid(field.to_vec()),
+                    },
+                    KeyType::Date => match parse_with_preference(value, self.prefer_dmy) {
+                        Ok(dt) => KeyValue::Date(dt.timestamp_micros()),
+                        Err(_) => KeyValue::Invalid(field.to_vec()),
+                    },
+                }
+            })
+            .collect()
+    }
+
+    /// Compare the parsed sort key values of two records.
+    pub fn compare(&self, a: &[KeyValue], b: &[KeyValue]) -> cmp::Ordering {
+        for (key, (x, y)) in self.keys.iter().zip(a.iter().zip(b.iter())) {
+            let ordering = match (x, y) {
+                (KeyValue::Null, KeyValue::Null) => cmp::Ordering::Equal,
+                // NULL placement does not depend on the sort direction
+                (KeyValue::Null, _) if key.nulls_first => return cmp::Ordering::Less,
+                (KeyValue::Null, _) => return cmp::Ordering::Greater,
+                (_, KeyValue::Null) if key.nulls_first => return cmp::Ordering::Greater,
+                (_, KeyValue::Null) => return cmp::Ordering::Less,
+                (KeyValue::Bytes(x), KeyValue::Bytes(y)) => {
+                    if key.key_type == KeyType::Natural {
+                        natural_cmp(x, y)
+                    } else {
+                        x.cmp(y)
+                    }
+                },
+                (KeyValue::Num(x), KeyValue::Num(y)) => compare_float(*x, *y),
+                (KeyValue::Date(x), KeyValue::Date(y)) => x.cmp(y),
+                (KeyValue::Invalid(x), KeyValue::Invalid(y)) => x.cmp(y),
+                (KeyValue::Invalid(_), _) => cmp::Ordering::Greater,
+                (_, KeyValue::Invalid(_)) => cmp::Ordering::Less,
+                // a key always parses to the same kind of value
+                _ => cmp::Ordering::Equal,
+            };
+            let ordering = if key.descending {
+                ordering.reverse()
+            } else {
+                ordering
+            };
+            if ordering != cmp::Ordering::Equal {
+                return ordering;
+            }
+        }
+        cmp::Ordering::Equal
+    }
+
+    /// Compare two records on the sort keys, parsing their key values first.
+    #[inline]
+    pub fn compare_records(&self, a: &csv::ByteRecord, b: &csv::ByteRecord) -> cmp::Ordering {
+        self.compare(&self.key_values(a), &self.key_values(b))
+    }
+}
+
+/// Natural ordering - runs of ASCII digits are compared by their numeric value,
+/// everything else byte by byte. e.g. "file2" < "file10" and "1.9" < "1.10".
+fn natural_cmp(a: &[u8], b: &[u8]) -> cmp::Ordering {
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() && j < b.len() {
+        if a[i].is_ascii_digit() && b[j].is_ascii_digit() {
+            let start_a = i;
+            let start_b = j;
+            while i < a.len() && a[i].is_ascii_digit() {
+                i += 1;
+            }
+            while j < b.len() && b[j].is_ascii_digit() {
+                j += 1;
+            }
+            // ignore leading zeros, then the longer run of digits is the larger number
+            let digits_a = trim_leading_zeros(&a[start_a..i]);
+            let digits_b = trim_leading_zeros(&b[start_b..j]);
+            let ordering = digits_a
+                .len()
+                .cmp(&digits_b.len())
+                .then_with(|| digits_a.cmp(digits_b));
+            if ordering != cmp::Ordering::Equal {
+                return ordering;
+            }
+        } else {
+            match a[i].cmp(&b[j]) {
+                cmp::Ordering::Equal => {
+                    i += 1;
+                    j += 1;
+                },
+                non_eq => return non_eq,
+            }
+        }
+    }
+    (a.len() - i).cmp(&(b.len() - j))
+}
+
+#[inline]
+fn trim_leading_zeros(digits: &[u8]) -> &[u8] {
+    let zeros = digits.iter().take_while(|&&d| d == b'0').count();
+    &digits[zeros..]
+}
@@ -29,6 +29,10 @@ Usage:
 sort options:
     -s, --select <arg>      Select a subset of columns to check for sort.
                             See 'qsv select --help' for the format details.
+    -k, --keys <spec>       Check the sort order on a list of sort keys, each with
+                            its own type, direction & NULL placement, using the
+                            same key spec as 'qsv sort --keys'. Overrides --select.
+                            See 'qsv sort --help' for the format details.
     -i, --ignore-case       Compare strings disregarding case
     --all                   Check all records. Do not stop/short-circuit the check 
                             on the first unsorted record.
@@ -62,7 +66,10 @@ use indicatif::{HumanCount, ProgressBar, ProgressDrawTarget};
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    cmd::{dedup, sort::iter_cmp},
+    cmd::{
+        dedup,
+        sort::{iter_cmp, SortKeys},
+    },
     config::{Config, Delimiter},
     select::SelectColumns,
     util, CliResult,
@@ -73,6 +80,7 @@ use crate::{
 struct Args {
     arg_input:        Option<String>,
     flag_select:      SelectColumns,
+    flag_keys:        Option<String>,
     flag_ignore_case: bool,
     flag_all:         bool,
     flag_no_headers:  bool,
@@ -102,6 +110,16 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
 
     let headers = rdr.byte_headers()?.clone();
     let sel = rconfig.selection(&headers)?;
+    let sort_keys = match &args.flag_keys {
+        Some(spec) => Some(SortKeys::new(
+            spec,
+            &headers,
+            rconfig.no_headers,
+            ignore_case,
+            rconfig.get_dmy_preference(),
+        )?),
+        None => None,
+    };
     let record_count;
 
     // prep progress bar
@@ -146,12 +164,16 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
         if !more_records {
             break;
         };
-        let a = sel.select(&record);
-        let b = sel.select(&next_record);
-        let comparison = if ignore_case {
-            dedup::iter_cmp_ignore_case(a, b)
+        let comparison = if let Some(sort_keys) = &sort_keys {
+            sort_keys.compare_records(&record, &next_record)
         } else {
-            iter_cmp(a, b)
+            let a = sel.select(&record);
+            let b = sel.select(&next_record);
+            if ignore_case {
+                dedup::iter_cmp_ignore_case(a, b)
+            } else {
+                iter_cmp(a, b)
+            }
         };
 
         match comparison {
@@ -162,6 +162,11 @@ Common options:
                            in statistics.
     -d, --delimiter <arg>  The field delimiter for reading CSV data.
                            Must be a single character. (default: ,)
+    --null-values <list>   A comma-delimited list of case-sensitive values to treat
+                           as NULL, besides the empty string (e.g. NA,NULL,\N).
+                           They are counted in nullcount & sparsity, and excluded
+                           from the other statistics.
+                           Overrides the QSV_NULL_VALUES env var.
     --memcheck             Check if there is enough memory to load the entire
                            CSV into memory using CONSERVATIVE heuristics.
                            This option is ignored when computing default, streaming
@@ -237,6 +242,7 @@ pub struct Args {
     pub flag_output:          Option<String>,
     pub flag_no_headers:      bool,
     pub flag_delimiter:       Option<Delimiter>,
+    pub flag_null_values:     Option<String>,
     pub flag_memcheck:        bool,
 }
 
@@ -262,6 +268,8 @@ struct StatsArgs {
     flag_prefer_dmy:      bool,
     flag_no_headers:      bool,
     flag_delimiter:       String,
+    #[serde(default)]
+    flag_null_values:     String,
     flag_output_snappy:   bool,
     canonical_input_path: String,
     canonical_stats_path: String,
@@ -331,6 +339,7 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
         flag_prefer_dmy:      args.flag_prefer_dmy,
         flag_no_headers:      args.flag_no_headers,
         flag_delimiter:       format!("{:?}", args.flag_delimiter.clone()),
+        flag_null_values:     args.rconfig().get_null_values().to_list(),
         // when we write to stdout, we don't use snappy compression
         // when we write to a file with the --output option, we use
         // snappy compression if the file ends with ".sz"
@@ -466,6 +475,8 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
                             == current_stats_args.flag_dates_whitelist
                         && existing_stats_args_json.flag_delimiter
                             == current_stats_args.flag_delimiter
+                        && existing_stats_args_json.flag_null_values
+                            == current_stats_args.flag_null_values
                         && existing_stats_args_json.flag_nulls == current_stats_args.flag_nulls
                         && existing_stats_args_json.qsv_version == current_stats_args.qsv_version)
             {
@@ -778,6 +789,9 @@ impl Args {
         // and hopefully the compiler will optimize this and use registers in the hot loop
         let infer_boolean = self.flag_infer_boolean;
         let prefer_dmy = self.flag_prefer_dmy;
+        let null_values = self.rconfig().get_null_values().clone();
+        // skip the NULL value checks altogether when there are no NULL values set
+        let check_null_values = !null_values.is_empty();
 
         let mut i;
         for row in it {
@@ -786,8 +800,13 @@ impl Args {
             // we know we don't need to bounds check
             unsafe {
                 for field in sel.select(&row.unwrap_unchecked()) {
+                    // NULL values are added as empty fields
                     stats.get_unchecked_mut(i).add(
-                        field,
+                        if check_null_values && null_values.is_null(field) {
+                            b""
+                        } else {
+                            field
+                        },
                         *infer_date_flags.get_unchecked(i),
                         infer_boolean,
                         prefer_dmy,
@@ -815,6 +834,7 @@ impl Args {
             .delimiter(self.flag_delimiter)
             .no_headers(self.flag_no_headers)
             .select(self.flag_select.clone())
+            .null_values(self.flag_null_values.clone())
     }
 
     #[inline]
@@ -34,6 +34,10 @@ Common options:
     -d, --delimiter <arg>  The field delimiter for reading CSV data.
                            Must be a single character. (default: ,)
     -o, --output <file>    Write output to <file> instead of stdout.
+    --null-values <list>   A comma-delimited list of case-sensitive values to treat
+                           as NULL, besides the empty string (e.g. NA,NULL,\N).
+                           They are converted to JSON null.
+                           Overrides the QSV_NULL_VALUES env var.
     --memcheck             Check if there is enough memory to load the entire
                            CSV into memory using CONSERVATIVE heuristics.
 "#;
@@ -56,14 +60,15 @@ use crate::{
 
 #[derive(Deserialize, Clone)]
 struct Args {
-    arg_input:       Option<String>,
-    flag_trim:       bool,
-    flag_no_boolean: bool,
-    flag_jobs:       Option<usize>,
-    flag_batch:      u32,
-    flag_delimiter:  Option<Delimiter>,
-    flag_output:     Option<String>,
-    flag_memcheck:   bool,
+    arg_input:        Option<String>,
+    flag_trim:        bool,
+    flag_no_boolean:  bool,
+    flag_jobs:        Option<usize>,
+    flag_batch:       u32,
+    flag_delimiter:   Option<Delimiter>,
+    flag_output:      Option<String>,
+    flag_null_values: Option<String>,
+    flag_memcheck:    bool,
 }
 
 impl From<std::fmt::Error> for CliError {
@@ -101,7 +106,9 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
         .into_os_string()
         .into_string()
         .unwrap();
-    let conf = Config::new(&Some(input_filename.clone())).delimiter(args.flag_delimiter);
+    let conf = Config::new(&Some(input_filename.clone()))
+        .delimiter(args.flag_delimiter)
+        .null_values(args.flag_null_values.clone());
 
     // we're loading the entire file into memory, we need to check avail mem
     util::mem_file_check(
@@ -114,6 +121,7 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
 
     // we're calling the schema command to infer data types and enums
     let schema_args = crate::cmd::schema::Args {
+        flag_format:          "jsonschema".to_string(),
         // we only do three, as we're only inferring boolean based on enum
         // i.e. we only inspect a field if its boolean if its domain
         // is just two values. if its more than 2, that's all we need know
@@ -134,7 +142,9 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
         flag_jobs:            Some(util::njobs(args.flag_jobs)),
         flag_no_headers:      false,
         flag_delimiter:       args.flag_delimiter,
-        arg_input:            Some(input_filename.clone()),
+        arg_input:            vec![input_filename.clone()],
+        flag_base:            None,
+        flag_null_values:     args.flag_null_values.clone(),
         flag_memcheck:        args.flag_memcheck,
     };
     // build schema for each field by their inferred type, min/max value/length, and unique values
@@ -147,6 +157,8 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
         };
 
     let mut rdr = conf.reader()?;
+    let null_values = conf.get_null_values().clone();
+    let check_null_values = !null_values.is_empty();
 
     // TODO: instead of abusing csv writer to write jsonl file
     // just use a normal buffered writer
@@ -303,6 +315,12 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
                 }
                 write!(json_string, "{{").unwrap();
                 for (idx, field) in record.iter().enumerate() {
+                    // NULL values are converted like empty fields
+                    let field = if check_null_values && null_values.is_null(field.as_bytes()) {
+                        ""
+                    } else {
+                        field
+                    };
                     let field_val = if let Some(field_type) = field_type_vec.get(idx) {
                         match field_type {
                             JsonlType::String => {
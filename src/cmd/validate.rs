@@ -51,12 +51,90 @@ If piped from stdin, the filenames will use `stdin.csv` as the base filename. Fo
    * stdin.csv.invalid
    * stdin.csv.validation-errors.tsv
 
+Besides the standard JSON Schema keywords, the schema can use these qsv keywords:
+
+  * dynamicEnum: the value must be one of the values of a column of a local CSV file,
+    so large lookup tables don't have to be pasted into the schema as an "enum".
+    Set it to the path of the CSV to use its first column, e.g. "dynamicEnum": "codes.csv",
+    or to an object to use another column, by name or 0-based index, e.g.
+    "dynamicEnum": {"file": "codes.csv", "column": "iso_code"}
+  * currency: if true, the value must be a currency amount, e.g. "$1,234.56" or "€100".
+    Requires the apply feature, so it is not available in qsvlite & qsvdp.
+  * dateFormat: the value must be a date in one of the formats recognized by qsv
+    (see https://github.com/jqnatividad/belt/tree/main/dateparser#accepted-date-formats).
+    Set it to "mdy" or "dmy" to set the preferred order of ambiguous dates like 01/02/2024.
+
+Empty values are not checked by the qsv keywords - use "type" to require a value.
+
+As JSON Schema validates each record on its own, these table-level constraints, which are
+checked across records, can also be declared in the schema:
+
+  * primaryKey: at the top level, a column name or an array of column names for a composite key.
+    The primary key columns must not be empty, and each key must be unique.
+    e.g. "primaryKey": ["region", "id"]
+  * unique: in a property, if true, the non-empty values of the column must be unique.
+    e.g. "properties": {"email": {"type": "string", "unique": true}}
+  * foreignKeys: at the top level, an array of foreign keys, each one referencing the values of
+    a column/s of another local CSV file. Records with an empty foreign key column are not checked.
+    e.g. "foreignKeys": [{"fields": "country", "reference": {"resource": "countries.csv",
+                                                              "fields": "iso_code"}}]
+
+Violations are reported in "validation-errors.tsv" like any other validation error. A duplicate
+key is reported on every record with that key, except the first one.
+
+FRICTIONLESS DATA:
+Instead of a JSON Schema, a Frictionless Table Schema (https://specs.frictionlessdata.io/table-schema/)
+can be used. It is converted to an equivalent JSON Schema - its field types, formats,
+constraints (required, unique, minimum, maximum, minLength, maxLength, pattern & enum),
+primaryKey, foreignKeys & missingValues are all checked. Values in missingValues are validated
+as empty values.
+
+A Frictionless Data Package "datapackage.json" can also be used as the schema. The schema of
+the resource whose path or name matches the input CSV is used:
+`qsv validate cities.csv datapackage.json`
+
+To validate all the resources of a Data Package against their schemas, pass the package
+as the input - each resource gets its own valid/invalid/error files, if it has invalid records:
+`qsv validate datapackage.json`
+
+CROSS-FIELD RULES:
+Rules that involve several columns, like "end_date >= start_date", can be checked with --rules.
+The rules file has a rule per line, with its name and a boolean expression over column names:
+
+  # lines starting with # are comments
+  end_after_start: end_date >= start_date
+  closed_at_required: status != "closed" || typeof(closed_at) != "empty"
+  total_adds_up: math::abs(total - (subtotal + tax)) < 0.005
+
+The expressions are evaluated with evalexpr (https://docs.rs/evalexpr/11/evalexpr/#features).
+In a rule, a column whose name is not a valid identifier can be referred to with all its
+characters other than letters, digits & underscores replaced with underscores.
+
+The values are typed with the JSON Schema - integer, number & boolean properties are numbers &
+booleans, and properties with a date "format" or a dateFormat are Unix timestamps in seconds.
+Columns that are strings or not in the schema are typed with the stats cache of the input
+(e.g. "data.stats.csv" created by `qsv stats data.csv --cache-threshold 1`), if it's up to date.
+Empty values are () - a rule that can't be evaluated because of an empty value is skipped.
+
+Each failed rule is reported in "validation-errors.tsv", with the columns the rule uses as the
+field, and the rule name in the error.
+
 RFC 4180 VALIDATION MODE:
 If run without a JSON Schema file, the CSV is validated if it complies with qsv's interpretation of
 the RFC 4180 CSV standard (see https://github.com/jqnatividad/qsv#rfc-4180-csv-standard).
 
 It also confirms if the CSV is UTF-8 encoded.
 
+By default, it stops at the first problem. With --exhaustive, it scans the whole file and reports
+every malformed record as JSON on stdout. Each error has the record (0-based, counting the header),
+its line (1-based), its byte offset, the expected & actual number of fields, the byte offsets of
+its bad quotes (stray quotes in unquoted fields, closing quotes followed by other characters, and
+unterminated quoted fields) and the [start, end) byte ranges of its invalid UTF-8 sequences.
+
+With --repair <file>, a best-effort fixed copy of the CSV is also written - ragged records are
+padded with empty fields or truncated to the number of fields of the first record, fields with
+stray quotes are re-quoted, and invalid UTF-8 sequences are replaced with U+FFFD.
+
 For both modes, returns exit code 0 when the CSV file is valid, exitcode > 0 otherwise.
 If all records are valid, no output files are produced.
 
@@ -68,8 +146,10 @@ Usage:
 
 Validate arguments:
     <input>                    Input CSV file to validate. If not provided, will read from stdin.
-    <json-schema>              JSON Schema file to validate against. If not provided, `validate`
-                               will run in RFC 4180 validation mode. The file can be a local file
+                               If it's a "datapackage.json" file, all its resources are validated.
+    <json-schema>              JSON Schema, Frictionless Table Schema or Data Package file to
+                               validate against. If not provided, `validate` will run in RFC 4180
+                               validation mode. The file can be a local file
                                or a URL (http and https schemes supported).
 
 Validate options:
@@ -80,6 +160,10 @@ Validate options:
     --json                     When validating without a schema, return the RFC 4180 check
                                as a JSON file instead of a message.
     --pretty-json              Same as --json, but pretty printed.
+    --exhaustive               When validating without a schema, do not stop at the first problem.
+                               Report all the malformed records as JSON instead.
+    --repair <file>            When validating without a schema, write a best-effort fixed copy
+                               of the CSV to <file>. Implies --exhaustive.
     --valid-output <file>      Change validation mode behavior so if ALL rows are valid, to pass it to
                                output, return exit code 1, and set stderr to the number of valid rows.
                                Setting this will override the default behavior of creating
@@ -93,6 +177,8 @@ Validate options:
                                [default: 50000]
     --timeout <seconds>        Timeout for downloading json-schemas on URLs.
                                [default: 30]
+    --rules <file>             Also check each record against the cross-field rules in <file>.
+                               Can be used without a JSON Schema. See CROSS-FIELD RULES above.
 
 Common options:
     -h, --help                 Display this message
@@ -107,10 +193,12 @@ Common options:
 "#;
 
 use std::{
+    collections::{HashMap, HashSet},
     env,
     fs::File,
-    io::{BufReader, BufWriter, Read, Write},
-    str,
+    io::{BufRead, BufReader, BufWriter, Read, Write},
+    path::Path,
+    str::{self, FromStr},
     sync::{
         atomic::{AtomicU16, Ordering},
         OnceLock,
@@ -118,12 +206,20 @@ use std::{
 };
 
 use csv::ByteRecord;
+use evalexpr::{ContextWithMutableVariables, HashMapContext, Value as EvalValue};
 use indicatif::HumanCount;
 #[cfg(any(feature = "feature_capable", feature = "lite"))]
 use indicatif::{ProgressBar, ProgressDrawTarget};
 use itertools::Itertools;
-use jsonschema::{output::BasicOutput, paths::PathChunk, JSONSchema};
+use jsonschema::{
+    output::BasicOutput,
+    paths::{JSONPointer, JsonPointerNode, PathChunk},
+    ErrorIterator, JSONSchema, Keyword, ValidationError,
+};
 use log::{debug, info, log_enabled};
+#[cfg(feature = "apply")]
+use qsv_currency::Currency;
+use qsv_dateparser::parse_with_preference;
 use rayon::{
     iter::{IndexedParallelIterator, ParallelIterator},
     prelude::IntoParallelRefIterator,
@@ -161,6 +257,9 @@ struct Args {
     arg_input:         Option<String>,
     arg_json_schema:   Option<String>,
     flag_timeout:      u16,
+    flag_rules:        Option<String>,
+    flag_exhaustive:   bool,
+    flag_repair:       Option<String>,
 }
 
 enum JSONtypes {
@@ -189,19 +288,27 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
         Ordering::Relaxed,
     );
 
-    #[cfg(any(feature = "feature_capable", feature = "lite"))]
-    let mut rconfig = Config::new(&args.arg_input)
-        .delimiter(args.flag_delimiter)
-        .no_headers(args.flag_no_headers);
-    #[cfg(feature = "datapusher_plus")]
+    // a Frictionless Data Package - validate each of its resources against its schema
+    if args.arg_json_schema.is_none()
+        && args
+            .arg_input
+            .as_deref()
+            .is_some_and(|input| input.ends_with("datapackage.json"))
+    {
+        return validate_datapackage(&args);
+    }
+
     let rconfig = Config::new(&args.arg_input)
         .delimiter(args.flag_delimiter)
         .no_headers(args.flag_no_headers);
 
-    let mut rdr = rconfig.reader()?;
-
     // if no JSON Schema supplied, only let csv reader RFC4180-validate csv file
-    if args.arg_json_schema.is_none() {
+    if args.arg_json_schema.is_none() && args.flag_rules.is_none() {
+        if args.flag_exhaustive || args.flag_repair.is_some() {
+            return rfc4180_inventory(&args, &rconfig);
+        }
+        let mut rdr = rconfig.reader()?;
+
         // just read csv file and let csv reader report problems
         // since we're using csv::StringRecord, this will also detect non-utf8 sequences
 
@@ -402,6 +509,75 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
         return Ok(());
     }
 
+    let input_path = args
+        .arg_input
+        .clone()
+        .unwrap_or_else(|| "stdin.csv".to_string());
+    // with --rules only, the records are only checked against the rules
+    let Some(schema_uri) = args.arg_json_schema.clone() else {
+        let schema_json = json!({"type": "object", "properties": {}});
+        return validate_with_schema(&args, rconfig, &input_path, &schema_json);
+    };
+
+    // parse supplied schema
+    let schema_json: Value = match load_json(&schema_uri) {
+        Ok(s) => {
+            // parse JSON string
+            match serde_json::from_str(&s) {
+                Ok(json) => json,
+                Err(e) => {
+                    return fail_clierror!("Unable to parse schema json. error: {e}");
+                },
+            }
+        },
+        Err(e) => {
+            return fail_clierror!("Unable to retrieve json. error: {e}");
+        },
+    };
+
+    // Frictionless Table Schemas & Data Packages are converted to JSON Schema
+    let schema_json = if schema_json.get("resources").is_some() {
+        let resources = get_package_resources(&schema_json, &schema_uri, &args)?;
+        let input_name = Path::new(&input_path)
+            .file_name()
+            .unwrap_or_default()
+            .to_string_lossy();
+        let input_stem = Path::new(&input_path)
+            .file_stem()
+            .unwrap_or_default()
+            .to_string_lossy();
+        let Some(resource) = resources.iter().find(|resource| {
+            resource.path == input_path
+                || Path::new(&resource.path).file_name().unwrap_or_default() == &*input_name
+                || resource.name == input_stem
+        }) else {
+            return fail_incorrectusage_clierror!(
+                "No resource in the Data Package {schema_uri} matches {input_path}."
+            );
+        };
+        resource.schema.clone()
+    } else if is_table_schema(&schema_json) {
+        table_schema_to_json_schema(&schema_json, &HashMap::new(), &input_path)?
+    } else {
+        schema_json
+    };
+
+    validate_with_schema(&args, rconfig, &input_path, &schema_json)
+}
+
+/// validate a CSV against a JSON Schema.
+/// If there are invalid records, write the valid/invalid/error files and return an error.
+fn validate_with_schema(
+    args: &Args,
+    rconfig: Config,
+    input_path: &str,
+    schema_json: &Value,
+) -> CliResult<()> {
+    #[cfg(any(feature = "feature_capable", feature = "lite"))]
+    let mut rconfig = rconfig;
+
+    let mut rdr = rconfig.reader()?;
+
     // prep progress bar
     #[cfg(any(feature = "feature_capable", feature = "lite"))]
     let progress = ProgressBar::with_draw_target(None, ProgressDrawTarget::stderr_with_hz(5));
@@ -424,42 +600,37 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
     let headers = rdr.byte_headers()?.clone();
     let header_len = headers.len();
 
-    // parse and compile supplied JSON Schema
-    let (schema_json, schema_compiled): (Value, JSONSchema) =
-        match load_json(&args.arg_json_schema.unwrap()) {
-            Ok(s) => {
-                // parse JSON string
-                match serde_json::from_str(&s) {
-                    Ok(json) => {
-                        // compile JSON Schema
-                        match JSONSchema::options().compile(&json) {
-                            Ok(schema) => (json, schema),
-                            Err(e) => {
-                                return fail_clierror!("Cannot compile schema json. error: {e}");
-                            },
-                        }
-                    },
-                    Err(e) => {
-                        return fail_clierror!("Unable to parse schema json. error: {e}");
-                    },
-                }
-            },
-            Err(e) => {
-                return fail_clierror!("Unable to retrieve json. error: {e}");
-            },
-        };
+    // compile JSON Schema
+    let schema_compiled = match compile_schema(schema_json) {
+        Ok(schema) => schema,
+        Err(e) => {
+            return fail_clierror!("Cannot compile schema json. error: {e}");
+        },
+    };
 
     if log::log_enabled!(log::Level::Debug) {
         // only log if debug is enabled
         // as it can be quite large and expensive to deserialize the schema
-        debug!("schema json: {:?}", &schema_json);
+        debug!("schema json: {:?}", schema_json);
     }
 
     // set this once, as this is used repeatedly in a hot loop
-    NULL_TYPE.set(Value::String("null".to_string())).unwrap();
+    NULL_TYPE.get_or_init(|| Value::String("null".to_string()));
 
     // get JSON types for each column in CSV file
-    let header_types = get_json_types(&headers, &schema_json)?;
+    let header_types = get_json_types(&headers, schema_json)?;
+
+    // get the table-level constraints, which are checked across records
+    let mut table_constraints = TableConstraints::new(&headers, schema_json)?;
+
+    // get the cross-field rules, which are checked on each record
+    let row_rules = match args.flag_rules {
+        Some(ref rules_path) => {
+            let col_types = get_rule_types(&headers, schema_json, input_path)?;
+            RowRules::load(rules_path, &headers, col_types)?
+        },
+        None => RowRules::default(),
+    };
 
     // how many rows read and processed as batches
     let mut row_number: u64 = 0;
@@ -475,6 +646,7 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
     let mut valid_flags: Vec<bool> = Vec::with_capacity(batch_size);
     let mut validation_error_messages: Vec<String> = Vec::with_capacity(50);
     let flag_trim = args.flag_trim;
+    let missing_values = get_missing_values(schema_json)?;
 
     // set RAYON_NUM_THREADS
     util::njobs(args.flag_jobs);
@@ -489,6 +661,21 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
                 Ok(has_data) => {
                     if has_data {
                         row_number += 1;
+                        // missing values are validated as empty values
+                        if !missing_values.is_empty()
+                            && record.iter().any(|field| missing_values.contains(field))
+                        {
+                            record = record
+                                .iter()
+                                .map(|field| {
+                                    if missing_values.contains(field) {
+                                        &b""[..]
+                                    } else {
+                                        field
+                                    }
+                                })
+                                .collect();
+                        }
                         record.push_field(buffer.format(row_number).as_bytes());
                         if flag_trim {
                             record.trim();
@@ -514,21 +701,35 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
         // validation_results vector should have same row count and in same order as input CSV
         batch
             .par_iter()
-            .map(|record| do_json_validation(&header_types, header_len, record, &schema_compiled))
+            .map(|record| {
+                let schema_errors =
+                    do_json_validation(&header_types, header_len, record, &schema_compiled);
+                match (schema_errors, row_rules.check(record, header_len)) {
+                    (Some(schema_errors), Some(rule_errors)) => {
+                        Some(format!("{schema_errors}\n{rule_errors}"))
+                    },
+                    (schema_errors, rule_errors) => schema_errors.or(rule_errors),
+                }
+            })
             .collect_into_vec(&mut validation_results);
 
         // write to validation error report, but keep Vec<bool> to gen valid/invalid files later
         // because Rayon collect() guarantees original order, we can sequentially append results
         // to vector with each batch
-        for result in &validation_results {
-            if let Some(validation_error_msg) = result {
-                invalid_count += 1;
-                valid_flags.push(false);
+        // the table-level constraints are checked sequentially, as they're checked across records
+        for (record, result) in batch.iter().zip(&validation_results) {
+            let constraint_errors = table_constraints.check(record, header_len);
+            if result.is_none() && constraint_errors.is_empty() {
+                valid_flags.push(true);
+                continue;
+            }
+            invalid_count += 1;
+            valid_flags.push(false);
 
+            if let Some(validation_error_msg) = result {
                 validation_error_messages.push(validation_error_msg.to_string());
-            } else {
-                valid_flags.push(true);
             }
+            validation_error_messages.extend(constraint_errors);
         }
 
         #[cfg(any(feature = "feature_capable", feature = "lite"))]
@@ -555,7 +756,7 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
     if invalid_count == 0 {
         // no invalid records found
         // see if we need to pass all valid records to output
-        if let Some(valid_output) = args.flag_valid_output {
+        if let Some(valid_output) = args.flag_valid_output.clone() {
             // pass all valid records to output and return exit code 1
             let valid_path = if valid_output == "-" {
                 // write to stdout
@@ -581,23 +782,18 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
         // if 100% invalid, valid file isn't needed, but this is rare so OK creating empty file.
         woutinfo!("Writing invalid/valid/error files...");
 
-        let input_path = args
-            .arg_input
-            .clone()
-            .unwrap_or_else(|| "stdin.csv".to_string());
-
-        write_error_report(&input_path, validation_error_messages)?;
+        write_error_report(input_path, validation_error_messages)?;
 
-        let valid_suffix = args.flag_valid.unwrap_or_else(|| "valid".to_string());
-        let invalid_suffix = args.flag_invalid.unwrap_or_else(|| "invalid".to_string());
+        let valid_suffix = args.flag_valid.as_deref().unwrap_or("valid");
+        let invalid_suffix = args.flag_invalid.as_deref().unwrap_or("invalid");
 
         split_invalid_records(
             &rconfig,
             &valid_flags[..],
             &headers,
-            &input_path,
-            &valid_suffix,
-            &invalid_suffix,
+            input_path,
+            valid_suffix,
+            invalid_suffix,
         )?;
 
         // done with validation; print output
@@ -623,6 +819,1342 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
     Ok(())
 }
 
+/// compile a JSON Schema, with the qsv custom keywords registered
+#[allow(clippy::result_large_err)]
+fn compile_schema(json: &Value) -> Result<JSONSchema, ValidationError<'_>> {
+    JSONSchema::options()
+        .with_keyword("dynamicEnum", dynamic_enum_factory)
+        .with_keyword("currency", currency_factory)
+        .with_keyword("dateFormat", date_format_factory)
+        .compile(json)
+}
+
+/// the "dynamicEnum" keyword - the value must be in a column of a local CSV file
+struct DynamicEnumValidator {
+    file:   String,
+    values: HashSet<String>,
+}
+
+impl Keyword for DynamicEnumValidator {
+    fn validate<'instance>(
+        &self,
+        instance: &'instance Value,
+        instance_path: &JsonPointerNode,
+    ) -> ErrorIterator<'instance> {
+        if self.is_valid(instance) {
+            return Box::new(std::iter::empty());
+        }
+        let error = ValidationError::custom(
+            JSONPointer::default(),
+            instance_path.into(),
+            instance,
+            format!("{instance} is not one of the values in {}", self.file),
+        );
+        Box::new(std::iter::once(error))
+    }
+
+    fn is_valid(&self, instance: &Value) -> bool {
+        match instance {
+            Value::String(s) => self.values.contains(s),
+            Value::Number(n) => self.values.contains(&n.to_string()),
+            Value::Bool(b) => self.values.contains(&b.to_string()),
+            _ => true,
+        }
+    }
+}
+
+#[allow(clippy::result_large_err)]
+fn dynamic_enum_factory<'a>(
+    _parent: &'a Map<String, Value>,
+    value: &'a Value,
+    path: JSONPointer,
+) -> Result<Box<dyn Keyword>, ValidationError<'a>> {
+    let (file, column) = match value {
+        Value::String(file) => (file.as_str(), None),
+        Value::Object(obj) => match (obj.get("file"), obj.get("column")) {
+            (
+                Some(Value::String(file)),
+                column @ (None | Some(Value::String(_) | Value::Number(_))),
+            ) => (file.as_str(), column),
+            _ => {
+                return Err(ValidationError::custom(
+                    JSONPointer::default(),
+                    path,
+                    value,
+                    "dynamicEnum must have a \"file\" string and an optional \"column\" name or \
+                     index",
+                ));
+            },
+        },
+        _ => {
+            return Err(ValidationError::custom(
+                JSONPointer::default(),
+                path,
+                value,
+                "dynamicEnum must be the path of a CSV file, or an object with a \"file\" and a \
+                 \"column\"",
+            ));
+        },
+    };
+
+    let values = load_dynamic_enum(file, column).map_err(|e| {
+        ValidationError::custom(
+            JSONPointer::default(),
+            path,
+            value,
+            format!("Cannot load the dynamicEnum values from {file}: {e}"),
+        )
+    })?;
+    Ok(Box::new(DynamicEnumValidator {
+        file: file.to_string(),
+        values,
+    }))
+}
+
+/// load the distinct values of a column of a CSV - its first column if none is given
+fn load_dynamic_enum(file: &str, column: Option<&Value>) -> CliResult<HashSet<String>> {
+    let mut rdr = Config::new(&Some(file.to_string())).reader()?;
+    let headers = rdr.headers()?.clone();
+    let col_idx = match column {
+        None => 0,
+        Some(Value::Number(n)) => match n.as_u64() {
+            Some(idx) if (idx as usize) < headers.len() => idx as usize,
+            _ => return fail_clierror!("column index {n} is out of range"),
+        },
+        Some(name) => {
+            let name = name.as_str().unwrap_or_default();
+            match headers.iter().position(|header| header == name) {
+                Some(idx) => idx,
+                None => return fail_clierror!("column \"{name}\" not found"),
+            }
+        },
+    };
+
+    let mut values = HashSet::new();
+    let mut record = csv::StringRecord::new();
+    while rdr.read_record(&mut record)? {
+        if let Some(value) = record.get(col_idx) {
+            values.insert(value.to_string());
+        }
+    }
+    Ok(values)
+}
+
+/// the "currency" keyword - if true, the value must be a currency amount
+#[cfg(feature = "apply")]
+struct CurrencyValidator;
+
+#[cfg(feature = "apply")]
+impl Keyword for CurrencyValidator {
+    fn validate<'instance>(
+        &self,
+        instance: &'instance Value,
+        instance_path: &JsonPointerNode,
+    ) -> ErrorIterator<'instance> {
+        if self.is_valid(instance) {
+            return Box::new(std::iter::empty());
+        }
+        let error = ValidationError::custom(
+            JSONPointer::default(),
+            instance_path.into(),
+            instance,
+            format!("{instance} is not a valid currency amount"),
+        );
+        Box::new(std::iter::once(error))
+    }
+
+    fn is_valid(&self, instance: &Value) -> bool {
+        match instance {
+            Value::String(s) => Currency::from_str(s).is_ok(),
+            _ => true,
+        }
+    }
+}
+
+/// accepts all values, for the qsv keywords when they're turned off
+struct AlwaysValid;
+
+impl Keyword for AlwaysValid {
+    fn validate<'instance>(
+        &self,
+        _instance: &'instance Value,
+        _instance_path: &JsonPointerNode,
+    ) -> ErrorIterator<'instance> {
+        Box::new(std::iter::empty())
+    }
+
+    fn is_valid(&self, _instance: &Value) -> bool {
+        true
+    }
+}
+
+#[allow(clippy::result_large_err)]
+fn currency_factory<'a>(
+    _parent: &'a Map<String, Value>,
+    value: &'a Value,
+    path: JSONPointer,
+) -> Result<Box<dyn Keyword>, ValidationError<'a>> {
+    match value {
+        #[cfg(feature = "apply")]
+        Value::Bool(true) => Ok(Box::new(CurrencyValidator)),
+        #[cfg(not(feature = "apply"))]
+        Value::Bool(true) => Err(ValidationError::custom(
+            JSONPointer::default(),
+            path,
+            value,
+            "the currency keyword requires the apply feature, which is not enabled in this build \
+             of qsv",
+        )),
+        Value::Bool(false) => Ok(Box::new(AlwaysValid)),
+        _ => Err(ValidationError::custom(
+            JSONPointer::default(),
+            path,
+            value,
+            "currency must be true or false",
+        )),
+    }
+}
+
+/// the "dateFormat" keyword - the value must be a date recognized by qsv-dateparser
+struct DateFormatValidator {
+    prefer_dmy: bool,
+}
+
+impl Keyword for DateFormatValidator {
+    fn validate<'instance>(
+        &self,
+        instance: &'instance Value,
+        instance_path: &JsonPointerNode,
+    ) -> ErrorIterator<'instance> {
+        if self.is_valid(instance) {
+            return Box::new(std::iter::empty());
+        }
+        let error = ValidationError::custom(
+            JSONPointer::default(),
+            instance_path.into(),
+            instance,
+            format!("{instance} is not a date in a recognized format"),
+        );
+        Box::new(std::iter::once(error))
+    }
+
+    fn is_valid(&self, instance: &Value) -> bool {
+        match instance {
+            Value::String(s) => parse_with_preference(s, self.prefer_dmy).is_ok(),
+            Value::Null => true,
+            // dates are strings, so any other type can't be a date
+            _ => false,
+        }
+    }
+}
+
+#[allow(clippy::result_large_err)]
+fn date_format_factory<'a>(
+    _parent: &'a Map<String, Value>,
+    value: &'a Value,
+    path: JSONPointer,
+) -> Result<Box<dyn Keyword>, ValidationError<'a>> {
+    match value.as_str().map(str::to_ascii_lowercase).as_deref() {
+        Some("mdy") => Ok(Box::new(DateFormatValidator { prefer_dmy: false })),
+        Some("dmy") => Ok(Box::new(DateFormatValidator { prefer_dmy: true })),
+        _ => Err(ValidationError::custom(
+            JSONPointer::default(),
+            path,
+            value,
+            "dateFormat must be \"mdy\" or \"dmy\"",
+        )),
+    }
+}
+
+/// a primary key or unique constraint - the values of its columns must be unique
+struct KeyConstraint {
+    /// the column name/s, as shown in the error report
+    name:        String,
+    cols:        Vec<usize>,
+    primary_key: bool,
+    /// the first row number of each key
+    seen:        HashMap<Vec<Vec<u8>>, u64>,
+}
+
+/// a foreign key - the values of its columns must be in another CSV file
+struct ForeignKey {
+    name:       String,
+    cols:       Vec<usize>,
+    resource:   String,
+    ref_fields: String,
+    ref_values: HashSet<Vec<Vec<u8>>>,
+}
+
+/// the table-level constraints of a schema, which are checked across records
+struct TableConstraints {
+    keys:         Vec<KeyConstraint>,
+    foreign_keys: Vec<ForeignKey>,
+}
+
+impl TableConstraints {
+    fn new(headers: &ByteRecord, schema: &Value) -> CliResult<Self> {
+        let mut keys = Vec::new();
+        if let Some(primary_key) = schema.get("primaryKey") {
+            let fields = get_field_names(primary_key, "primaryKey")?;
+            keys.push(KeyConstraint {
+                name:        fields.join(","),
+                cols:        get_field_indices(headers, &fields, "primaryKey")?,
+                primary_key: true,
+                seen:        HashMap::new(),
+            });
+        }
+        if let Some(Value::Object(properties)) = schema.get("properties") {
+            for (field, field_def) in properties {
+                if field_def.get("unique") != Some(&Value::Bool(true)) {
+                    continue;
+                }
+                let fields = vec![field.clone()];
+                keys.push(KeyConstraint {
+                    name:        field.clone(),
+                    cols:        get_field_indices(headers, &fields, "unique")?,
+                    primary_key: false,
+                    seen:        HashMap::new(),
+                });
+            }
+        }
+
+        let mut foreign_keys = Vec::new();
+        if let Some(fk_defs) = schema.get("foreignKeys") {
+            let Value::Array(fk_defs) = fk_defs else {
+                return fail_clierror!("foreignKeys must be an array.");
+            };
+            for fk_def in fk_defs {
+                foreign_keys.push(ForeignKey::new(headers, fk_def)?);
+            }
+        }
+
+        Ok(Self { keys, foreign_keys })
+    }
+
+    /// check a record, returning an error report line for each violation
+    fn check(&mut self, record: &ByteRecord, header_len: usize) -> Vec<String> {
+        if self.keys.is_empty() && self.foreign_keys.is_empty() {
+            return Vec::new();
+        }
+        // row number was added as last column
+        let row_number_string = from_utf8(record.get(header_len).unwrap()).unwrap();
+        let mut errors = Vec::new();
+
+        for key in &mut self.keys {
+            let values = key_values(record, &key.cols);
+            if values.iter().any(Vec::is_empty) {
+                if key.primary_key {
+                    errors.push(format!(
+                        "{row_number_string}\t{}\tprimary key column is empty",
+                        key.name
+                    ));
+                }
+                continue;
+            }
+            let row_number: u64 = row_number_string.parse().unwrap_or_default();
+            if let Some(first_row) = key.seen.get(&values) {
+                errors.push(format!(
+                    "{row_number_string}\t{}\t{} {} is a duplicate of row {first_row}",
+                    key.name,
+                    if key.primary_key {
+                        "primary key"
+                    } else {
+                        "unique value"
+                    },
+                    key_text(&values)
+                ));
+            } else {
+                key.seen.insert(values, row_number);
+            }
+        }
+
+        for fk in &self.foreign_keys {
+            let values = key_values(record, &fk.cols);
+            if values.iter().any(Vec::is_empty) || fk.ref_values.contains(&values) {
+                continue;
+            }
+            errors.push(format!(
+                "{row_number_string}\t{}\tforeign key {} is not in the {} column/s of {}",
+                fk.name,
+                key_text(&values),
+                fk.ref_fields,
+                fk.resource
+            ));
+        }
+        errors
+    }
+}
+
+impl ForeignKey {
+    fn new(headers: &ByteRecord, fk_def: &Value) -> CliResult<Self> {
+        let (Some(fields), Some(reference)) = (fk_def.get("fields"), fk_def.get("reference"))
+        else {
+            return fail_clierror!(
+                "Each foreignKeys entry must have \"fields\" and a \"reference\" object."
+            );
+        };
+        let fields = get_field_names(fields, "foreignKeys")?;
+        let resource = match reference.get("resource") {
+            Some(Value::String(resource)) if !resource.is_empty() => resource.clone(),
+            _ => {
+                return fail_clierror!(
+                    "The foreignKeys reference must have a \"resource\" - the path of the CSV \
+                     file to check against."
+                );
+            },
+        };
+        let ref_fields = match reference.get("fields") {
+            Some(ref_fields) => get_field_names(ref_fields, "foreignKeys reference")?,
+            None => fields.clone(),
+        };
+        if ref_fields.len() != fields.len() {
+            return fail_clierror!(
+                "The foreignKeys fields ({}) and reference fields ({}) must have the same number \
+                 of columns.",
+                fields.join(","),
+                ref_fields.join(",")
+            );
+        }
+
+        // load the keys of the referenced CSV
+        let mut rdr = Config::new(&Some(resource.clone())).reader()?;
+        let ref_headers = rdr.byte_headers()?.clone();
+        let ref_cols = get_field_indices(&ref_headers, &ref_fields, &resource)?;
+        let mut ref_values = HashSet::new();
+        let mut record = ByteRecord::new();
+        while rdr.read_byte_record(&mut record)? {
+            ref_values.insert(key_values(&record, &ref_cols));
+        }
+
+        Ok(Self {
+            name: fields.join(","),
+            cols: get_field_indices(headers, &fields, "foreignKeys")?,
+            resource,
+            ref_fields: ref_fields.join(","),
+            ref_values,
+        })
+    }
+}
+
+/// a column name or an array of column names
+fn get_field_names(value: &Value, keyword: &str) -> CliResult<Vec<String>> {
+    match value {
+        Value::String(field) => Ok(vec![field.clone()]),
+        Value::Array(fields) if !fields.is_empty() => fields
+            .iter()
+            .map(|field| match field {
+                Value::String(field) => Ok(field.clone()),
+                _ => fail_clierror!("{keyword} column names must be strings."),
+            })
+            .collect(),
+        _ => fail_clierror!("{keyword} must be a column name or an array of column names."),
+    }
+}
+
+fn get_field_indices(
+    headers: &ByteRecord,
+    fields: &[String],
+    source: &str,
+) -> CliResult<Vec<usize>> {
+    fields
+        .iter()
+        .map(
+            |field| match headers.iter().position(|h| h == field.as_bytes()) {
+                Some(idx) => Ok(idx),
+                None => fail_clierror!("{source}: column \"{field}\" not found."),
+            },
+        )
+        .collect()
+}
+
+#[inline]
+fn key_values(record: &ByteRecord, cols: &[usize]) -> Vec<Vec<u8>> {
+    cols.iter()
+        .map(|&i| record.get(i).unwrap_or_default().to_vec())
+        .collect()
+}
+
+fn key_text(values: &[Vec<u8>]) -> String {
+    let text = values
+        .iter()
+        .map(|value| String::from_utf8_lossy(value))
+        .join(",");
+    format!("\"{text}\"")
+}
+
+/// the empty values of the top-level "missingValues" array, besides the empty string
+fn get_missing_values(schema: &Value) -> CliResult<HashSet<Vec<u8>>> {
+    match schema.get("missingValues") {
+        None => Ok(HashSet::new()),
+        Some(Value::Array(values)) => values
+            .iter()
+            .filter(|value| value.as_str() != Some(""))
+            .map(|value| match value {
+                Value::String(s) => Ok(s.as_bytes().to_vec()),
+                _ => fail_clierror!("missingValues must be an array of strings."),
+            })
+            .collect(),
+        Some(_) => fail_clierror!("missingValues must be an array of strings."),
+    }
+}
+
+/// a Frictionless Table Schema has a "fields" array instead of JSON Schema "properties"
+fn is_table_schema(schema: &Value) -> bool {
+    schema.get("fields").is_some_and(Value::is_array) && schema.get("properties").is_none()
+}
+
+/// a resource of a Frictionless Data Package, with its schema converted to JSON Schema
+struct PackageResource {
+    name:      String,
+    path:      String,
+    delimiter: Option<Delimiter>,
+    schema:    Value,
+}
+
+/// get the tabular resources of a Frictionless Data Package.
+/// Relative paths are relative to the directory of the package.
+fn get_package_resources(
+    package: &Value,
+    package_uri: &str,
+    args: &Args,
+) -> CliResult<Vec<PackageResource>> {
+    let Some(Value::Array(resources)) = package.get("resources") else {
+        return fail_clierror!("The Data Package \"resources\" must be an array.");
+    };
+    let base_dir = if package_uri.to_lowercase().starts_with("http") {
+        None
+    } else {
+        Path::new(package_uri).parent()
+    };
+    let resolve = |path: &str| -> String {
+        match base_dir {
+            Some(dir) if !Path::new(path).is_absolute() && !path.starts_with("http") => {
+                dir.join(path).to_string_lossy().to_string()
+            },
+            _ => path.to_string(),
+        }
+    };
+
+    // the paths of the resources by name, to resolve foreign key references
+    let mut resource_paths: HashMap<String, String> = HashMap::new();
+    for resource in resources {
+        if let (Some(Value::String(name)), Some(Value::String(path))) =
+            (resource.get("name"), resource.get("path"))
+        {
+            resource_paths.insert(name.clone(), resolve(path));
+        }
+    }
+
+    let mut package_resources = Vec::with_capacity(resources.len());
+    for (idx, resource) in resources.iter().enumerate() {
+        let name = match resource.get("name") {
+            Some(Value::String(name)) => name.clone(),
+            _ => format!("resource {}", idx + 1),
+        };
+        let path = match resource.get("path") {
+            Some(Value::String(path)) => resolve(path),
+            _ => {
+                return fail_clierror!(
+                    "The Data Package resource \"{name}\" must have a \"path\" to a single local \
+                     file."
+                );
+            },
+        };
+        let table_schema = match resource.get("schema") {
+            Some(Value::Object(_)) => resource["schema"].clone(),
+            Some(Value::String(schema_path)) => {
+                let schema_str = match load_json(&resolve(schema_path)) {
+                    Ok(s) => s,
+                    Err(e) => return fail_clierror!("Unable to retrieve json. error: {e}"),
+                };
+                match serde_json::from_str(&schema_str) {
+                    Ok(json) => json,
+                    Err(e) => return fail_clierror!("Unable to parse schema json. error: {e}"),
+                }
+            },
+            _ => {
+                return fail_clierror!(
+                    "The Data Package resource \"{name}\" does not have a Table Schema."
+                );
+            },
+        };
+        // the resource's CSV dialect takes precedence over --delimiter
+        let delimiter = match resource.pointer("/dialect/delimiter") {
+            Some(Value::String(delim)) if delim.len() == 1 => Some(Delimiter(delim.as_bytes()[0])),
+            _ => args.flag_delimiter,
+        };
+
+        let schema = table_schema_to_json_schema(&table_schema, &resource_paths, &path)?;
+        package_resources.push(PackageResource {
+            name,
+            path,
+            delimiter,
+            schema,
+        });
+    }
+    Ok(package_resources)
+}
+
+/// validate each resource of a Frictionless Data Package against its schema
+fn validate_datapackage(args: &Args) -> CliResult<()> {
+    if args.flag_valid_output.is_some() {
+        return fail_incorrectusage_clierror!(
+            "--valid-output cannot be used when validating a Data Package."
+        );
+    }
+    // safety: we only get here if there's an input
+    let package_uri = args.arg_input.clone().unwrap();
+    let package: Value = match load_json(&package_uri) {
+        Ok(s) => match serde_json::from_str(&s) {
+            Ok(json) => json,
+            Err(e) => return fail_clierror!("Unable to parse Data Package json. error: {e}"),
+        },
+        Err(e) => return fail_clierror!("Unable to retrieve json. error: {e}"),
+    };
+    let resources = get_package_resources(&package, &package_uri, args)?;
+
+    let mut invalid_resources = 0_usize;
+    for resource in &resources {
+        if !args.flag_quiet {
+            winfo!(
+                "Validating resource \"{}\" ({})...",
+                resource.name,
+                resource.path
+            );
+        }
+        let rconfig = Config::new(&Some(resource.path.clone()))
+            .delimiter(resource.delimiter)
+            .no_headers(args.flag_no_headers);
+        if let Err(e) = validate_with_schema(args, rconfig, &resource.path, &resource.schema) {
+            werr!("{e}");
+            invalid_resources += 1;
+        }
+    }
+
+    if invalid_resources > 0 {
+        return fail_clierror!(
+            "{invalid_resources} out of {} resources invalid.",
+            resources.len()
+        );
+    }
+    if !args.flag_quiet {
+        winfo!("All {} resources valid.", resources.len());
+    }
+    Ok(())
+}
+
+/// convert a Frictionless Table Schema to a JSON Schema, with the qsv keywords.
+/// The foreign key references are resolved with the resource paths of its Data Package, if any,
+/// and a self-reference refers to the CSV being validated.
+fn table_schema_to_json_schema(
+    table_schema: &Value,
+    resource_paths: &HashMap<String, String>,
+    self_path: &str,
+) -> CliResult<Value> {
+    let Some(Value::Array(fields)) = table_schema.get("fields") else {
+        return fail_clierror!("The Table Schema \"fields\" must be an array.");
+    };
+
+    let mut properties = Map::with_capacity(fields.len());
+    for field in fields {
+        let Some(Value::String(name)) = field.get("name") else {
+            return fail_clierror!("Each Table Schema field must have a \"name\".");
+        };
+        let field_type = field
+            .get("type")
+            .and_then(Value::as_str)
+            .unwrap_or("string");
+        let format = field
+            .get("format")
+            .and_then(Value::as_str)
+            .unwrap_or("default");
+        let constraints = field.get("constraints").cloned().unwrap_or_default();
+        let required = constraints.get("required") == Some(&Value::Bool(true));
+
+        let mut property = Map::new();
+        let json_type = match field_type {
+            "integer" | "year" => "integer",
+            "number" => "number",
+            // all other types, including booleans, dates & times, are validated as strings
+            _ => "string",
+        };
+        property.insert(
+            "type".to_string(),
+            if required {
+                json!(json_type)
+            } else {
+                json!([json_type, "null"])
+            },
+        );
+
+        match (field_type, format) {
+            ("string", "email") => {
+                property.insert("pattern".to_string(), json!(r"^[^@\s]+@[^@\s]+\.[^@\s]+$"));
+            },
+            ("string", "uri") => {
+                property.insert(
+                    "pattern".to_string(),
+                    json!(r"^[a-zA-Z][a-zA-Z0-9+.-]*:\S+$"),
+                );
+            },
+            ("string", "uuid") => {
+                property.insert(
+                    "pattern".to_string(),
+                    json!(
+                        r"^[0-9a-fA-F]{8}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{12}$"
+                    ),
+                );
+            },
+            ("date", "default") => {
+                property.insert("pattern".to_string(), json!(r"^\d{4}-\d{2}-\d{2}$"));
+            },
+            ("datetime", "default") => {
+                property.insert(
+                    "pattern".to_string(),
+                    json!(r"^\d{4}-\d{2}-\d{2}T\d{2}:\d{2}:\d{2}(\.\d+)?(Z|[+-]\d{2}:\d{2})?$"),
+                );
+            },
+            ("time", "default") => {
+                property.insert("pattern".to_string(), json!(r"^\d{2}:\d{2}:\d{2}(\.\d+)?$"));
+            },
+            ("boolean", _) => {
+                let true_values = field
+                    .get("trueValues")
+                    .cloned()
+                    .unwrap_or_else(|| json!(["true", "True", "TRUE", "1"]));
+                let false_values = field
+                    .get("falseValues")
+                    .cloned()
+                    .unwrap_or_else(|| json!(["false", "False", "FALSE", "0"]));
+                let mut values: Vec<Value> = [true_values, false_values]
+                    .into_iter()
+                    .filter_map(|values| values.as_array().cloned())
+                    .flatten()
+                    .collect();
+                if !required {
+                    values.push(Value::Null);
+                }
+                property.insert("enum".to_string(), Value::Array(values));
+            },
+            ("yearmonth", _) => {
+                property.insert("pattern".to_string(), json!(r"^\d{4}-\d{2}$"));
+            },
+            // "any" or a strptime pattern - check it's a date qsv recognizes
+            ("date" | "datetime", _) => {
+                let order = if format.starts_with("%d") {
+                    "dmy"
+                } else {
+                    "mdy"
+                };
+                property.insert("dateFormat".to_string(), json!(order));
+            },
+            _ => {},
+        }
+
+        let numeric = matches!(json_type, "integer" | "number");
+        for (keyword, json_keyword) in [
+            ("minimum", "minimum"),
+            ("maximum", "maximum"),
+            ("minLength", "minLength"),
+            ("maxLength", "maxLength"),
+        ] {
+            let Some(limit) = constraints.get(keyword) else {
+                continue;
+            };
+            // min/max of dates & times are not supported
+            if keyword.ends_with("imum") && !numeric {
+                continue;
+            }
+            property.insert(json_keyword.to_string(), to_typed_value(limit, json_type));
+        }
+        if let Some(Value::String(pattern)) = constraints.get("pattern") {
+            // Table Schema patterns must match the whole value
+            property.insert("pattern".to_string(), json!(format!("^(?:{pattern})$")));
+        }
+        if let Some(Value::Array(values)) = constraints.get("enum") {
+            let mut values: Vec<Value> = values
+                .iter()
+                .map(|value| to_typed_value(value, json_type))
+                .collect();
+            if !required {
+                values.push(Value::Null);
+            }
+            property.insert("enum".to_string(), Value::Array(values));
+        }
+        if constraints.get("unique") == Some(&Value::Bool(true)) {
+            property.insert("unique".to_string(), Value::Bool(true));
+        }
+
+        properties.insert(name.clone(), Value::Object(property));
+    }
+
+    let mut json_schema = Map::new();
+    json_schema.insert(
+        "$schema".to_string(),
+        json!("https://json-schema.org/draft/2020-12/schema"),
+    );
+    json_schema.insert("type".to_string(), json!("object"));
+    json_schema.insert("properties".to_string(), Value::Object(properties));
+    if let Some(primary_key) = table_schema.get("primaryKey") {
+        json_schema.insert("primaryKey".to_string(), primary_key.clone());
+    }
+    if let Some(Value::Array(foreign_keys)) = table_schema.get("foreignKeys") {
+        let mut fks = Vec::with_capacity(foreign_keys.len());
+        for fk in foreign_keys {
+            let mut fk = fk.clone();
+            if let Some(Value::String(resource)) = fk.pointer("/reference/resource") {
+                let path = if resource.is_empty() {
+                    self_path.to_string()
+                } else {
+                    resource_paths
+                        .get(resource)
+                        .cloned()
+                        .unwrap_or_else(|| resource.clone())
+                };
+                fk["reference"]["resource"] = json!(path);
+            }
+            fks.push(fk);
+        }
+        json_schema.insert("foreignKeys".to_string(), Value::Array(fks));
+    }
+    // the Table Schema default is an empty string
+    json_schema.insert(
+        "missingValues".to_string(),
+        table_schema
+            .get("missingValues")
+            .cloned()
+            .unwrap_or_else(|| json!([""])),
+    );
+
+    Ok(Value::Object(json_schema))
+}
+
+/// Table Schema values of numeric fields may be strings
+fn to_typed_value(value: &Value, json_type: &str) -> Value {
+    match (value, json_type) {
+        (Value::String(s), "integer") => s
+            .parse::<i64>()
+            .map_or_else(|_| value.clone(), |n| json!(n)),
+        (Value::String(s), "number") => s
+            .parse::<f64>()
+            .map_or_else(|_| value.clone(), |n| json!(n)),
+        _ => value.clone(),
+    }
+}
+
+/// how the values of a column are typed in the cross-field rules
+#[derive(Clone, Copy, PartialEq)]
+enum RuleType {
+    String,
+    Integer,
+    Float,
+    Boolean,
+    Date,
+}
+
+/// a cross-field rule - a boolean expression over column names
+struct Rule {
+    name:       String,
+    expression: String,
+    node:       evalexpr::Node,
+    /// the columns used by the rule, with the identifiers they're referred to with
+    cols:       Vec<(usize, String)>,
+    /// the column names, as shown in the error report
+    fields:     String,
+}
+
+#[derive(Default)]
+struct RowRules {
+    rules:     Vec<Rule>,
+    col_types: Vec<RuleType>,
+}
+
+impl RowRules {
+    /// load the rules file - a rule per line, with its name & expression separated by a colon
+    fn load(path: &str, headers: &ByteRecord, col_types: Vec<RuleType>) -> CliResult<Self> {
+        // the columns by name, and by name with the non-identifier characters replaced
+        let mut col_idents: HashMap<String, usize> = HashMap::with_capacity(headers.len() * 2);
+        for (idx, header) in headers.iter().enumerate() {
+            let name = String::from_utf8_lossy(header).to_string();
+            let ident: String = name
+                .chars()
+                .map(|c| {
+                    if c.is_alphanumeric() || c == '_' {
+                        c
+                    } else {
+                        '_'
+                    }
+                })
+                .collect();
+            col_idents.entry(name).or_insert(idx);
+            col_idents.entry(ident).or_insert(idx);
+        }
+
+        let rules_text = match std::fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(e) => return fail_clierror!("Cannot read rules file {path}: {e}."),
+        };
+        let mut rules = Vec::new();
+        for (line_idx, line) in rules_text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let Some((name, expression)) = line.split_once(':') else {
+                return fail_incorrectusage_clierror!(
+                    "Line {} of the rules file must be <name>: <expression>.",
+                    line_idx + 1
+                );
+            };
+            let (name, expression) = (name.trim(), expression.trim());
+            let node = match evalexpr::build_operator_tree(expression) {
+                Ok(node) => node,
+                Err(e) => {
+                    return fail_incorrectusage_clierror!("Invalid rule \"{name}\": {e}.");
+                },
+            };
+            let mut cols: Vec<(usize, String)> = Vec::new();
+            for ident in node.iter_variable_identifiers() {
+                let Some(&idx) = col_idents.get(ident) else {
+                    return fail_incorrectusage_clierror!(
+                        "Invalid rule \"{name}\": column \"{ident}\" not found."
+                    );
+                };
+                if !cols.iter().any(|(_, col_ident)| col_ident == ident) {
+                    cols.push((idx, ident.to_string()));
+                }
+            }
+            cols.sort_unstable_by_key(|(idx, _)| *idx);
+            let fields = cols
+                .iter()
+                .map(|(idx, _)| String::from_utf8_lossy(&headers[*idx]))
+                .dedup()
+                .join(",");
+
+            rules.push(Rule {
+                name: name.to_string(),
+                expression: expression.to_string(),
+                node,
+                cols,
+                fields,
+            });
+        }
+
+        Ok(Self { rules, col_types })
+    }
+
+    /// if the record passes all the rules, return None, otherwise, error file entry string
+    fn check(&self, record: &ByteRecord, header_len: usize) -> Option<String> {
+        if self.rules.is_empty() {
+            return None;
+        }
+        // row number was added as last column
+        let row_number_string = from_utf8(record.get(header_len).unwrap()).unwrap();
+
+        let mut errors: Vec<String> = Vec::new();
+        for rule in &self.rules {
+            let mut context = HashMapContext::new();
+            let mut has_empty = false;
+            for (idx, ident) in &rule.cols {
+                let value = self.typed_value(record.get(*idx).unwrap_or_default(), *idx);
+                has_empty = has_empty || value.is_empty();
+                // safety: setting a value can only fail if its type changes
+                let _ = context.set_value(ident.clone(), value);
+            }
+            match rule.node.eval_boolean_with_context(&context) {
+                Ok(true) => {},
+                Ok(false) => errors.push(format!(
+                    "{row_number_string}\t{}\tfailed rule \"{}\": {}",
+                    rule.fields, rule.name, rule.expression
+                )),
+                // leave empty values to the schema
+                Err(_) if has_empty => {},
+                Err(e) => errors.push(format!(
+                    "{row_number_string}\t{}\trule \"{}\" cannot be evaluated: {e}",
+                    rule.fields, rule.name
+                )),
+            }
+        }
+
+        if errors.is_empty() {
+            None
+        } else {
+            Some(errors.join("\n"))
+        }
+    }
+
+    /// type a value for the rules. Values that are not of their column's type are strings.
+    #[inline]
+    fn typed_value(&self, value: &[u8], idx: usize) -> EvalValue {
+        if value.is_empty() {
+            return EvalValue::Empty;
+        }
+        let value = String::from_utf8_lossy(value);
+        let typed = match self.col_types[idx] {
+            RuleType::String => None,
+            RuleType::Integer => atoi_simd::parse::<i64>(value.as_bytes())
+                .ok()
+                .map(EvalValue::Int),
+            RuleType::Float => value.parse::<f64>().ok().map(EvalValue::Float),
+            RuleType::Boolean => value.parse::<bool>().ok().map(EvalValue::Boolean),
+            RuleType::Date => parse_with_preference(&value, false)
+                .ok()
+                .map(|date| EvalValue::Int(date.timestamp())),
+        };
+        typed.unwrap_or_else(|| EvalValue::String(value.to_string()))
+    }
+}
+
+/// type the columns for the cross-field rules with the JSON Schema, and for the string columns
+/// & those not in the schema, with the stats cache of the input, if it's up to date
+fn get_rule_types(
+    headers: &ByteRecord,
+    schema: &Value,
+    input_path: &str,
+) -> CliResult<Vec<RuleType>> {
+    let mut col_types = Vec::with_capacity(headers.len());
+    for header in headers {
+        let field_def = schema
+            .get("properties")
+            .and_then(|properties| properties.get(String::from_utf8_lossy(header).as_ref()));
+        let Some(field_def) = field_def else {
+            col_types.push(RuleType::String);
+            continue;
+        };
+        let types: Vec<&str> = match field_def.get("type") {
+            Some(Value::String(s)) => vec![s.as_str()],
+            Some(Value::Array(types)) => types.iter().filter_map(Value::as_str).collect(),
+            _ => vec![],
+        };
+        let is_date = matches!(
+            field_def.get("format").and_then(Value::as_str),
+            Some("date" | "date-time")
+        ) || field_def.get("dateFormat").is_some();
+        col_types.push(if types.contains(&"integer") {
+            RuleType::Integer
+        } else if types.contains(&"number") {
+            RuleType::Float
+        } else if types.contains(&"boolean") {
+            RuleType::Boolean
+        } else if is_date {
+            RuleType::Date
+        } else {
+            RuleType::String
+        });
+    }
+
+    // the stats cache is only used if it's not older than the input
+    let input_path = Path::new(input_path);
+    let stats_path = input_path.with_extension("stats.csv");
+    let stats_is_current = match (stats_path.metadata(), input_path.metadata()) {
+        (Ok(stats_meta), Ok(input_meta)) => match (stats_meta.modified(), input_meta.modified()) {
+            (Ok(stats_modified), Ok(input_modified)) => stats_modified >= input_modified,
+            _ => false,
+        },
+        _ => false,
+    };
+    if !stats_is_current {
+        return Ok(col_types);
+    }
+
+    let mut stats_rdr = csv::Reader::from_path(&stats_path)?;
+    let stats_headers = stats_rdr.headers()?.clone();
+    let (Some(field_idx), Some(type_idx)) = (
+        stats_headers.iter().position(|h| h == "field"),
+        stats_headers.iter().position(|h| h == "type"),
+    ) else {
+        return Ok(col_types);
+    };
+    for stats_record in stats_rdr.records() {
+        let stats_record = stats_record?;
+        let Some(col_idx) = headers
+            .iter()
+            .position(|h| h == stats_record[field_idx].as_bytes())
+        else {
+            continue;
+        };
+        if col_types[col_idx] != RuleType::String {
+            continue;
+        }
+        col_types[col_idx] = match &stats_record[type_idx] {
+            "Integer" => RuleType::Integer,
+            "Float" => RuleType::Float,
+            "Boolean" => RuleType::Boolean,
+            "Date" | "DateTime" => RuleType::Date,
+            _ => RuleType::String,
+        };
+    }
+    Ok(col_types)
+}
+
+/// a structural problem of a record, found in RFC 4180 exhaustive mode
+#[derive(Serialize)]
+struct StructuralError {
+    record:          u64,
+    line:            u64,
+    byte_offset:     u64,
+    expected_fields: usize,
+    actual_fields:   usize,
+    bad_quotes:      Vec<u64>,
+    invalid_utf8:    Vec<(u64, u64)>,
+}
+
+#[derive(Serialize)]
+struct RFC4180Inventory {
+    delimiter_char: char,
+    header_row:     bool,
+    quote_char:     char,
+    num_records:    u64,
+    num_fields:     usize,
+    num_malformed:  u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    repaired_file:  Option<String>,
+    errors:         Vec<StructuralError>,
+}
+
+#[derive(PartialEq)]
+enum ScanState {
+    StartField,
+    Unquoted,
+    Quoted,
+    /// a quote in a quoted field - either an escaped quote or the closing quote
+    QuoteInQuoted,
+}
+
+/// a record as scanned from the raw bytes of a CSV
+pub(crate) struct RawRecord {
+    pub(crate) byte_offset: u64,
+    pub(crate) line:        u64,
+    pub(crate) fields:      Vec<Vec<u8>>,
+    pub(crate) bad_quotes:  Vec<u64>,
+    /// the bytes of the record, without its terminator
+    pub(crate) raw:         Vec<u8>,
+}
+
+/// scans a CSV record by record, keeping track of the structural problems that
+/// the csv reader either stops at or silently tolerates
+pub(crate) struct RecordScanner<R: BufRead> {
+    rdr:       R,
+    delimiter: u8,
+    quote:     u8,
+    /// the offset & line of the next byte
+    offset:    u64,
+    line:      u64,
+}
+
+impl<R: BufRead> RecordScanner<R> {
+    pub(crate) fn new(rdr: R, delimiter: u8, quote: u8) -> Self {
+        RecordScanner {
+            rdr,
+            delimiter,
+            quote,
+            offset: 0,
+            line: 1,
+        }
+    }
+
+    fn next_byte(&mut self) -> std::io::Result<Option<u8>> {
+        let Some(&b) = self.rdr.fill_buf()?.first() else {
+            return Ok(None);
+        };
+        self.rdr.consume(1);
+        self.offset += 1;
+        if b == b'\n' {
+            self.line += 1;
+        }
+        Ok(Some(b))
+    }
+
+    pub(crate) fn next_record(&mut self) -> std::io::Result<Option<RawRecord>> {
+        let mut record = RawRecord {
+            byte_offset: self.offset,
+            line:        self.line,
+            fields:      Vec::new(),
+            bad_quotes:  Vec::new(),
+            raw:         Vec::new(),
+        };
+        let mut field: Vec<u8> = Vec::new();
+        let mut state = ScanState::StartField;
+        let mut quote_start = 0_u64;
+
+        loop {
+            let Some(b) = self.next_byte()? else {
+                // EOF
+                if state == ScanState::Quoted {
+                    record.bad_quotes.push(quote_start);
+                }
+                if record.raw.is_empty() && record.fields.is_empty() {
+                    return Ok(None);
+                }
+                record.fields.push(field);
+                return Ok(Some(record));
+            };
+            let pos = self.offset - 1;
+
+            match state {
+                ScanState::Quoted => {
+                    if b == self.quote {
+                        state = ScanState::QuoteInQuoted;
+                    } else {
+                        field.push(b);
+                    }
+                    record.raw.push(b);
+                    continue;
+                },
+                ScanState::QuoteInQuoted if b == self.quote => {
+                    // an escaped quote
+                    field.push(b);
+                    state = ScanState::Quoted;
+                    record.raw.push(b);
+                    continue;
+                },
+                _ => {},
+            }
+
+            if b == b'\n' || b == b'\r' {
+                if b == b'\r' && self.rdr.fill_buf()?.first() == Some(&b'\n') {
+                    self.next_byte()?;
+                }
+                if record.raw.is_empty() && record.fields.is_empty() {
+                    // skip empty lines, like the csv reader
+                    record.byte_offset = self.offset;
+                    record.line = self.line;
+                    continue;
+                }
+                record.fields.push(field);
+                return Ok(Some(record));
+            }
+            record.raw.push(b);
+            if b == self.delimiter {
+                record.fields.push(std::mem::take(&mut field));
+                state = ScanState::StartField;
+                continue;
+            }
+            match state {
+                ScanState::StartField if b == self.quote => {
+                    state = ScanState::Quoted;
+                    quote_start = pos;
+                },
+                ScanState::QuoteInQuoted => {
+                    // the closing quote is followed by something other than a delimiter
+                    record.bad_quotes.push(pos - 1);
+                    field.push(b);
+                    state = ScanState::Unquoted;
+                },
+                _ => {
+                    if b == self.quote {
+                        // a stray quote in an unquoted field
+                        record.bad_quotes.push(pos);
+                    }
+                    field.push(b);
+                    state = ScanState::Unquoted;
+                },
+            }
+        }
+    }
+}
+
+/// the [start, end) byte ranges of the invalid UTF-8 sequences in bytes starting at offset
+pub(crate) fn invalid_utf8_ranges(bytes: &[u8], offset: u64) -> Vec<(u64, u64)> {
+    let mut ranges: Vec<(u64, u64)> = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        let Err(e) = str::from_utf8(&bytes[i..]) else {
+            break;
+        };
+        let start = i + e.valid_up_to();
+        let end = start + e.error_len().unwrap_or(bytes.len() - start);
+        match ranges.last_mut() {
+            // merge adjacent invalid sequences
+            Some(last) if last.1 == offset + start as u64 => last.1 = offset + end as u64,
+            _ => ranges.push((offset + start as u64, offset + end as u64)),
+        }
+        i = end;
+    }
+    ranges
+}
+
+/// RFC 4180 exhaustive mode - report all the malformed records, and optionally repair them
+fn rfc4180_inventory(args: &Args, rconfig: &Config) -> CliResult<()> {
+    let mut scanner = RecordScanner::new(
+        BufReader::new(rconfig.io_reader()?),
+        rconfig.get_delimiter(),
+        rconfig.quote,
+    );
+    let mut repair_wtr = match args.flag_repair {
+        Some(ref repair_path) => Some(
+            Config::new(&Some(repair_path.clone()))
+                .delimiter(args.flag_delimiter)
+                .writer()?,
+        ),
+        None => None,
+    };
+
+    let mut errors: Vec<StructuralError> = Vec::new();
+    let mut expected_fields = 0_usize;
+    let mut record_idx = 0_u64;
+    while let Some(record) = scanner.next_record()? {
+        if record_idx == 0 {
+            expected_fields = record.fields.len();
+        }
+        let invalid_utf8 = invalid_utf8_ranges(&record.raw, record.byte_offset);
+        if record.fields.len() != expected_fields
+            || !record.bad_quotes.is_empty()
+            || !invalid_utf8.is_empty()
+        {
+            errors.push(StructuralError {
+                record: record_idx,
+                line: record.line,
+                byte_offset: record.byte_offset,
+                expected_fields,
+                actual_fields: record.fields.len(),
+                bad_quotes: record.bad_quotes,
+                invalid_utf8,
+            });
+        }
+
+        if let Some(ref mut wtr) = repair_wtr {
+            let mut fields: Vec<String> = record
+                .fields
+                .iter()
+                .map(|field| String::from_utf8_lossy(field).to_string())
+                .collect();
+            fields.resize(expected_fields, String::new());
+            wtr.write_record(&fields)?;
+        }
+        record_idx += 1;
+    }
+    if let Some(ref mut wtr) = repair_wtr {
+        wtr.flush()?;
+    }
+
+    let header_row = !rconfig.no_headers;
+    let inventory = RFC4180Inventory {
+        delimiter_char: rconfig.get_delimiter() as char,
+        header_row,
+        quote_char: rconfig.quote as char,
+        num_records: record_idx.saturating_sub(u64::from(header_row)),
+        num_fields: expected_fields,
+        num_malformed: errors.len() as u64,
+        repaired_file: args.flag_repair.clone(),
+        errors,
+    };
+    let json = if args.flag_pretty_json {
+        serde_json::to_string_pretty(&inventory).unwrap()
+    } else {
+        serde_json::to_string(&inventory).unwrap()
+    };
+    woutinfo!("{json}");
+
+    if inventory.num_malformed > 0 {
+        return fail_clierror!(
+            "{} malformed record/s found.",
+            HumanCount(inventory.num_malformed)
+        );
+    }
+    Ok(())
+}
+
 fn split_invalid_records(
     rconfig: &Config,
     valid_flags: &[bool],
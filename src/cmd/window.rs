@@ -0,0 +1,496 @@
+static USAGE: &str = r#"
+Compute window functions (running totals, lag/lead, row numbers & rolling
+aggregates) over CSV data, adding the results as new columns.
+
+The CSV is processed in streaming mode. Rows are grouped into partitions of
+consecutive rows with the same --partition key values, and each window function
+is evaluated over the rows of its partition in input order. This means that the
+input must already be sorted by the partition columns, and then by the columns
+that determine the row order within a partition (e.g. a date column) - typically
+with the sort cmd, or the extsort cmd for larger than memory CSV files:
+
+    $ qsv sort -s region,date data.csv | qsv window -p region "cumsum:sales"
+
+Only the rows needed by the largest lag/lead offset and the largest rolling window
+size are kept in memory, so memory use is bounded regardless of the input size.
+
+The <functions> argument is a comma-separated list of window function specs.
+Each spec has the form "function:column[:n]", where column is a column name or
+index as in 'qsv select', and n is the optional offset/window size:
+
+  row_number                  1-based row number within the partition.
+  cumsum:<col>                Running sum of <col>.
+  cummin:<col>                Running minimum of <col>.
+  cummax:<col>                Running maximum of <col>.
+  cumcount:<col>              Running count of non-empty values of <col>.
+  lag:<col>[:n]               Value of <col> n rows before (default n: 1).
+  lead:<col>[:n]              Value of <col> n rows after (default n: 1).
+  diff:<col>[:n]              <col> minus the value of <col> n rows before (default n: 1).
+  rolling_sum:<col>:n         Sum of <col> over the current & previous n-1 rows.
+  rolling_mean:<col>:n        Mean of <col> over the current & previous n-1 rows.
+  rolling_median:<col>:n      Median of <col> over the current & previous n-1 rows.
+  rolling_min:<col>:n         Minimum of <col> over the current & previous n-1 rows.
+  rolling_max:<col>:n         Maximum of <col> over the current & previous n-1 rows.
+
+Aggregating functions ignore empty and non-numeric values. Rolling windows at the
+start of a partition are computed over the rows available so far, unless a
+minimum is set with --min-periods. lag/lead/diff values that fall outside the
+partition are empty.
+
+New columns are named "<column>_<function>" (e.g. "sales_cumsum"), with the offset
+or window size appended when given (e.g. "sales_lag2", "sales_rolling_mean7").
+row_number is named "row_number".
+
+Examples:
+Add a running total and a 7-row rolling average of sales per region:
+    $ qsv window -p region "cumsum:sales,rolling_mean:sales:7" sorted.csv
+
+Add the previous day's closing price & the day-over-day change per ticker,
+checking that the rows of each ticker are in date order:
+    $ qsv window -p ticker --order-by date "lag:close,diff:close" prices.csv
+
+For more examples, see https://github.com/jqnatividad/qsv/blob/master/tests/test_window.rs.
+
+Usage:
+    qsv window [options] <functions> [<input>]
+    qsv window --help
+
+window options:
+    -p, --partition <cols>   The columns that partition the rows. The window functions
+                             restart at every change of the partition key values.
+                             If not set, the whole CSV is one partition.
+                             See 'qsv select --help' for the format details.
+    --order-by <cols>        The columns that order the rows within a partition.
+                             When set, qsv checks that the rows of each partition are
+                             sorted by these columns and fails otherwise.
+    -N, --numeric            Compare --order-by columns according to their numerical value.
+    --min-periods <n>        The minimum number of numeric values in a rolling window
+                             to compute a value. Otherwise, the value is empty.
+                             [default: 1]
+    --round <places>         Round computed values to <places> decimal places.
+                             Rounding follows Midpoint Nearest Even (Bankers Rounding).
+                             [default: 4]
+
+Common options:
+    -h, --help               Display this message
+    -o, --output <file>      Write output to <file> instead of stdout.
+    -n, --no-headers         When set, the first row will not be interpreted
+                             as headers. New columns will not get header names,
+                             and columns must be referred to by index.
+    -d, --delimiter <arg>    The field delimiter for reading CSV data.
+                             Must be a single character. (default: ,)
+"#;
+
+use std::{cmp, collections::VecDeque};
+
+use serde::Deserialize;
+use simdutf8::basic::from_utf8;
+
+use crate::{
+    cmd::sort::{iter_cmp, iter_cmp_num},
+    config::{Config, Delimiter},
+    select::SelectColumns,
+    util, CliResult,
+};
+
+#[derive(Deserialize)]
+struct Args {
+    arg_functions:    String,
+    arg_input:        Option<String>,
+    flag_partition:   Option<SelectColumns>,
+    flag_order_by:    Option<SelectColumns>,
+    flag_numeric:     bool,
+    flag_min_periods: usize,
+    flag_round:       u32,
+    flag_output:      Option<String>,
+    flag_no_headers:  bool,
+    flag_delimiter:   Option<Delimiter>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum WindowKind {
+    RowNumber,
+    CumSum,
+    CumMin,
+    CumMax,
+    CumCount,
+    Lag,
+    Lead,
+    Diff,
+    RollingSum,
+    RollingMean,
+    RollingMedian,
+    RollingMin,
+    RollingMax,
+}
+
+impl WindowKind {
+    fn from_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "row_number" => WindowKind::RowNumber,
+            "cumsum" => WindowKind::CumSum,
+            "cummin" => WindowKind::CumMin,
+            "cummax" => WindowKind::CumMax,
+            "cumcount" => WindowKind::CumCount,
+            "lag" => WindowKind::Lag,
+            "lead" => WindowKind::Lead,
+            "diff" => WindowKind::Diff,
+            "rolling_sum" => WindowKind::RollingSum,
+            "rolling_mean" => WindowKind::RollingMean,
+            "rolling_median" => WindowKind::RollingMedian,
+            "rolling_min" => WindowKind::RollingMin,
+            "rolling_max" => WindowKind::RollingMax,
+            _ => return None,
+        })
+    }
+
+    const fn is_rolling(self) -> bool {
+        matches!(
+            self,
+            WindowKind::RollingSum
+                | WindowKind::RollingMean
+                | WindowKind::RollingMedian
+                | WindowKind::RollingMin
+                | WindowKind::RollingMax
+        )
+    }
+}
+
+/// A parsed window function spec, e.g. "rolling_mean:sales:7"
+#[derive(Debug)]
+struct WindowFunc {
+    kind:   WindowKind,
+    column: usize,
+    n:      usize,
+    name:   String,
+}
+
+impl WindowFunc {
+    fn parse(
+        spec: &str,
+        headers: &csv::ByteRecord,
+        no_headers: bool,
+    ) -> Result<WindowFunc, String> {
+        let parts: Vec<&str> = spec.trim().split(':').collect();
+        let func_name = parts[0].trim();
+        let Some(kind) = WindowKind::from_name(func_name) else {
+            return fail_format!("Unknown window function \"{func_name}\" in \"{spec}\".");
+        };
+
+        if kind == WindowKind::RowNumber {
+            if parts.len() > 1 {
+                return fail_format!("row_number does not take a column: \"{spec}\".");
+            }
+            return Ok(WindowFunc {
+                kind,
+                column: 0,
+                n: 0,
+                name: "row_number".to_string(),
+            });
+        }
+
+        let (col_spec, n_spec) = match parts.len() {
+            2 => (parts[1], None),
+            3 => (parts[1], Some(parts[2])),
+            _ => {
+                return fail_format!(
+                    "Invalid window function spec \"{spec}\". Expected \"function:column[:n]\"."
+                )
+            },
+        };
+
+        let sel = SelectColumns::parse(col_spec)?.selection(headers, !no_headers)?;
+        if sel.len() != 1 {
+            return fail_format!("\"{spec}\" must select exactly one column.");
+        }
+        let column = sel[0];
+
+        let n = match n_spec {
+            Some(n_str) => match n_str.trim().parse::<usize>() {
+                Ok(n) if n > 0 => n,
+                _ => {
+                    return fail_format!(
+                        "Invalid offset/window size \"{n_str}\" in \"{spec}\". It must be a \
+                         positive integer."
+                    )
+                },
+            },
+            None if kind.is_rolling() => {
+                return fail_format!(
+                    "{func_name} requires a window size: \"{func_name}:column:n\"."
+                )
+            },
+            None => 1,
+        };
+
+        let col_name = String::from_utf8_lossy(&headers[column]);
+        let name = match (kind, n_spec) {
+            (WindowKind::CumSum, _) => format!("{col_name}_cumsum"),
+            (WindowKind::CumMin, _) => format!("{col_name}_cummin"),
+            (WindowKind::CumMax, _) => format!("{col_name}_cummax"),
+            (WindowKind::CumCount, _) => format!("{col_name}_cumcount"),
+            (_, Some(_)) => format!("{col_name}_{func_name}{n}"),
+            (_, None) => format!("{col_name}_{func_name}"),
+        };
+
+        Ok(WindowFunc {
+            kind,
+            column,
+            n,
+            name,
+        })
+    }
+}
+
+/// Running state of the cumulative window functions for the current partition
+#[derive(Clone, Copy, Default)]
+struct Running {
+    value: Option<f64>,
+    count: u64,
+}
+
+pub fn run(argv: &[&str]) -> CliResult<()> {
+    let args: Args = util::get_args(USAGE, argv)?;
+
+    let rconfig = Config::new(&args.arg_input)
+        .delimiter(args.flag_delimiter)
+        .no_headers(args.flag_no_headers);
+
+    let mut rdr = rconfig.reader()?;
+    let mut wtr = Config::new(&args.flag_output).writer()?;
+
+    let mut headers = rdr.byte_headers()?.clone();
+
+    let funcs = args
+        .arg_functions
+        .split(',')
+        .filter(|s| !s.trim().is_empty())
+        .map(|spec| WindowFunc::parse(spec, &headers, rconfig.no_headers))
+        .collect::<Result<Vec<_>, _>>()?;
+    if funcs.is_empty() {
+        return fail_incorrectusage_clierror!("No window functions specified.");
+    }
+
+    let partition = match args.flag_partition {
+        Some(sel) => Some(sel.selection(&headers, !rconfig.no_headers)?),
+        None => None,
+    };
+    let order_by = match args.flag_order_by {
+        Some(sel) => Some(sel.selection(&headers, !rconfig.no_headers)?),
+        None => None,
+    };
+
+    // the number of rows before & after the current row we need to keep around
+    let lookback = funcs
+        .iter()
+        .map(|f| match f.kind {
+            WindowKind::Lag | WindowKind::Diff => f.n,
+            k if k.is_rolling() => f.n - 1,
+            _ => 0,
+        })
+        .max()
+        .unwrap_or(0);
+    let lookahead = funcs
+        .iter()
+        .filter(|f| f.kind == WindowKind::Lead)
+        .map(|f| f.n)
+        .max()
+        .unwrap_or(0);
+
+    if !rconfig.no_headers {
+        for func in &funcs {
+            headers.push_field(func.name.as_bytes());
+        }
+        wtr.write_record(&headers)?;
+    }
+
+    let mut windower = Windower {
+        running: vec![Running::default(); funcs.len()],
+        funcs,
+        lookback,
+        lookahead,
+        min_periods: args.flag_min_periods,
+        round: args.flag_round,
+        buffer: VecDeque::with_capacity(lookback + lookahead + 1),
+        next_emit: 0,
+        row_number: 0,
+        output: csv::ByteRecord::new(),
+    };
+
+    let mut record = csv::ByteRecord::new();
+    let mut curr_key: Option<Vec<Vec<u8>>> = None;
+    let mut prev_record: Option<csv::ByteRecord> = None;
+    let mut row_idx = 0_u64;
+
+    while rdr.read_byte_record(&mut record)? {
+        row_idx += 1;
+        let key = partition
+            .as_ref()
+            .map(|sel| sel.select(&record).map(<[u8]>::to_vec).collect::<Vec<_>>());
+
+        let new_partition = match (&curr_key, &key) {
+            (Some(curr), Some(key)) => curr != key,
+            _ => false,
+        };
+        if new_partition {
+            windower.flush(&mut wtr)?;
+            prev_record = None;
+        }
+
+        if let (Some(order_sel), Some(prev)) = (&order_by, &prev_record) {
+            let ordering = if args.flag_numeric {
+                iter_cmp_num(order_sel.select(prev), order_sel.select(&record))
+            } else {
+                iter_cmp(order_sel.select(prev), order_sel.select(&record))
+            };
+            if ordering == cmp::Ordering::Greater {
+                return fail_clierror!(
+                    "Row {row_idx} is out of order. The input must be sorted by the --partition \
+                     and --order-by columns. Use the sort or extsort command to sort it first."
+                );
+            }
+        }
+        if order_by.is_some() {
+            prev_record = Some(record.clone());
+        }
+
+        curr_key = key;
+        windower.push(record.clone(), &mut wtr)?;
+    }
+    windower.flush(&mut wtr)?;
+
+    Ok(wtr.flush()?)
+}
+
+type BoxedWriter = csv::Writer<Box<dyn std::io::Write + 'static>>;
+
+struct Windower {
+    funcs:       Vec<WindowFunc>,
+    lookback:    usize,
+    lookahead:   usize,
+    min_periods: usize,
+    round:       u32,
+    // the rows of the current partition we still need - up to `lookback` rows
+    // that were already written, followed by the rows that are still pending
+    buffer:      VecDeque<csv::ByteRecord>,
+    // index into buffer of the next row to write
+    next_emit:   usize,
+    running:     Vec<Running>,
+    row_number:  u64,
+    output:      csv::ByteRecord,
+}
+
+impl Windower {
+    fn push(&mut self, record: csv::ByteRecord, wtr: &mut BoxedWriter) -> CliResult<()> {
+        self.buffer.push_back(record);
+        while self.buffer.len() - self.next_emit > self.lookahead {
+            self.emit(wtr)?;
+        }
+        Ok(())
+    }
+
+    /// write out all the pending rows of the current partition and reset its state
+    fn flush(&mut self, wtr: &mut BoxedWriter) -> CliResult<()> {
+        while self.next_emit < self.buffer.len() {
+            self.emit(wtr)?;
+        }
+        self.buffer.clear();
+        self.next_emit = 0;
+        self.row_number = 0;
+        self.running.fill(Running::default());
+        Ok(())
+    }
+
+    fn emit(&mut self, wtr: &mut BoxedWriter) -> CliResult<()> {
+        let idx = self.next_emit;
+        self.row_number += 1;
+
+        self.output.clone_from(&self.buffer[idx]);
+        for (func, running) in self.funcs.iter().zip(self.running.iter_mut()) {
+            let value = match func.kind {
+                WindowKind::RowNumber => Some(self.row_number.to_string()),
+                WindowKind::CumSum | WindowKind::CumMin | WindowKind::CumMax => {
+                    if let Some(val) = parse_num(&self.buffer[idx][func.column]) {
+                        running.value = Some(match (func.kind, running.value) {
+                            (_, None) => val,
+                            (WindowKind::CumSum, Some(acc)) => acc + val,
+                            (WindowKind::CumMin, Some(acc)) => acc.min(val),
+                            (_, Some(acc)) => acc.max(val),
+                        });
+                    }
+                    running.value.map(|v| util::round_num(v, self.round))
+                },
+                WindowKind::CumCount => {
+                    if !self.buffer[idx][func.column].is_empty() {
+                        running.count += 1;
+                    }
+                    Some(running.count.to_string())
+                },
+                WindowKind::Lag => idx
+                    .checked_sub(func.n)
+                    .map(|i| String::from_utf8_lossy(&self.buffer[i][func.column]).into_owned()),
+                WindowKind::Lead => self
+                    .buffer
+                    .get(idx + func.n)
+                    .map(|r| String::from_utf8_lossy(&r[func.column]).into_owned()),
+                WindowKind::Diff => match (
+                    parse_num(&self.buffer[idx][func.column]),
+                    idx.checked_sub(func.n)
+                        .and_then(|i| parse_num(&self.buffer[i][func.column])),
+                ) {
+                    (Some(curr), Some(prev)) => Some(util::round_num(curr - prev, self.round)),
+                    _ => None,
+                },
+                _ => {
+                    let start = (idx + 1).saturating_sub(func.n);
+                    let mut values: Vec<f64> = (start..=idx)
+                        .filter_map(|i| parse_num(&self.buffer[i][func.column]))
+                        .collect();
+                    if values.is_empty() || values.len() < self.min_periods {
+                        None
+                    } else {
+                        Some(util::round_num(
+                            rolling_aggregate(func.kind, &mut values),
+                            self.round,
+                        ))
+                    }
+                },
+            };
+            self.output
+                .push_field(value.as_deref().unwrap_or_default().as_bytes());
+        }
+        wtr.write_byte_record(&self.output)?;
+
+        // drop the rows we no longer need to look back to
+        self.next_emit += 1;
+        while self.next_emit > self.lookback {
+            self.buffer.pop_front();
+            self.next_emit -= 1;
+        }
+        Ok(())
+    }
+}
+
+#[inline]
+fn parse_num(bytes: &[u8]) -> Option<f64> {
+    from_utf8(bytes).ok()?.trim().parse::<f64>().ok()
+}
+
+fn rolling_aggregate(kind: WindowKind, values: &mut [f64]) -> f64 {
+    match kind {
+        WindowKind::RollingSum => values.iter().sum(),
+        WindowKind::RollingMean => values.iter().sum::<f64>() / values.len() as f64,
+        WindowKind::RollingMin => values.iter().copied().fold(f64::INFINITY, f64::min),
+        WindowKind::RollingMax => values.iter().copied().fold(f64::NEG_INFINITY, f64::max),
+        _ => {
+            // RollingMedian
+            values.sort_unstable_by(|a, b| a.partial_cmp(b).unwrap_or(cmp::Ordering::Equal));
+            let mid = values.len() / 2;
+            if values.len() % 2 == 0 {
+                (values[mid - 1] + values[mid]) / 2.0
+            } else {
+                values[mid]
+            }
+        },
+    }
+}
@@ -70,6 +70,44 @@ impl<'de> Deserialize<'de> for Delimiter {
     }
 }
 
+/// NullValues are the values that are treated as NULL, besides the empty string.
+///
+/// They are set with the QSV_NULL_VALUES environment variable, or the --null-values
+/// option of the commands that support it, as a comma-delimited list of case-sensitive
+/// values (e.g. "NA,NULL,\N,-999").
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct NullValues(Vec<Vec<u8>>);
+
+impl NullValues {
+    pub fn new(list: &str) -> NullValues {
+        NullValues(
+            list.split(',')
+                .map(str::trim)
+                .filter(|value| !value.is_empty())
+                .map(|value| value.as_bytes().to_vec())
+                .collect(),
+        )
+    }
+
+    #[inline]
+    pub fn is_null(&self, field: &[u8]) -> bool {
+        field.is_empty() || self.0.iter().any(|null_value| null_value == field)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// the NULL values as a comma-delimited list, e.g. to pass them on to another command
+    pub fn to_list(&self) -> String {
+        self.0
+            .iter()
+            .map(|null_value| String::from_utf8_lossy(null_value))
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct Config {
     pub path:           Option<PathBuf>, // None implies <stdin>
@@ -92,6 +130,7 @@ pub struct Config {
     snappy:             bool, // flag to enable snappy compression/decompression
     pub read_buffer:    u32,
     pub write_buffer:   u32,
+    null_values:        NullValues,
 }
 
 // Empty trait as an alias for Seek and Read that avoids auto trait errors
@@ -212,6 +251,7 @@ impl Config {
                 .unwrap_or_else(|_| DEFAULT_WTR_BUFFER_CAPACITY.to_string())
                 .parse()
                 .unwrap_or(DEFAULT_WTR_BUFFER_CAPACITY as u32),
+            null_values: NullValues::new(&env::var("QSV_NULL_VALUES").unwrap_or_default()),
         }
     }
 
@@ -226,6 +266,18 @@ impl Config {
         self.delimiter
     }
 
+    /// set the NULL values, overriding the QSV_NULL_VALUES env var
+    pub fn null_values(mut self, null_values: Option<String>) -> Config {
+        if let Some(null_values) = null_values {
+            self.null_values = NullValues::new(&null_values);
+        }
+        self
+    }
+
+    pub const fn get_null_values(&self) -> &NullValues {
+        &self.null_values
+    }
+
     pub const fn comment(mut self, c: Option<u8>) -> Config {
         self.comment = c;
         self
@@ -100,6 +100,7 @@ fn main() -> QsvExitCode {
     dedup       Remove redundant rows
     describegpt Infer extended metadata using a LLM
     diff        Find the difference between two CSVs
+    doctor      Diagnose data quality issues & suggest fixes
     enum        Add a new column enumerating CSV lines
     excel       Exports an Excel sheet to a CSV
     exclude     Excludes the records in one CSV from another
@@ -127,6 +128,12 @@ fn main() -> QsvExitCode {
 
     enabled_commands.push_str("    frequency   Show frequency tables\n");
 
+    #[cfg(all(feature = "fuzzy", not(feature = "lite")))]
+    enabled_commands.push_str(
+        "    fuzzydedup  Cluster near-duplicate rows for review
+    fuzzyjoin   Join CSV files on approximate string similarity\n",
+    );
+
     #[cfg(all(feature = "geocode", not(feature = "lite")))]
     enabled_commands
         .push_str("    geocode     Geocodes a location against the Geonames cities database.\n");
@@ -148,20 +155,29 @@ fn main() -> QsvExitCode {
     enabled_commands.push_str("    luau        Execute Luau script on CSV data\n");
 
     enabled_commands.push_str(
-        "    partition   Partition CSV data based on a column value
-    pseudo      Pseudonymise the values of a column\n",
+        "    mask        Mask the values of columns using a rules file
+    partition   Partition CSV data based on a column value
+    patch       Apply a diff to a CSV\n",
     );
 
+    #[cfg(all(feature = "piiscan", not(feature = "lite")))]
+    enabled_commands
+        .push_str("    piiscan     Scan columns for personally identifiable information\n");
+
+    enabled_commands.push_str("    pseudo      Pseudonymise the values of columns\n");
+
     #[cfg(all(feature = "python", feature = "feature_capable"))]
     enabled_commands.push_str("    py          Evaluate a Python expression on CSV data\n");
 
     enabled_commands.push_str(
-        "    rename      Rename the columns of CSV data efficiently
+        "    rangejoin   Join rows to the ranges of another CSV they fall in
+    rename      Rename the columns of CSV data efficiently
     replace     Replace patterns in CSV data
     reverse     Reverse rows of CSV data
     safenames   Modify a CSV's header names to db-safe names
     sample      Randomly sample CSV data
     schema      Generate JSON Schema from CSV data
+    schemadiff  Compare two datasets' structure & stats for drift
     search      Search CSV data with a regex
     searchset   Search CSV data with a regex set
     select      Select, re-order, duplicate or drop columns
@@ -190,7 +206,8 @@ fn main() -> QsvExitCode {
 
     enabled_commands.push_str(
         "    transpose   Transpose rows/columns of CSV data
-    validate    Validate CSV data for RFC4180-compliance or with JSON Schema",
+    validate    Validate CSV data for RFC4180-compliance or with JSON Schema
+    window      Compute running totals, lag/lead & rolling window functions",
     );
     let num_commands = enabled_commands.split('\n').count();
 
@@ -328,6 +345,7 @@ enum Command {
     Dedup,
     Describegpt,
     Diff,
+    Doctor,
     Enum,
     Excel,
     Exclude,
@@ -345,6 +363,10 @@ enum Command {
     #[cfg(all(feature = "foreach", target_family = "unix", not(feature = "lite")))]
     ForEach,
     Frequency,
+    #[cfg(all(feature = "fuzzy", feature = "feature_capable"))]
+    FuzzyDedup,
+    #[cfg(all(feature = "fuzzy", feature = "feature_capable"))]
+    FuzzyJoin,
     #[cfg(all(feature = "geocode", feature = "feature_capable"))]
     Geocode,
     Headers,
@@ -357,16 +379,22 @@ enum Command {
     Jsonl,
     #[cfg(all(feature = "luau", feature = "feature_capable"))]
     Luau,
+    Mask,
     Partition,
+    Patch,
+    #[cfg(all(feature = "piiscan", feature = "feature_capable"))]
+    PiiScan,
     Pseudo,
     #[cfg(all(feature = "python", feature = "feature_capable"))]
     Py,
+    RangeJoin,
     Rename,
     Replace,
     Reverse,
     Safenames,
     Sample,
     Schema,
+    SchemaDiff,
     Search,
     SearchSet,
     Select,
@@ -385,6 +413,7 @@ enum Command {
     To,
     Tojsonl,
     Validate,
+    Window,
 }
 
 impl Command {
@@ -412,6 +441,7 @@ impl Command {
             Command::Dedup => cmd::dedup::run(argv),
             Command::Describegpt => cmd::describegpt::run(argv),
             Command::Diff => cmd::diff::run(argv),
+            Command::Doctor => cmd::doctor::run(argv),
             Command::Enum => cmd::enumerate::run(argv),
             Command::Excel => cmd::excel::run(argv),
             Command::Exclude => cmd::exclude::run(argv),
@@ -429,6 +459,10 @@ impl Command {
             Command::Flatten => cmd::flatten::run(argv),
             Command::Fmt => cmd::fmt::run(argv),
             Command::Frequency => cmd::frequency::run(argv),
+            #[cfg(all(feature = "fuzzy", feature = "feature_capable"))]
+            Command::FuzzyDedup => cmd::fuzzydedup::run(argv),
+            #[cfg(all(feature = "fuzzy", feature = "feature_capable"))]
+            Command::FuzzyJoin => cmd::fuzzyjoin::run(argv),
             #[cfg(all(feature = "geocode", feature = "feature_capable"))]
             Command::Geocode => cmd::geocode::run(argv),
             Command::Headers => cmd::headers::run(argv),
@@ -445,16 +479,22 @@ impl Command {
             Command::Jsonl => cmd::jsonl::run(argv),
             #[cfg(all(feature = "luau", feature = "feature_capable"))]
             Command::Luau => cmd::luau::run(argv),
+            Command::Mask => cmd::mask::run(argv),
             Command::Partition => cmd::partition::run(argv),
+            Command::Patch => cmd::patch::run(argv),
+            #[cfg(all(feature = "piiscan", feature = "feature_capable"))]
+            Command::PiiScan => cmd::piiscan::run(argv),
             Command::Pseudo => cmd::pseudo::run(argv),
             #[cfg(all(feature = "python", feature = "feature_capable"))]
             Command::Py => cmd::python::run(argv),
+            Command::RangeJoin => cmd::rangejoin::run(argv),
             Command::Rename => cmd::rename::run(argv),
             Command::Replace => cmd::replace::run(argv),
             Command::Reverse => cmd::reverse::run(argv),
             Command::Safenames => cmd::safenames::run(argv),
             Command::Sample => cmd::sample::run(argv),
             Command::Schema => cmd::schema::run(argv),
+            Command::SchemaDiff => cmd::schemadiff::run(argv),
             Command::Search => cmd::search::run(argv),
             Command::SearchSet => cmd::searchset::run(argv),
             Command::Select => cmd::select::run(argv),
@@ -473,6 +513,7 @@ impl Command {
             Command::To => cmd::to::run(argv),
             Command::Tojsonl => cmd::tojsonl::run(argv),
             Command::Validate => cmd::validate::run(argv),
+            Command::Window => cmd::window::run(argv),
         }
     }
 }
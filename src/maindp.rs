@@ -69,7 +69,7 @@ macro_rules! command_list {
     input       Read CSVs w/ special quoting, skipping, trimming & transcoding rules
     joinp       Join CSV files using the Pola.rs engine
     luau        Execute Luau script on CSV data
-    pseudo      Pseudonymise the values of a column
+    pseudo      Pseudonymise the values of columns
     rename      Rename the columns of CSV data efficiently
     replace     Replace patterns in CSV data
     reverse     Reverse rows of CSV data
@@ -26,6 +26,7 @@ macro_rules! command_list {
     dedup       Remove redundant rows
     describegpt Infer extended metadata using a LLM
     diff        Find the difference between two CSVs
+    doctor      Diagnose data quality issues & suggest fixes
     enum        Add a new column enumerating CSV lines
     excel       Exports an Excel sheet to a CSV
     exclude     Excludes the records in one CSV from another
@@ -43,14 +44,18 @@ macro_rules! command_list {
     input       Read CSVs w/ special quoting, skipping, trimming & transcoding rules
     join        Join CSV files
     jsonl       Convert newline-delimited JSON files to CSV
+    mask        Mask the values of columns using a rules file
     partition   Partition CSV data based on a column value
-    pseudo      Pseudonymise the values of a column
+    patch       Apply a diff to a CSV
+    pseudo      Pseudonymise the values of columns
+    rangejoin   Join rows to the ranges of another CSV they fall in
     rename      Rename the columns of CSV data efficiently
     replace     Replace patterns in CSV data
     reverse     Reverse rows of CSV data
     safenames   Modify a CSV's header names to db-safe names
     sample      Randomly sample CSV data
     schema      Generate JSON Schema from CSV data
+    schemadiff  Compare two datasets' structure & stats for drift
     search      Search CSV data with a regex
     searchset   Search CSV data with a regex set
     select      Select, re-order, duplicate or drop columns
@@ -65,6 +70,7 @@ macro_rules! command_list {
     tojsonl     Convert CSV to newline-delimited JSON
     transpose   Transpose rows/columns of CSV data
     validate    Validate CSV data for RFC4180-compliance or with JSON Schema
+    window      Compute running totals, lag/lead & rolling window functions
 
 sponsored by datHere - Data Infrastructure Engineering (https://qsv.datHere.com)
 "
@@ -229,6 +235,7 @@ enum Command {
     Dedup,
     Describegpt,
     Diff,
+    Doctor,
     Enum,
     Excel,
     Exclude,
@@ -246,14 +253,18 @@ enum Command {
     Input,
     Join,
     Jsonl,
+    Mask,
     Partition,
+    Patch,
     Pseudo,
+    RangeJoin,
     Rename,
     Replace,
     Reverse,
     Safenames,
     Sample,
     Schema,
+    SchemaDiff,
     Search,
     SearchSet,
     Select,
@@ -268,6 +279,7 @@ enum Command {
     Tojsonl,
     Transpose,
     Validate,
+    Window,
 }
 
 impl Command {
@@ -292,6 +304,7 @@ impl Command {
             Command::Dedup => cmd::dedup::run(argv),
             Command::Describegpt => cmd::describegpt::run(argv),
             Command::Diff => cmd::diff::run(argv),
+            Command::Doctor => cmd::doctor::run(argv),
             Command::Enum => cmd::enumerate::run(argv),
             Command::Excel => cmd::excel::run(argv),
             Command::Exclude => cmd::exclude::run(argv),
@@ -313,14 +326,18 @@ impl Command {
             Command::Input => cmd::input::run(argv),
             Command::Join => cmd::join::run(argv),
             Command::Jsonl => cmd::jsonl::run(argv),
+            Command::Mask => cmd::mask::run(argv),
             Command::Partition => cmd::partition::run(argv),
+            Command::Patch => cmd::patch::run(argv),
             Command::Pseudo => cmd::pseudo::run(argv),
+            Command::RangeJoin => cmd::rangejoin::run(argv),
             Command::Rename => cmd::rename::run(argv),
             Command::Replace => cmd::replace::run(argv),
             Command::Reverse => cmd::reverse::run(argv),
             Command::Safenames => cmd::safenames::run(argv),
             Command::Sample => cmd::sample::run(argv),
             Command::Schema => cmd::schema::run(argv),
+            Command::SchemaDiff => cmd::schemadiff::run(argv),
             Command::Search => cmd::search::run(argv),
             Command::SearchSet => cmd::searchset::run(argv),
             Command::Select => cmd::select::run(argv),
@@ -335,6 +352,7 @@ impl Command {
             Command::Tojsonl => cmd::tojsonl::run(argv),
             Command::Transpose => cmd::transpose::run(argv),
             Command::Validate => cmd::validate::run(argv),
+            Command::Window => cmd::window::run(argv),
         }
     }
 }
@@ -1,5 +1,10 @@
 // blatantly copied from https://github.com/race604/dedup/blob/master/src/cache.rs
-use std::collections::HashSet;
+use std::{
+    collections::{hash_map::Entry, HashMap},
+    fs,
+    io::{self, Read, Write},
+    path::Path,
+};
 
 use log::debug;
 use odht::{Config, FxHashFn, HashTableOwned};
@@ -8,12 +13,16 @@ struct ExtDedupConfig;
 
 const CHUNK_SIZE: usize = 127;
 
+/// the magic bytes & format version at the start of a saved cache
+const STORE_MAGIC: &[u8; 8] = b"QSVXDDUP";
+const STORE_VERSION: u8 = 1;
+
 impl Config for ExtDedupConfig {
     type EncodedKey = [u8; CHUNK_SIZE + 1];
-    type EncodedValue = [u8; 1];
+    type EncodedValue = [u8; 8];
     type H = FxHashFn;
     type Key = [u8; CHUNK_SIZE + 1];
-    type Value = bool;
+    type Value = u64;
 
     #[inline]
     fn encode_key(k: &Self::Key) -> Self::EncodedKey {
@@ -22,7 +31,7 @@ impl Config for ExtDedupConfig {
 
     #[inline]
     fn encode_value(v: &Self::Value) -> Self::EncodedValue {
-        [*v as u8; 1]
+        v.to_le_bytes()
     }
 
     #[inline]
@@ -32,21 +41,24 @@ impl Config for ExtDedupConfig {
 
     #[inline]
     fn decode_value(v: &Self::EncodedValue) -> Self::Value {
-        v[0] == 1
+        u64::from_le_bytes(*v)
     }
 }
 
+/// A set of items, each with the row number it was inserted at, that spills to an
+/// on-disk hash table once its memory limit is reached.
 pub struct ExtDedupCache {
-    memo:       HashSet<String>,
+    memo:       HashMap<String, u64>,
     disk:       Option<HashTableOwned<ExtDedupConfig>>,
     memo_limit: u64,
     memo_size:  u64,
+    items:      u64,
 }
 
 impl ExtDedupCache {
     pub fn new(memo_limit: u64) -> Self {
         Self {
-            memo:       HashSet::new(),
+            memo:       HashMap::new(),
             disk:       None,
             memo_limit: if memo_limit == 0 {
                 u64::MAX
@@ -54,48 +66,155 @@ impl ExtDedupCache {
                 memo_limit
             },
             memo_size:  0,
+            items:      0,
+        }
+    }
+
+    /// Load a cache saved with `save` from `path`, returning it with the number of rows
+    /// that were recorded when it was saved.
+    pub fn load(path: &Path, memo_limit: u64) -> io::Result<(Self, u64)> {
+        let invalid = |msg: String| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("{}: {msg}", path.display()),
+            )
+        };
+
+        let mut bytes = Vec::new();
+        fs::File::open(path)?.read_to_end(&mut bytes)?;
+        let header_len = STORE_MAGIC.len() + 1 + 16;
+        if bytes.len() < header_len || &bytes[..STORE_MAGIC.len()] != STORE_MAGIC {
+            return Err(invalid("not an extdedup store".to_string()));
+        }
+        let version = bytes[STORE_MAGIC.len()];
+        if version != STORE_VERSION {
+            return Err(invalid(format!("unsupported store version {version}")));
+        }
+        let read_u64 = |offset: usize| {
+            u64::from_le_bytes(bytes[offset..offset + 8].try_into().unwrap_or_default())
+        };
+        let rows = read_u64(STORE_MAGIC.len() + 1);
+        let items = read_u64(STORE_MAGIC.len() + 9);
+        let disk = HashTableOwned::<ExtDedupConfig>::from_raw_bytes(&bytes[header_len..])
+            .map_err(|e| invalid(e.to_string()))?;
+
+        let mut cache = Self::new(memo_limit);
+        cache.disk = Some(disk);
+        cache.items = items;
+        Ok((cache, rows))
+    }
+
+    /// Save the cache to `path` with the number of `rows` seen so far, so it can be
+    /// loaded by a later run. The file is replaced atomically.
+    pub fn save(&mut self, path: &Path, rows: u64) -> io::Result<()> {
+        self.dump_to_disk(false);
+        let disk = self
+            .disk
+            .get_or_insert_with(|| HashTableOwned::<ExtDedupConfig>::with_capacity(1_000_000, 95));
+
+        let mut tmp_path = path.as_os_str().to_owned();
+        tmp_path.push(".tmp");
+        let mut wtr = io::BufWriter::new(fs::File::create(&tmp_path)?);
+        wtr.write_all(STORE_MAGIC)?;
+        wtr.write_all(&[STORE_VERSION])?;
+        wtr.write_all(&rows.to_le_bytes())?;
+        wtr.write_all(&self.items.to_le_bytes())?;
+        wtr.write_all(disk.raw_bytes())?;
+        wtr.into_inner()
+            .map_err(io::IntoInnerError::into_error)?
+            .sync_all()?;
+        fs::rename(&tmp_path, path)
+    }
+
+    /// The number of distinct items in the cache.
+    pub const fn len(&self) -> u64 {
+        self.items
+    }
+
+    /// Insert `item` with the `row` number it was first seen at.
+    /// Returns false, keeping the original row number, if the item was already in the cache.
+    #[inline]
+    pub fn insert(&mut self, item: &str, row: u64) -> bool {
+        if self.memo_size >= self.memo_limit {
+            self.dump_to_disk(false);
+        }
+
+        if self.is_on_disk(item) {
+            return false;
+        }
+        match self.memo.entry(item.to_owned()) {
+            Entry::Occupied(_) => false,
+            Entry::Vacant(entry) => {
+                entry.insert(row);
+                self.memo_size += item.len() as u64;
+                self.items += 1;
+                true
+            },
         }
     }
 
+    /// Set the row number of `item`, inserting it if needed.
+    /// Unlike `insert`, the latest row number wins. Don't mix `insert` & `update`
+    /// on the same cache, as they resolve the row numbers on disk differently.
+    /// Once spilled, the row numbers are only exact for items of at most CHUNK_SIZE bytes,
+    /// as a later update of an item also updates the chunks it shares with other items.
     #[inline]
-    pub fn insert(&mut self, item: &str) -> bool {
+    pub fn update(&mut self, item: &str, row: u64) {
         if self.memo_size >= self.memo_limit {
-            self.dump_to_disk();
+            self.dump_to_disk(true);
         }
 
-        let mut res = self.memo.insert(item.to_owned());
-        if res {
+        let on_disk = self.is_on_disk(item);
+        if self.memo.insert(item.to_owned(), row).is_none() {
             self.memo_size += item.len() as u64;
-            if self.disk.is_some() {
-                res = self.insert_on_disk(item);
-                // debug!("Insert on disk: {res}");
+            if !on_disk {
+                self.items += 1;
             }
         }
-
-        res
     }
 
     #[inline]
     pub fn contains(&self, item: &str) -> bool {
-        if self.memo.contains(item) {
-            return true;
+        self.get(item).is_some()
+    }
+
+    /// Get the row number of `item`, if it is in the cache.
+    #[inline]
+    pub fn get(&self, item: &str) -> Option<u64> {
+        if let Some(row) = self.memo.get(item) {
+            return Some(*row);
         }
 
-        return if let Some(ref disk) = self.disk {
+        let disk = self.disk.as_ref()?;
+        // an item is on disk if all its chunks are. As the row number of a chunk is only
+        // set when the chunk is first inserted, the item's own row number is the
+        // largest one of its chunks - the chunks it shares with earlier items have
+        // smaller row numbers.
+        let mut res = None;
+        for key in ExtDedupCache::item_to_keys(item) {
+            let row = disk.get(&key)?;
+            res = res.max(Some(row));
+        }
+        res
+    }
+
+    /// an item is on disk if all its chunks are
+    fn is_on_disk(&self, item: &str) -> bool {
+        self.disk.as_ref().is_some_and(|disk| {
             ExtDedupCache::item_to_keys(item).all(|key| disk.contains_key(&key))
-        } else {
-            false
-        };
+        })
     }
 
-    fn insert_on_disk(&mut self, item: &str) -> bool {
+    fn insert_on_disk(&mut self, item: &str, row: u64, overwrite: bool) -> bool {
         let disk = self.disk.get_or_insert_with(|| {
             debug!("Create new disk cache");
             HashTableOwned::<ExtDedupConfig>::with_capacity(1_000_000, 95)
         });
         let mut res = false;
         for key in ExtDedupCache::item_to_keys(item) {
-            res = disk.insert(&key, &true).is_none() || res;
+            if overwrite || !disk.contains_key(&key) {
+                res = disk.insert(&key, &row).is_none() || res;
+            }
         }
         res
     }
@@ -114,11 +233,19 @@ impl ExtDedupCache {
         res
     }
 
-    fn dump_to_disk(&mut self) {
+    fn dump_to_disk(&mut self, overwrite: bool) {
         // debug!("Memory cache is full, dump to disk");
-        let keys = self.memo.drain().collect::<Vec<_>>();
-        for key in keys {
-            self.insert_on_disk(&key);
+        let mut items = self.memo.drain().collect::<Vec<_>>();
+        // write the items in row order, so a chunk shared by several items keeps the
+        // smallest of their row numbers - the first one written without `overwrite`,
+        // the last one written with it. `get` then finds each item's own row number.
+        if overwrite {
+            items.sort_unstable_by(|(_, row1), (_, row2)| row2.cmp(row1));
+        } else {
+            items.sort_unstable_by_key(|(_, row)| *row);
+        }
+        for (item, row) in items {
+            self.insert_on_disk(&item, row, overwrite);
         }
         self.memo_size = 0;
     }
@@ -133,25 +260,112 @@ mod tests {
     #[test]
     fn test_basic_cache() {
         let mut cache = ExtDedupCache::new(0);
-        assert!(cache.insert("hello"));
-        assert!(cache.insert("world"));
+        assert!(cache.insert("hello", 1));
+        assert!(cache.insert("world", 2));
+        assert!(!cache.insert("hello", 3));
 
         assert!(cache.contains("hello"));
         assert!(cache.contains("world"));
         assert!(!cache.contains("other"));
+        assert_eq!(cache.get("hello"), Some(1));
+        assert_eq!(cache.get("other"), None);
+    }
+
+    #[test]
+    fn test_row_numbers_on_disk() {
+        let mut cache = ExtDedupCache::new(64);
+        let long_item = "x".repeat(CHUNK_SIZE * 2);
+        let items = (0..100_u64)
+            .map(|i| format!("{long_item}{i}"))
+            .collect::<Vec<_>>();
+        for (row, item) in items.iter().enumerate() {
+            assert!(cache.insert(item, row as u64));
+        }
+        assert!(cache.disk.is_some());
+        // the items share their first two chunks, but keep their own row numbers
+        for (row, item) in items.iter().enumerate() {
+            assert_eq!(cache.get(item), Some(row as u64));
+        }
+
+        let mut latest = ExtDedupCache::new(4);
+        for row in 0..100_u64 {
+            latest.update(&(row % 10).to_string(), row);
+        }
+        assert!(latest.disk.is_some());
+        for item in 0..10_u64 {
+            assert_eq!(latest.get(&item.to_string()), Some(90 + item));
+        }
+    }
+
+    #[test]
+    fn test_row_numbers_of_spilled_memo() {
+        // several long items sharing their first two chunks are in the memo at each dump
+        let mut cache = ExtDedupCache::new(2048);
+        let long_item = "y".repeat(CHUNK_SIZE * 2);
+        let items = (0..200_u64)
+            .map(|i| format!("{long_item}{i}"))
+            .collect::<Vec<_>>();
+        for (row, item) in items.iter().enumerate() {
+            assert!(cache.insert(item, row as u64));
+        }
+        cache.dump_to_disk(false);
+        assert!(cache.memo.is_empty());
+        for (row, item) in items.iter().enumerate() {
+            assert_eq!(cache.get(item), Some(row as u64));
+        }
+
+        // items already on disk are not inserted again
+        assert!(!cache.insert(&items[10], 500));
+        assert!(cache.memo.is_empty());
+        assert_eq!(cache.len(), 200);
+        assert_eq!(cache.get(&items[10]), Some(10));
+
+        // with a single dump, the latest row numbers of long items are exact too
+        let mut latest = ExtDedupCache::new(0);
+        for (row, item) in items.iter().enumerate() {
+            latest.update(item, row as u64);
+        }
+        latest.dump_to_disk(true);
+        for (row, item) in items.iter().enumerate() {
+            assert_eq!(latest.get(item), Some(row as u64));
+        }
+        assert_eq!(latest.len(), 200);
     }
 
     #[test]
     fn test_limit_memory() {
         let mut cache = ExtDedupCache::new(1024);
         for _ in 0..100 {
-            cache.insert(&rand_string(32));
+            cache.insert(&rand_string(32), 0);
         }
         assert!(cache.memo.len() < 100);
         assert!(cache.disk.is_some());
         assert!(cache.disk.unwrap().len() > 0);
     }
 
+    #[test]
+    fn test_save_and_load() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("test.store");
+
+        let mut cache = ExtDedupCache::new(0);
+        assert!(cache.insert("hello", 0));
+        assert!(cache.insert("world", 1));
+        cache.save(&path, 2).unwrap();
+
+        let (mut loaded, rows) = ExtDedupCache::load(&path, 0).unwrap();
+        assert_eq!(rows, 2);
+        assert_eq!(loaded.len(), 2);
+        assert_eq!(loaded.get("hello"), Some(0));
+        assert_eq!(loaded.get("world"), Some(1));
+        assert!(!loaded.contains("other"));
+        assert!(loaded.insert("other", 2));
+        assert_eq!(loaded.len(), 3);
+
+        fs::write(&path, b"not a store").unwrap();
+        assert!(ExtDedupCache::load(&path, 0).is_err());
+    }
+
     fn rand_string(len: usize) -> String {
         thread_rng()
             .sample_iter(&Alphanumeric)
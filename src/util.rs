@@ -158,6 +158,8 @@ pub fn version() -> String {
     enabled_features.push_str("foreach;");
     #[cfg(all(feature = "geocode", not(feature = "lite")))]
     enabled_features.push_str("geocode;");
+    #[cfg(all(feature = "piiscan", not(feature = "lite")))]
+    enabled_features.push_str("piiscan;");
 
     #[cfg(all(feature = "luau", not(feature = "lite")))]
     {
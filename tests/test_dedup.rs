@@ -287,3 +287,60 @@ fn dedup_not_sorted2() {
     let got: String = wrk.output_stderr(&mut cmd);
     assert!(got.contains("Aborting! Input not sorted!"));
 }
+
+#[test]
+fn dedup_keys_sorted() {
+    let wrk = Workdir::new("dedup_keys_sorted");
+    wrk.create(
+        "in.csv",
+        vec![
+            svec!["N", "S"],
+            svec!["20", "a"],
+            svec!["020", "b"],
+            svec!["10", "c"],
+            svec!["9", "d"],
+            svec!["9.0", "e"],
+        ],
+    );
+
+    let mut cmd = wrk.command("dedup");
+    cmd.args(["--keys", "N:num:desc"])
+        .arg("--sorted")
+        .arg("in.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["N", "S"],
+        svec!["20", "a"],
+        svec!["10", "c"],
+        svec!["9", "d"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn dedup_keys_unsorted() {
+    let wrk = Workdir::new("dedup_keys_unsorted");
+    wrk.create(
+        "in.csv",
+        vec![
+            svec!["file"],
+            svec!["file10"],
+            svec!["file2"],
+            svec!["file10"],
+            svec!["file1"],
+        ],
+    );
+
+    let mut cmd = wrk.command("dedup");
+    cmd.args(["--keys", "file:natural"]).arg("in.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["file"],
+        svec!["file1"],
+        svec!["file2"],
+        svec!["file10"],
+    ];
+    assert_eq!(got, expected);
+}
@@ -467,6 +467,139 @@ diffresult;h1;h2;h3
     assert_eq!(got.as_str(), expected);
 }
 
+fn create_change_report_files(wrk: &Workdir) {
+    wrk.create(
+        "left.csv",
+        vec![
+            svec!["id", "name", "amount"],
+            svec!["1", "Ann", "10"],
+            svec!["2", "Bob", "20"],
+            svec!["3", "Cy", "30"],
+            svec!["4", "Dan", "7"],
+        ],
+    );
+    wrk.create(
+        "right.csv",
+        vec![
+            svec!["id", "name", "amount"],
+            svec!["1", "Ann", "10"],
+            svec!["2", "Robert", "25"],
+            svec!["3", "Cy", "31"],
+            svec!["5", "Eve", "50"],
+        ],
+    );
+}
+
+#[test]
+fn diff_format_json() {
+    let wrk = Workdir::new("diff_format_json");
+    create_change_report_files(&wrk);
+
+    let mut cmd = wrk.command("diff");
+    cmd.args(["--format", "json", "left.csv", "right.csv"]);
+
+    let got: serde_json::Value = serde_json::from_str(&wrk.stdout::<String>(&mut cmd)).unwrap();
+    let expected = serde_json::json!({
+        "summary": {
+            "added": 1,
+            "deleted": 1,
+            "modified": 2,
+            "columns": {"name": 1, "amount": 2}
+        },
+        "changes": [
+            {
+                "change": "modified",
+                "key": {"id": "2"},
+                "columns": {
+                    "name": {"old": "Bob", "new": "Robert"},
+                    "amount": {"old": "20", "new": "25"}
+                }
+            },
+            {
+                "change": "modified",
+                "key": {"id": "3"},
+                "columns": {"amount": {"old": "30", "new": "31"}}
+            },
+            {
+                "change": "deleted",
+                "key": {"id": "4"},
+                "row": {"id": "4", "name": "Dan", "amount": "7"}
+            },
+            {
+                "change": "added",
+                "key": {"id": "5"},
+                "row": {"id": "5", "name": "Eve", "amount": "50"}
+            }
+        ]
+    });
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn diff_format_jsonl() {
+    let wrk = Workdir::new("diff_format_jsonl");
+    create_change_report_files(&wrk);
+
+    let mut cmd = wrk.command("diff");
+    cmd.args(["--format", "jsonl", "left.csv", "right.csv"]);
+
+    let got: String = wrk.stdout(&mut cmd);
+    let expected = r#"{"change":"modified","key":{"id":"2"},"columns":{"name":{"old":"Bob","new":"Robert"},"amount":{"old":"20","new":"25"}}}
+{"change":"modified","key":{"id":"3"},"columns":{"amount":{"old":"30","new":"31"}}}
+{"change":"deleted","key":{"id":"4"},"row":{"id":"4","name":"Dan","amount":"7"}}
+{"change":"added","key":{"id":"5"},"row":{"id":"5","name":"Eve","amount":"50"}}"#;
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn diff_format_side_by_side() {
+    let wrk = Workdir::new("diff_format_side_by_side");
+    create_change_report_files(&wrk);
+
+    let mut cmd = wrk.command("diff");
+    cmd.args(["--format", "side-by-side", "left.csv", "right.csv"]);
+
+    let got: String = wrk.stdout(&mut cmd);
+    let expected = "  KEY   COLUMN  OLD       | NEW
+~ id=2  name    Bob       | Robert
+~       amount  20        | 25
+~ id=3  amount  30        | 31
+- id=4          4, Dan, 7 |
++ id=5                    | 5, Eve, 50";
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn diff_format_summary() {
+    let wrk = Workdir::new("diff_format_summary");
+    create_change_report_files(&wrk);
+
+    let mut cmd = wrk.command("diff");
+    cmd.args(["--format", "summary", "left.csv", "right.csv"]);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["change", "column", "count"],
+        svec!["added", "", "1"],
+        svec!["deleted", "", "1"],
+        svec!["modified", "", "2"],
+        svec!["modified", "name", "1"],
+        svec!["modified", "amount", "2"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn diff_format_invalid() {
+    let wrk = Workdir::new("diff_format_invalid");
+    create_change_report_files(&wrk);
+
+    let mut cmd = wrk.command("diff");
+    cmd.args(["--format", "html", "left.csv", "right.csv"]);
+
+    wrk.assert_err(&mut cmd);
+}
+
 fn create_file_with_delim(wrk: &Workdir, file_path_new: &str, file_path: &str, delimiter: u8) {
     let mut select_cmd = wrk.command("select");
     select_cmd.args(["1-", file_path]);
@@ -0,0 +1,181 @@
+use crate::workdir::Workdir;
+
+#[test]
+fn doctor_findings() {
+    let wrk = Workdir::new("doctor_findings");
+    wrk.create(
+        "data.csv",
+        vec![
+            svec![
+                "id",
+                "unit price",
+                "amount",
+                "qty",
+                "signup_date",
+                "status",
+                "country",
+                "notes"
+            ],
+            svec![
+                "1",
+                " 10",
+                "1,234.50",
+                "1",
+                "2024-01-05",
+                "active",
+                "US",
+                ""
+            ],
+            svec!["2", "12", "99.5", "2", "2024-01-06", "N/A", "US", ""],
+            svec!["3", "13", "100", "three", "01/07/2024", "active", "US", ""],
+            svec!["4", "14", "2,000", "4", "2024-01-08", "-", "US", ""],
+            svec!["4", "14", "2,000", "4", "2024-01-08", "-", "US", ""],
+        ],
+    );
+
+    let mut cmd = wrk.command("doctor");
+    cmd.arg("data.csv").args(["--delimiter", ","]);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["field", "issue", "count", "example", "suggested_fix"],
+        svec!["", "duplicate_rows", "1", "6", "qsv dedup data.csv"],
+        svec![
+            "unit price",
+            "header",
+            "1",
+            "unit price -> unit_price",
+            "qsv safenames data.csv"
+        ],
+        svec![
+            "unit price",
+            "whitespace",
+            "1",
+            " 10",
+            "qsv apply operations trim 2 data.csv"
+        ],
+        svec![
+            "amount",
+            "thousands_separator",
+            "3",
+            "1,234.50",
+            "qsv apply operations regex_replace amount --comparand , --replacement '<NULL>' \
+             data.csv"
+        ],
+        svec![
+            "qty",
+            "mixed_types",
+            "1",
+            "three (string in a number column)",
+            r"qsv search --select qty --invert-match '^\s*$|^[+-]?(\d{1,3}(,\d{3})+(\.\d+)?|\d+(\.\d*)?|\.\d+)([eE][+-]?\d+)?$' data.csv"
+        ],
+        svec![
+            "signup_date",
+            "date_formats",
+            "1",
+            "01/07/2024 (99/99/9999 in a 9999-99-99 column)",
+            "qsv datefmt signup_date data.csv"
+        ],
+        svec![
+            "status",
+            "placeholder_nulls",
+            "3",
+            "N/A, -",
+            r"qsv apply operations regex_replace status --comparand '^\s*(N/A|\-)\s*$' --replacement '<NULL>' data.csv"
+        ],
+        svec![
+            "country",
+            "constant_column",
+            "5",
+            "US",
+            "qsv select '!country' data.csv"
+        ],
+        svec![
+            "notes",
+            "empty_column",
+            "5",
+            "",
+            "qsv select '!notes' data.csv"
+        ],
+    ];
+    assert_eq!(got, expected);
+
+    let got_err = wrk.output_stderr(&mut cmd);
+    assert_eq!(got_err, "9 issue/s found in data.csv.\n");
+    wrk.assert_err(&mut cmd);
+}
+
+#[test]
+fn doctor_structural_errors() {
+    let wrk = Workdir::new("doctor_structural_errors");
+    wrk.create_from_string("data.csv", "a,b\n1,x\n3\n4,\"y\"z\n");
+
+    let mut cmd = wrk.command("doctor");
+    cmd.arg("data.csv").args(["--delimiter", ","]);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["field", "issue", "count", "example", "suggested_fix"],
+        svec!["", "ragged_rows", "1", "3", "qsv fixlengths data.csv"],
+        svec![
+            "",
+            "bad_quotes",
+            "1",
+            "4",
+            "qsv validate --repair data.repaired.csv data.csv"
+        ],
+    ];
+    assert_eq!(got, expected);
+    wrk.assert_err(&mut cmd);
+}
+
+#[test]
+fn doctor_no_headers_placeholders() {
+    let wrk = Workdir::new("doctor_no_headers_placeholders");
+    wrk.create(
+        "data.csv",
+        vec![svec!["x", "unknown"], svec!["y", "ok"], svec!["z", "N/A"]],
+    );
+
+    let mut cmd = wrk.command("doctor");
+    cmd.arg("data.csv")
+        .args(["--delimiter", ","])
+        .args(["--placeholders", "Unknown"])
+        .arg("--no-headers");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["field", "issue", "count", "example", "suggested_fix"],
+        svec![
+            "2",
+            "placeholder_nulls",
+            "1",
+            "unknown",
+            r"qsv apply operations regex_replace 2 --comparand '^\s*(unknown)\s*$' --replacement '<NULL>' data.csv"
+        ],
+    ];
+    assert_eq!(got, expected);
+    wrk.assert_err(&mut cmd);
+}
+
+#[test]
+fn doctor_no_issues() {
+    let wrk = Workdir::new("doctor_no_issues");
+    wrk.create(
+        "data.csv",
+        vec![
+            svec!["id", "name", "joined"],
+            svec!["1", "Alice", "2024-01-05"],
+            svec!["2", "Bob", "2024-02-11"],
+            svec!["3", "Carol", "2024-03-20"],
+        ],
+    );
+
+    let mut cmd = wrk.command("doctor");
+    cmd.arg("data.csv").args(["--delimiter", ","]);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![svec!["field", "issue", "count", "example", "suggested_fix"]];
+    assert_eq!(got, expected);
+    wrk.assert_success(&mut cmd);
+}
@@ -54,3 +54,205 @@ fn extdedup_dupesoutput() {
 
     assert_eq!(dos2unix(&dupes_output), dos2unix(&expected_output));
 }
+
+fn setup_orders(name: &str) -> Workdir {
+    let wrk = Workdir::new(name);
+    wrk.create(
+        "orders.csv",
+        vec![
+            svec!["customer", "sku", "qty"],
+            svec!["alice", "A1", "1"],
+            svec!["bob", "B2", "2"],
+            svec!["alice", "A1", "3"],
+            svec!["carol", "A1", "4"],
+            svec!["bob", "B2", "5"],
+            svec!["alice", "B2", "6"],
+            svec!["bob", "B2", "7"],
+        ],
+    );
+    wrk
+}
+
+#[test]
+fn extdedup_select() {
+    let wrk = setup_orders("extdedup_select");
+    let mut cmd = wrk.command("extdedup");
+    cmd.args(["--select", "customer,sku"])
+        .args(["--dupes-output", "dupes.txt"])
+        .arg("--kept-row")
+        .arg("orders.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["customer", "sku", "qty"],
+        svec!["alice", "A1", "1"],
+        svec!["bob", "B2", "2"],
+        svec!["carol", "A1", "4"],
+        svec!["alice", "B2", "6"],
+    ];
+    assert_eq!(got, expected);
+
+    let got_err = wrk.output_stderr(&mut cmd);
+    assert_eq!(got_err, "3\n");
+
+    // the row number of each duplicate, then the row number of the kept row
+    let dupes: String = wrk.from_str(&wrk.path("dupes.txt"));
+    assert_eq!(
+        dos2unix(&dupes),
+        "3\t1\talice,A1,3\n5\t2\tbob,B2,5\n7\t2\tbob,B2,7\n"
+    );
+
+    // without --kept-row, only the row number of each duplicate
+    let mut cmd = wrk.command("extdedup");
+    cmd.args(["--select", "customer,sku"])
+        .args(["--dupes-output", "dupes.txt"])
+        .arg("orders.csv");
+    wrk.assert_success(&mut cmd);
+
+    let dupes: String = wrk.from_str(&wrk.path("dupes.txt"));
+    assert_eq!(
+        dos2unix(&dupes),
+        "3\talice,A1,3\n5\tbob,B2,5\n7\tbob,B2,7\n"
+    );
+}
+
+#[test]
+fn extdedup_select_keep_last() {
+    let wrk = setup_orders("extdedup_select_keep_last");
+    let mut cmd = wrk.command("extdedup");
+    cmd.args(["--select", "customer,sku"])
+        .args(["--keep", "last"])
+        .args(["--dupes-output", "dupes.txt"])
+        .arg("--kept-row")
+        .arg("orders.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["customer", "sku", "qty"],
+        svec!["alice", "A1", "3"],
+        svec!["carol", "A1", "4"],
+        svec!["alice", "B2", "6"],
+        svec!["bob", "B2", "7"],
+    ];
+    assert_eq!(got, expected);
+
+    let dupes: String = wrk.from_str(&wrk.path("dupes.txt"));
+    assert_eq!(
+        dos2unix(&dupes),
+        "1\t3\talice,A1,1\n2\t7\tbob,B2,2\n5\t7\tbob,B2,5\n"
+    );
+}
+
+#[test]
+fn extdedup_keep_last_lines() {
+    let wrk = Workdir::new("extdedup_keep_last_lines");
+    wrk.create_from_string("lines.txt", "a\nb\na\nc\nb\n");
+    let mut cmd = wrk.command("extdedup");
+    cmd.args(["--keep", "last"]).arg("lines.txt");
+
+    let got: String = wrk.stdout(&mut cmd);
+    assert_eq!(got, "a\nc\nb");
+}
+
+#[test]
+fn extdedup_keep_last_stdin_error() {
+    let wrk = Workdir::new("extdedup_keep_last_stdin_error");
+    let mut cmd = wrk.command("extdedup");
+    cmd.args(["--keep", "last"]);
+
+    wrk.assert_err(&mut cmd);
+}
+
+#[test]
+fn extdedup_store() {
+    let wrk = Workdir::new("extdedup_store");
+    wrk.create(
+        "day1.csv",
+        vec![
+            svec!["id", "value"],
+            svec!["1", "a"],
+            svec!["2", "b"],
+            svec!["1", "a"],
+        ],
+    );
+    wrk.create(
+        "day2.csv",
+        vec![
+            svec!["id", "value"],
+            svec!["2", "b"],
+            svec!["3", "c"],
+            svec!["3", "c"],
+            svec!["4", "d"],
+        ],
+    );
+
+    let mut cmd = wrk.command("extdedup");
+    cmd.args(["--select", "id"])
+        .args(["--store", "seen.store"])
+        .arg("day1.csv");
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![svec!["id", "value"], svec!["1", "a"], svec!["2", "b"]];
+    assert_eq!(got, expected);
+    assert!(wrk.path("seen.store").exists());
+
+    // only the records never seen before are output. The kept row number of
+    // a row seen in a previous run is empty.
+    let mut cmd = wrk.command("extdedup");
+    cmd.args(["--select", "id"])
+        .args(["--store", "seen.store"])
+        .args(["--dupes-output", "dupes.txt"])
+        .arg("--kept-row")
+        .arg("day2.csv");
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![svec!["id", "value"], svec!["3", "c"], svec!["4", "d"]];
+    assert_eq!(got, expected);
+
+    let dupes: String = wrk.from_str(&wrk.path("dupes.txt"));
+    assert_eq!(dos2unix(&dupes), "1\t\t2,b\n3\t2\t3,c\n");
+
+    // readonly doesn't add the rows of this run to the store
+    wrk.create("day3.csv", vec![svec!["id", "value"], svec!["5", "e"]]);
+    let mut cmd = wrk.command("extdedup");
+    cmd.args(["--select", "id"])
+        .args(["--store", "seen.store"])
+        .args(["--store-mode", "readonly"])
+        .arg("day3.csv");
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![svec!["id", "value"], svec!["5", "e"]];
+    assert_eq!(got, expected);
+
+    let mut cmd = wrk.command("extdedup");
+    cmd.args(["--select", "id"])
+        .args(["--store", "seen.store"])
+        .arg("--stats")
+        .arg("day3.csv");
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    assert_eq!(got, expected);
+
+    // running it again, the row is now a store hit
+    let got_err = wrk.output_stderr(&mut cmd);
+    assert!(got_err.contains("new rows: 0"));
+    assert!(got_err.contains("store hits: 1 (100% hit rate)"));
+    assert!(got_err.contains("store size: 5 keys (5 before)"));
+
+    // create starts over
+    let mut cmd = wrk.command("extdedup");
+    cmd.args(["--select", "id"])
+        .args(["--store", "seen.store"])
+        .args(["--store-mode", "create"])
+        .arg("day3.csv");
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn extdedup_store_readonly_missing() {
+    let wrk = Workdir::new("extdedup_store_readonly_missing");
+    wrk.create_from_string("lines.txt", "a\nb\n");
+    let mut cmd = wrk.command("extdedup");
+    cmd.args(["--store", "missing.store"])
+        .args(["--store-mode", "readonly"])
+        .arg("lines.txt");
+
+    wrk.assert_err(&mut cmd);
+}
@@ -24,3 +24,92 @@ fn extsort() {
 
     assert_eq!(dos2unix(&sorted_output), dos2unix(&expected_csv));
 }
+
+#[test]
+fn extsort_select_embedded_newlines() {
+    let wrk = Workdir::new("extsort_select_embedded_newlines");
+    wrk.create(
+        "in.csv",
+        vec![
+            svec!["id", "note"],
+            svec!["c", "line one\nline two"],
+            svec!["a", "single"],
+            svec!["b", "has, comma"],
+        ],
+    );
+
+    let mut cmd = wrk.command("extsort");
+    cmd.args(["--select", "id"]).arg("in.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["id", "note"],
+        svec!["a", "single"],
+        svec!["b", "has, comma"],
+        svec!["c", "line one\nline two"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn extsort_select_numeric_reverse() {
+    let wrk = Workdir::new("extsort_select_numeric_reverse");
+    let mut rows = vec![svec!["n", "seq"]];
+    for i in 0..50 {
+        rows.push(vec![((i * 37) % 13).to_string(), i.to_string()]);
+    }
+    wrk.create("in.csv", rows);
+
+    // the in-memory sort is the reference
+    let mut cmd = wrk.command("sort");
+    cmd.args(["--select", "n"])
+        .arg("--numeric")
+        .arg("--reverse")
+        .arg("in.csv");
+    let expected: String = wrk.stdout(&mut cmd);
+
+    let mut cmd = wrk.command("extsort");
+    cmd.args(["--select", "n"])
+        .arg("--numeric")
+        .arg("--reverse")
+        .args(["--tmp-dir", wrk.path(".").to_str().unwrap()])
+        .arg("in.csv");
+    let got: String = wrk.stdout(&mut cmd);
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn extsort_select_memory_limit_spill() {
+    let wrk = Workdir::new("extsort_select_memory_limit_spill");
+    let mut rows = vec![svec!["n", "seq"]];
+    for i in 0..50 {
+        rows.push(vec![((i * 37) % 13).to_string(), i.to_string()]);
+    }
+    wrk.create("in.csv", rows);
+
+    // the in-memory sort is the reference
+    let mut cmd = wrk.command("sort");
+    cmd.args(["--select", "n"]).arg("--numeric").arg("in.csv");
+    let expected: String = wrk.stdout(&mut cmd);
+
+    // a --memory-limit of 0 spills every record to its own sorted run,
+    // and the merge of the runs keeps the sort stable
+    let mut cmd = wrk.command("extsort");
+    cmd.args(["--select", "n"])
+        .arg("--numeric")
+        .args(["--memory-limit", "0"])
+        .args(["--tmp-dir", wrk.path(".").to_str().unwrap()])
+        .arg("in.csv");
+    let got: String = wrk.stdout(&mut cmd);
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn extsort_numeric_requires_select() {
+    let wrk = Workdir::new("extsort_numeric_requires_select");
+    wrk.create("in.csv", vec![svec!["n"], svec!["2"], svec!["1"]]);
+
+    let mut cmd = wrk.command("extsort");
+    cmd.arg("--numeric").arg("in.csv");
+    wrk.assert_err(&mut cmd);
+}
@@ -154,3 +154,36 @@ fn fill_default() {
     let expected = svec!["dat", "dat", "abc", "dat", "zap", "bar", "bongo", "dat", "dat", "dat"];
     compare_column(&got, &expected, 0, true);
 }
+
+#[test]
+fn fill_null_values() {
+    let wrk = Workdir::new("fill_null_values");
+    wrk.create(
+        "in.csv",
+        vec![
+            svec!["h1", "h2"],
+            svec!["NA", "a"],
+            svec!["abc", "b"],
+            svec!["NULL", "c"],
+            svec!["", "NA"],
+            svec!["def", "e"],
+        ],
+    );
+
+    let mut cmd = wrk.command("fill");
+    cmd.args(["--null-values", "NA,NULL"])
+        .arg("--")
+        .arg("1")
+        .arg("in.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["h1", "h2"],
+        svec!["NA", "a"],
+        svec!["abc", "b"],
+        svec!["abc", "c"],
+        svec!["abc", "NA"],
+        svec!["def", "e"],
+    ];
+    assert_eq!(got, expected);
+}
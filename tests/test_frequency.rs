@@ -100,6 +100,42 @@ fn frequency_no_nulls() {
     assert_eq!(got, expected);
 }
 
+#[test]
+fn frequency_no_nulls_null_values() {
+    let (wrk, mut cmd) = setup("frequency_no_nulls_null_values");
+    cmd.arg("--no-nulls")
+        .args(["--null-values", "(NULL)"])
+        .args(["--limit", "0"])
+        .args(["--select", "h1"]);
+
+    let mut got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    got.sort();
+    let expected = vec![
+        svec!["field", "value", "count", "percentage"],
+        svec!["h1", "a", "4", "80"],
+        svec!["h1", "b", "1", "20"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn frequency_null_values_env() {
+    let (wrk, mut cmd) = setup("frequency_null_values_env");
+    cmd.env("QSV_NULL_VALUES", "(NULL)")
+        .args(["--limit", "0"])
+        .args(["--select", "h1"]);
+
+    let mut got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    got.sort();
+    let expected = vec![
+        svec!["field", "value", "count", "percentage"],
+        svec!["h1", "(NULL)", "2", "28.57143"],
+        svec!["h1", "a", "4", "57.14286"],
+        svec!["h1", "b", "1", "14.28571"],
+    ];
+    assert_eq!(got, expected);
+}
+
 #[test]
 fn frequency_nulls() {
     let (wrk, mut cmd) = setup("frequency_nulls");
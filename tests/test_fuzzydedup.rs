@@ -0,0 +1,221 @@
+use crate::workdir::Workdir;
+
+fn setup(name: &str) -> Workdir {
+    let wrk = Workdir::new(name);
+    wrk.create(
+        "vendors.csv",
+        vec![
+            svec!["id", "vendor"],
+            svec!["1", "Acme Corp."],
+            svec!["2", "Globex"],
+            svec!["3", "ACME corp"],
+            svec!["4", "Corp, Acme"],
+            svec!["5", "Initech"],
+            svec!["6", "globex"],
+            svec!["7", ""],
+            svec!["8", "Initrode"],
+        ],
+    );
+    wrk
+}
+
+#[test]
+fn fuzzydedup_fingerprint() {
+    let wrk = setup("fuzzydedup_fingerprint");
+    let mut cmd = wrk.command("fuzzydedup");
+    cmd.args(["-s", "vendor"]).arg("vendors.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["id", "vendor", "cluster_id"],
+        svec!["1", "Acme Corp.", "1"],
+        svec!["2", "Globex", "2"],
+        svec!["3", "ACME corp", "1"],
+        svec!["4", "Corp, Acme", "1"],
+        svec!["5", "Initech", ""],
+        svec!["6", "globex", "2"],
+        svec!["7", "", ""],
+        svec!["8", "Initrode", ""],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn fuzzydedup_summary() {
+    let wrk = Workdir::new("fuzzydedup_summary");
+    wrk.create(
+        "names.csv",
+        vec![
+            svec!["name"],
+            svec!["José Pérez"],
+            svec!["Jose Perez"],
+            svec!["jose perez"],
+            svec!["Jose Perez"],
+            svec!["Ann Lee"],
+        ],
+    );
+    let mut cmd = wrk.command("fuzzydedup");
+    cmd.args(["--summary", "summary.csv"])
+        .args(["--cluster-column", "group"])
+        .arg("names.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["name", "group"],
+        svec!["José Pérez", "1"],
+        svec!["Jose Perez", "1"],
+        svec!["jose perez", "1"],
+        svec!["Jose Perez", "1"],
+        svec!["Ann Lee", ""],
+    ];
+    assert_eq!(got, expected);
+
+    let got_summary: String = wrk.from_str(&wrk.path("summary.csv"));
+    let expected_summary = "cluster_id,rows,distinct_values,suggested,values\n1,4,3,Jose \
+                            Perez,Jose Perez (2) | José Pérez (1) | jose perez (1)\n";
+    assert_eq!(got_summary, expected_summary);
+}
+
+#[test]
+fn fuzzydedup_ngram() {
+    let wrk = Workdir::new("fuzzydedup_ngram");
+    wrk.create(
+        "names.csv",
+        vec![
+            svec!["name"],
+            svec!["McDonald"],
+            svec!["Mc Donald"],
+            svec!["Macdonald"],
+        ],
+    );
+    let mut cmd = wrk.command("fuzzydedup");
+    cmd.args(["--method", "ngram"]).arg("names.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["name", "cluster_id"],
+        svec!["McDonald", "1"],
+        svec!["Mc Donald", "1"],
+        svec!["Macdonald", ""],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn fuzzydedup_phonetic() {
+    let wrk = Workdir::new("fuzzydedup_phonetic");
+    wrk.create(
+        "names.csv",
+        vec![
+            svec!["first", "last"],
+            svec!["John", "Smith"],
+            svec!["Jon", "Smyth"],
+            svec!["Jane", "Doe"],
+            svec!["Robert", "Rupert"],
+            svec!["Rupert", "Robert"],
+        ],
+    );
+    let mut cmd = wrk.command("fuzzydedup");
+    cmd.args(["--method", "phonetic"])
+        .args(["-s", "first,last"])
+        .arg("names.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["first", "last", "cluster_id"],
+        svec!["John", "Smith", "1"],
+        svec!["Jon", "Smyth", "1"],
+        svec!["Jane", "Doe", ""],
+        svec!["Robert", "Rupert", "2"],
+        svec!["Rupert", "Robert", "2"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn fuzzydedup_levenshtein() {
+    let wrk = Workdir::new("fuzzydedup_levenshtein");
+    wrk.create(
+        "names.csv",
+        vec![
+            svec!["name"],
+            svec!["Catherine"],
+            svec!["Katherine"],
+            svec!["Kathryn"],
+            svec!["CATHERINE"],
+            svec!["Bob"],
+        ],
+    );
+    let mut cmd = wrk.command("fuzzydedup");
+    cmd.args(["--method", "levenshtein"])
+        .args(["--block-size", "4"])
+        .arg("names.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["name", "cluster_id"],
+        svec!["Catherine", "1"],
+        svec!["Katherine", "1"],
+        svec!["Kathryn", ""],
+        svec!["CATHERINE", "1"],
+        svec!["Bob", ""],
+    ];
+    assert_eq!(got, expected);
+
+    // with a larger radius, clusters are transitive: Catherine ~ Katherine ~ Kathryn
+    let mut cmd = wrk.command("fuzzydedup");
+    cmd.args(["--method", "levenshtein"])
+        .args(["--radius", "3"])
+        .args(["--block-size", "4"])
+        .arg("names.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["name", "cluster_id"],
+        svec!["Catherine", "1"],
+        svec!["Katherine", "1"],
+        svec!["Kathryn", "1"],
+        svec!["CATHERINE", "1"],
+        svec!["Bob", ""],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn fuzzydedup_jaro_winkler() {
+    let wrk = Workdir::new("fuzzydedup_jaro_winkler");
+    wrk.create(
+        "names.csv",
+        vec![
+            svec!["name"],
+            svec!["Martha"],
+            svec!["Marhta"],
+            svec!["Dwayne"],
+            svec!["Duane"],
+        ],
+    );
+    let mut cmd = wrk.command("fuzzydedup");
+    cmd.args(["--method", "jaro-winkler"])
+        .args(["--threshold", "0.95"])
+        .args(["--block-size", "2"])
+        .arg("names.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["name", "cluster_id"],
+        svec!["Martha", "1"],
+        svec!["Marhta", "1"],
+        svec!["Dwayne", ""],
+        svec!["Duane", ""],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn fuzzydedup_invalid_method() {
+    let wrk = setup("fuzzydedup_invalid_method");
+    let mut cmd = wrk.command("fuzzydedup");
+    cmd.args(["--method", "soundex"]).arg("vendors.csv");
+
+    wrk.assert_err(&mut cmd);
+}
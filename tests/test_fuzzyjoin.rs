@@ -0,0 +1,214 @@
+use crate::workdir::Workdir;
+
+fn setup(name: &str) -> Workdir {
+    let wrk = Workdir::new(name);
+    wrk.create(
+        "vendors.csv",
+        vec![
+            svec!["name", "zip"],
+            svec!["Acme Corporation", "10001"],
+            svec!["Globex Inc", "10001"],
+            svec!["Initech", "94016"],
+            svec!["Umbrella Corp", "60601"],
+        ],
+    );
+    wrk.create(
+        "master.csv",
+        vec![
+            svec!["id", "company", "zip"],
+            svec!["1", "ACME Corporation", "10001"],
+            svec!["2", "Acme Corp", "94016"],
+            svec!["3", "Globex Incorporated", "10001"],
+            svec!["4", "Initech LLC", "94016"],
+            svec!["5", "Initrode", "94016"],
+        ],
+    );
+    wrk
+}
+
+#[test]
+fn fuzzyjoin_jaro_winkler() {
+    let wrk = setup("fuzzyjoin_jaro_winkler");
+    let mut cmd = wrk.command("fuzzyjoin");
+    cmd.args(["--threshold", "0.85"]).arg("-i").args([
+        "name",
+        "vendors.csv",
+        "company",
+        "master.csv",
+    ]);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["name", "zip", "id", "company", "zip", "fuzzy_score"],
+        svec![
+            "Acme Corporation",
+            "10001",
+            "1",
+            "ACME Corporation",
+            "10001",
+            "1"
+        ],
+        svec![
+            "Globex Inc",
+            "10001",
+            "3",
+            "Globex Incorporated",
+            "10001",
+            "0.9053"
+        ],
+        svec!["Initech", "94016", "4", "Initech LLC", "94016", "0.9273"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn fuzzyjoin_block_left() {
+    let wrk = setup("fuzzyjoin_block_left");
+    let mut cmd = wrk.command("fuzzyjoin");
+    cmd.args(["--block", "zip"])
+        .args(["--threshold", "0.7"])
+        .arg("--left")
+        .args(["name", "vendors.csv", "company", "master.csv"]);
+
+    // "Acme Corp" in 94016 is never compared against the 10001 vendors
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["name", "zip", "id", "company", "zip", "fuzzy_score"],
+        svec![
+            "Acme Corporation",
+            "10001",
+            "1",
+            "ACME Corporation",
+            "10001",
+            "0.8644"
+        ],
+        svec![
+            "Globex Inc",
+            "10001",
+            "3",
+            "Globex Incorporated",
+            "10001",
+            "0.9053"
+        ],
+        svec!["Initech", "94016", "4", "Initech LLC", "94016", "0.9273"],
+        svec!["Umbrella Corp", "60601", "", "", "", ""],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn fuzzyjoin_top_n() {
+    let wrk = setup("fuzzyjoin_top_n");
+    let mut cmd = wrk.command("fuzzyjoin");
+    cmd.args(["--block", "zip"])
+        .args(["--threshold", "0.5"])
+        .args(["--top", "2"])
+        .args(["--score-column", "score"])
+        .args(["--round", "2"])
+        .args(["name", "vendors.csv", "company", "master.csv"]);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["name", "zip", "id", "company", "zip", "score"],
+        svec![
+            "Acme Corporation",
+            "10001",
+            "1",
+            "ACME Corporation",
+            "10001",
+            "0.86"
+        ],
+        svec![
+            "Acme Corporation",
+            "10001",
+            "3",
+            "Globex Incorporated",
+            "10001",
+            "0.65"
+        ],
+        svec![
+            "Globex Inc",
+            "10001",
+            "3",
+            "Globex Incorporated",
+            "10001",
+            "0.91"
+        ],
+        svec!["Initech", "94016", "4", "Initech LLC", "94016", "0.93"],
+        svec!["Initech", "94016", "5", "Initrode", "94016", "0.87"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn fuzzyjoin_tokenset() {
+    let wrk = Workdir::new("fuzzyjoin_tokenset");
+    wrk.create(
+        "a.csv",
+        vec![
+            svec!["addr"],
+            svec!["12 Main Street North"],
+            svec!["7 Elm Ave"],
+        ],
+    );
+    wrk.create(
+        "b.csv",
+        vec![
+            svec!["address"],
+            svec!["North Main Street 12"],
+            svec!["Elm Ave 9"],
+        ],
+    );
+    let mut cmd = wrk.command("fuzzyjoin");
+    cmd.args(["--metric", "tokenset"])
+        .args(["--threshold", "0.9"])
+        .args(["addr", "a.csv", "address", "b.csv"]);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["addr", "address", "fuzzy_score"],
+        svec!["12 Main Street North", "North Main Street 12", "1"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn fuzzyjoin_damerau_levenshtein() {
+    let wrk = Workdir::new("fuzzyjoin_damerau_levenshtein");
+    wrk.create(
+        "a.csv",
+        vec![svec!["city"], svec!["Chicgao"], svec!["Boston"]],
+    );
+    wrk.create(
+        "b.csv",
+        vec![svec!["city"], svec!["Chicago"], svec!["Austin"]],
+    );
+    let mut cmd = wrk.command("fuzzyjoin");
+    cmd.args(["--metric", "dl"])
+        .args(["city", "a.csv", "city", "b.csv"]);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["city", "city", "fuzzy_score"],
+        svec!["Chicgao", "Chicago", "0.8571"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn fuzzyjoin_invalid_args() {
+    let wrk = setup("fuzzyjoin_invalid_args");
+    let mut cmd = wrk.command("fuzzyjoin");
+    cmd.args(["--metric", "soundex"])
+        .args(["name", "vendors.csv", "company", "master.csv"]);
+    wrk.assert_err(&mut cmd);
+
+    let mut cmd = wrk.command("fuzzyjoin");
+    cmd.args(["--threshold", "1.5"])
+        .args(["name", "vendors.csv", "company", "master.csv"]);
+    wrk.assert_err(&mut cmd);
+
+    let mut cmd = wrk.command("fuzzyjoin");
+    cmd.args(["name,zip", "vendors.csv", "company", "master.csv"]);
+    wrk.assert_err(&mut cmd);
+}
@@ -301,3 +301,244 @@ fn join_cross_no_headers() {
     ];
     assert_eq!(got, expected);
 }
+
+#[test]
+fn join_normalize_numeric() {
+    let wrk = Workdir::new("join_normalize_numeric");
+    wrk.create(
+        "orders.csv",
+        vec![
+            svec!["customer_id", "amount"],
+            svec!["007", "10"],
+            svec!["1.0", "20"],
+            svec!["1e3", "30"],
+            svec!["abc", "40"],
+        ],
+    );
+    wrk.create(
+        "customers.csv",
+        vec![
+            svec!["id", "name"],
+            svec!["7", "Bond"],
+            svec!["1", "Smith"],
+            svec!["1000", "Jones"],
+            svec!["abc", "Doe"],
+        ],
+    );
+
+    let mut cmd = wrk.command("join");
+    cmd.args(["--normalize", "numeric"])
+        .args(["customer_id", "orders.csv", "id", "customers.csv"]);
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["customer_id", "amount", "id", "name"],
+        svec!["007", "10", "7", "Bond"],
+        svec!["1.0", "20", "1", "Smith"],
+        svec!["1e3", "30", "1000", "Jones"],
+        svec!["abc", "40", "abc", "Doe"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn join_normalize_date() {
+    let wrk = Workdir::new("join_normalize_date");
+    wrk.create(
+        "events.csv",
+        vec![
+            svec!["date", "event"],
+            svec!["2024-01-31", "launch"],
+            svec!["2024-02-01 00:00:00", "party"],
+        ],
+    );
+    wrk.create(
+        "weather.csv",
+        vec![
+            svec!["day", "weather"],
+            svec!["01/31/2024", "sunny"],
+            svec!["2024-02-01", "rainy"],
+        ],
+    );
+
+    let mut cmd = wrk.command("join");
+    cmd.args(["--normalize", "date"])
+        .args(["date", "events.csv", "day", "weather.csv"]);
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["date", "event", "day", "weather"],
+        svec!["2024-01-31", "launch", "01/31/2024", "sunny"],
+        svec!["2024-02-01 00:00:00", "party", "2024-02-01", "rainy"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn join_normalize_nfkc_squeeze() {
+    let wrk = Workdir::new("join_normalize_nfkc_squeeze");
+    wrk.create(
+        "a.csv",
+        vec![svec!["name", "x"], svec!["ＡＣＭＥ  Corp", "1"]],
+    );
+    wrk.create("b.csv", vec![svec!["name", "y"], svec!["acme corp", "2"]]);
+
+    let mut cmd = wrk.command("join");
+    cmd.args(["--normalize", "nfkc,squeeze,lower"])
+        .args(["name", "a.csv", "name", "b.csv"]);
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["name", "x", "name", "y"],
+        svec!["ＡＣＭＥ  Corp", "1", "acme corp", "2"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn join_normalize_per_key() {
+    let wrk = Workdir::new("join_normalize_per_key");
+    wrk.create(
+        "a.csv",
+        vec![
+            svec!["id", "city", "x"],
+            svec!["01", "New  York", "1"],
+            svec!["02", "Boston", "2"],
+        ],
+    );
+    wrk.create(
+        "b.csv",
+        vec![
+            svec!["id", "city", "y"],
+            svec!["1", "new york", "3"],
+            svec!["2", "boston", "4"],
+        ],
+    );
+
+    // only the second key column is lowercased, and its whitespace squeezed
+    let mut cmd = wrk.command("join");
+    cmd.args(["--normalize", "numeric;squeeze,lower"])
+        .args(["id,city", "a.csv", "id,city", "b.csv"]);
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["id", "city", "x", "id", "city", "y"],
+        svec!["01", "New  York", "1", "1", "new york", "3"],
+        svec!["02", "Boston", "2", "2", "boston", "4"],
+    ];
+    assert_eq!(got, expected);
+
+    // the number of transform lists must match the number of key columns
+    let mut cmd = wrk.command("join");
+    cmd.args(["--normalize", "numeric;squeeze;lower"])
+        .args(["id,city", "a.csv", "id,city", "b.csv"]);
+    wrk.assert_err(&mut cmd);
+}
+
+#[test]
+fn join_memory_limit_disk_backed() {
+    let wrk = Workdir::new("join_memory_limit_disk_backed");
+    let mut left = vec![svec!["id", "name"]];
+    let mut right = vec![svec!["key", "value"]];
+    for i in 0..200 {
+        left.push(vec![(i % 70).to_string(), format!("left{i}")]);
+        if i % 3 == 0 {
+            right.push(vec![(i % 90).to_string(), format!("right{i}")]);
+        }
+    }
+    // rows with an empty join key
+    left.push(svec!["", "left_null"]);
+    right.push(svec!["", "right_null"]);
+    wrk.create("left.csv", left);
+    wrk.create("right.csv", right);
+
+    // a --memory-limit of 0 always spills to disk, so the disk-backed join
+    // should give exactly the same output as the in-memory join
+    for join_flags in [
+        vec![],
+        vec!["--left"],
+        vec!["--left-anti"],
+        vec!["--left-semi"],
+        vec!["--right"],
+        vec!["--full"],
+        vec!["--full", "--nulls"],
+    ] {
+        let mut cmd = wrk.command("join");
+        cmd.args(&join_flags)
+            .args(["id", "left.csv", "key", "right.csv"]);
+        let expected: String = wrk.stdout(&mut cmd);
+
+        let mut cmd = wrk.command("join");
+        cmd.args(&join_flags)
+            .args(["--memory-limit", "0"])
+            .args(["--tmp-dir", wrk.path(".").to_str().unwrap()])
+            .args(["id", "left.csv", "key", "right.csv"]);
+        let got: String = wrk.stdout(&mut cmd);
+        assert!(!got.is_empty());
+        assert_eq!(got, expected, "join flags: {join_flags:?}");
+    }
+
+    // the temporary partition files are cleaned up
+    let leftovers = std::fs::read_dir(wrk.path("."))
+        .unwrap()
+        .filter(|entry| {
+            entry
+                .as_ref()
+                .unwrap()
+                .file_name()
+                .to_string_lossy()
+                .starts_with("qsv-join-")
+        })
+        .count();
+    assert_eq!(leftovers, 0);
+}
+
+#[test]
+fn join_null_values() {
+    let wrk = Workdir::new("join_null_values");
+    wrk.create(
+        "orders.csv",
+        vec![
+            svec!["customer_id", "amount"],
+            svec!["1", "10"],
+            svec!["NA", "20"],
+            svec!["", "30"],
+        ],
+    );
+    wrk.create(
+        "customers.csv",
+        vec![
+            svec!["id", "name"],
+            svec!["1", "Smith"],
+            svec!["NULL", "Unknown"],
+        ],
+    );
+
+    // NULL values are ignored like empty fields...
+    let mut cmd = wrk.command("join");
+    cmd.args(["--null-values", "NA,NULL"]).args([
+        "customer_id",
+        "orders.csv",
+        "id",
+        "customers.csv",
+    ]);
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["customer_id", "amount", "id", "name"],
+        svec!["1", "10", "1", "Smith"],
+    ];
+    assert_eq!(got, expected);
+
+    // ...and joined as empty fields with --nulls
+    let mut cmd = wrk.command("join");
+    cmd.args(["--null-values", "NA,NULL"]).arg("--nulls").args([
+        "customer_id",
+        "orders.csv",
+        "id",
+        "customers.csv",
+    ]);
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["customer_id", "amount", "id", "name"],
+        svec!["1", "10", "1", "Smith"],
+        svec!["NA", "20", "NULL", "Unknown"],
+        svec!["", "30", "NULL", "Unknown"],
+    ];
+    assert_eq!(got, expected);
+}
@@ -0,0 +1,187 @@
+use crate::workdir::Workdir;
+
+fn setup(name: &str) -> Workdir {
+    let wrk = Workdir::new(name);
+    wrk.create(
+        "people.csv",
+        vec![
+            svec![
+                "card_number",
+                "email",
+                "birth_date",
+                "salary",
+                "age",
+                "notes"
+            ],
+            svec![
+                "4111111111111111",
+                "jane.doe@example.com",
+                "1984-07-23",
+                "52875.50",
+                "37",
+                "allergic to nuts"
+            ],
+            svec!["123", "bob@example.org", "2001-12-01", "49999", "40", ""],
+            svec!["", "not an email", "not a date", "n/a", "9", "vip"],
+        ],
+    );
+    wrk
+}
+
+#[test]
+fn mask_rules() {
+    let wrk = setup("mask_rules");
+    wrk.create(
+        "rules.csv",
+        vec![
+            svec!["column", "rule", "arg"],
+            svec!["card_number", "keep-last", "4"],
+            svec!["email", "email", ""],
+            svec!["birth_date", "date", "year"],
+            svec!["salary", "round", "1000"],
+            svec!["age", "bucket", "10"],
+            svec!["/^notes/", "redact", ""],
+        ],
+    );
+    let mut cmd = wrk.command("mask");
+    cmd.arg("rules.csv").arg("people.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec![
+            "card_number",
+            "email",
+            "birth_date",
+            "salary",
+            "age",
+            "notes"
+        ],
+        svec![
+            "************1111",
+            "j*******@example.com",
+            "1984",
+            "53000",
+            "30-40",
+            "REDACTED"
+        ],
+        svec!["***", "b**@example.org", "2001", "50000", "40-50", ""],
+        svec!["", "REDACTED", "REDACTED", "REDACTED", "0-10", "REDACTED"],
+    ];
+    assert_eq!(got, expected);
+
+    let got_stderr = wrk.output_stderr(&mut cmd);
+    assert_eq!(
+        got_stderr,
+        "3 value/s that could not be masked by their rule were redacted.\n"
+    );
+}
+
+#[test]
+fn mask_digits_hash_and_month() {
+    let wrk = Workdir::new("mask_digits_hash_and_month");
+    wrk.create(
+        "data.csv",
+        vec![
+            svec!["phone", "id", "visit", "weight"],
+            svec!["+1 (555) 123-4567", "abc", "2024-03-15", "72.46"],
+        ],
+    );
+    wrk.create(
+        "rules.csv",
+        vec![
+            svec!["column", "rule", "arg"],
+            svec!["phone", "digits", "X"],
+            svec!["id", "hash", "12"],
+            svec!["visit", "date", ""],
+            svec!["weight", "round", "0.5"],
+        ],
+    );
+    let mut cmd = wrk.command("mask");
+    cmd.arg("rules.csv").arg("data.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["phone", "id", "visit", "weight"],
+        svec!["+X (XXX) XXX-XXXX", "ba7816bf8f01", "2024-03", "72.5"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn mask_multiple_columns_mask_char() {
+    let wrk = setup("mask_multiple_columns_mask_char");
+    wrk.create(
+        "rules.csv",
+        vec![
+            svec!["column", "rule"],
+            svec!["card_number,email", "keep-last"],
+        ],
+    );
+    let mut cmd = wrk.command("mask");
+    cmd.args(["--mask-char", "x"])
+        .arg("rules.csv")
+        .arg("people.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    assert_eq!(got[1][0], "xxxxxxxxxxxx1111");
+    assert_eq!(got[1][1], "xxxxxxxxxxxxxxxx.com");
+    assert_eq!(got[2][1], "xxxxxxxxxxx.org");
+    assert_eq!(got[3][1], "xxxxxxxxmail");
+    assert_eq!(got[1][2], "1984-07-23");
+}
+
+#[test]
+fn mask_no_headers() {
+    let wrk = Workdir::new("mask_no_headers");
+    wrk.create(
+        "data.csv",
+        vec![svec!["a", "jane@example.com"], svec!["b", "bo@example.com"]],
+    );
+    wrk.create(
+        "rules.csv",
+        vec![svec!["column", "rule", "arg"], svec!["2", "email", ""]],
+    );
+    let mut cmd = wrk.command("mask");
+    cmd.arg("--no-headers").arg("rules.csv").arg("data.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![svec!["a", "j***@example.com"], svec!["b", "b*@example.com"]];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn mask_column_in_two_rules() {
+    let wrk = setup("mask_column_in_two_rules");
+    wrk.create(
+        "rules.csv",
+        vec![
+            svec!["column", "rule", "arg"],
+            svec!["email", "email", ""],
+            svec!["1-2", "redact", ""],
+        ],
+    );
+    let mut cmd = wrk.command("mask");
+    cmd.arg("rules.csv").arg("people.csv");
+
+    wrk.assert_err(&mut cmd);
+    let got_stderr = wrk.output_stderr(&mut cmd);
+    assert!(got_stderr.contains("Column \"email\" is selected by more than one rule"));
+}
+
+#[test]
+fn mask_invalid_rule() {
+    let wrk = setup("mask_invalid_rule");
+    wrk.create(
+        "rules.csv",
+        vec![
+            svec!["column", "rule", "arg"],
+            svec!["email", "scramble", ""],
+        ],
+    );
+    let mut cmd = wrk.command("mask");
+    cmd.arg("rules.csv").arg("people.csv");
+
+    wrk.assert_err(&mut cmd);
+    let got_stderr = wrk.output_stderr(&mut cmd);
+    assert!(got_stderr.contains("Invalid rule \"scramble\" on line 2 of the rules file"));
+}
@@ -0,0 +1,140 @@
+use crate::workdir::Workdir;
+
+fn setup(name: &str) -> Workdir {
+    let wrk = Workdir::new(name);
+    wrk.create(
+        "old.csv",
+        vec![
+            svec!["id", "name", "amount"],
+            svec!["1", "Ann", "10"],
+            svec!["2", "Bob", "20"],
+            svec!["3", "Cy", "30"],
+            svec!["4", "Dan", "7"],
+        ],
+    );
+    wrk.create(
+        "new.csv",
+        vec![
+            svec!["id", "name", "amount"],
+            svec!["1", "Ann", "10"],
+            svec!["2", "Robert", "25"],
+            svec!["3", "Cy", "31"],
+            svec!["5", "Eve", "50"],
+        ],
+    );
+    wrk
+}
+
+#[test]
+fn patch_roundtrip() {
+    let wrk = setup("patch_roundtrip");
+    let mut cmd = wrk.command("diff");
+    cmd.args(["old.csv", "new.csv", "--output", "changes.csv"]);
+    wrk.assert_success(&mut cmd);
+
+    let mut cmd = wrk.command("patch");
+    cmd.arg("old.csv").arg("changes.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["id", "name", "amount"],
+        svec!["1", "Ann", "10"],
+        svec!["2", "Robert", "25"],
+        svec!["3", "Cy", "31"],
+        svec!["5", "Eve", "50"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn patch_composite_key() {
+    let wrk = Workdir::new("patch_composite_key");
+    wrk.create(
+        "base.csv",
+        vec![
+            svec!["country", "city", "population"],
+            svec!["US", "Paris", "25171"],
+            svec!["FR", "Paris", "2102650"],
+        ],
+    );
+    wrk.create(
+        "changes.csv",
+        vec![
+            svec!["diffresult", "country", "city", "population"],
+            svec!["-", "FR", "Paris", "2102650"],
+            svec!["+", "FR", "Paris", "2113705"],
+            svec!["+", "CA", "Paris", "12310"],
+        ],
+    );
+    let mut cmd = wrk.command("patch");
+    cmd.args(["--key", "0,1"])
+        .arg("base.csv")
+        .arg("changes.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["country", "city", "population"],
+        svec!["US", "Paris", "25171"],
+        svec!["FR", "Paris", "2113705"],
+        svec!["CA", "Paris", "12310"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn patch_conflicts() {
+    let wrk = setup("patch_conflicts");
+    let mut cmd = wrk.command("diff");
+    cmd.args(["old.csv", "new.csv", "--output", "changes.csv"]);
+    wrk.assert_success(&mut cmd);
+
+    // the base has drifted from old.csv
+    wrk.create(
+        "base.csv",
+        vec![
+            svec!["id", "name", "amount"],
+            svec!["1", "Ann", "10"],
+            svec!["2", "Bob", "21"],
+            svec!["3", "Cy", "30"],
+            svec!["5", "Eve", "50"],
+        ],
+    );
+    let mut cmd = wrk.command("patch");
+    cmd.args(["--rejects", "rejects.csv"])
+        .arg("base.csv")
+        .arg("changes.csv");
+
+    wrk.assert_err(&mut cmd);
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["id", "name", "amount"],
+        svec!["1", "Ann", "10"],
+        svec!["2", "Bob", "21"],
+        svec!["3", "Cy", "31"],
+        svec!["5", "Eve", "50"],
+    ];
+    assert_eq!(got, expected);
+
+    let got_stderr = wrk.output_stderr(&mut cmd);
+    assert_eq!(
+        got_stderr,
+        "Conflict for the key 2: the base row does not match the \"-\" row of the diff (columns: \
+         amount).\nConflict for the key 4: the row to change is not in the base.\nConflict for \
+         the key 5: the row to add is already in the base.\n3 conflict/s - the conflicting \
+         changes were not applied.\n"
+    );
+
+    let got_rejects: String = wrk.from_str(&wrk.path("rejects.csv"));
+    let expected_rejects =
+        "diffresult,id,name,amount\n-,2,Bob,20\n+,2,Robert,25\n-,4,Dan,7\n+,5,Eve,50\n";
+    assert_eq!(got_rejects, expected_rejects);
+}
+
+#[test]
+fn patch_not_a_diff() {
+    let wrk = setup("patch_not_a_diff");
+    let mut cmd = wrk.command("patch");
+    cmd.arg("old.csv").arg("new.csv");
+
+    wrk.assert_err(&mut cmd);
+}
@@ -0,0 +1,180 @@
+use crate::workdir::Workdir;
+
+fn setup(name: &str) -> Workdir {
+    let wrk = Workdir::new(name);
+    wrk.create(
+        "contacts.csv",
+        vec![
+            svec![
+                "name",
+                "email",
+                "phone",
+                "ssn",
+                "card",
+                "iban",
+                "ip",
+                "birth_date",
+                "notes"
+            ],
+            svec![
+                "Mary Smith",
+                "mary@example.com",
+                "+1 555-123-4567",
+                "123-45-6789",
+                "4111 1111 1111 1111",
+                "GB82 WEST 1234 5698 7654 32",
+                "192.168.1.10",
+                "1984-07-23",
+                "call back"
+            ],
+            svec![
+                "John Doe",
+                "john.doe@example.org",
+                "(555) 987-6543",
+                "234-56-7890",
+                "5500000000000004",
+                "DE89370400440532013000",
+                "2001:db8::1",
+                "1990-01-15",
+                ""
+            ],
+            svec![
+                "Acme Corp",
+                "not an email",
+                "12345",
+                "000-12-3456",
+                "4111111111111112",
+                "GB00WEST12345698765432",
+                "999.1.1.1",
+                "not a date",
+                "Mary called"
+            ],
+        ],
+    );
+    wrk
+}
+
+#[test]
+fn piiscan_report() {
+    let wrk = setup("piiscan_report");
+    let mut cmd = wrk.command("piiscan");
+    cmd.arg("contacts.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec![
+            "column",
+            "pii_type",
+            "scanned",
+            "hits",
+            "hit_rate",
+            "header_hint",
+            "confidence"
+        ],
+        svec!["name", "name", "3", "2", "0.6667", "true", "0.7"],
+        svec!["email", "email", "3", "2", "0.6667", "true", "0.8333"],
+        svec!["phone", "phone", "3", "2", "0.6667", "true", "0.7667"],
+        svec!["ssn", "ssn", "3", "2", "0.6667", "true", "0.8"],
+        svec!["card", "credit_card", "3", "2", "0.6667", "true", "0.8333"],
+        svec!["iban", "iban", "3", "2", "0.6667", "true", "0.8333"],
+        svec!["ip", "ip_address", "3", "2", "0.6667", "true", "0.8"],
+        svec!["birth_date", "dob", "3", "2", "0.6667", "true", "0.6333"],
+        svec!["notes", "name", "2", "1", "0.5", "false", "0.3"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn piiscan_json_select_sample() {
+    let wrk = setup("piiscan_json_select_sample");
+    let mut cmd = wrk.command("piiscan");
+    cmd.args(["--select", "email,notes"])
+        .args(["--sample", "2"])
+        .arg("--json")
+        .arg("contacts.csv");
+
+    let got: serde_json::Value = serde_json::from_str(&wrk.stdout::<String>(&mut cmd)).unwrap();
+    let expected = serde_json::json!([
+        {
+            "column": "email",
+            "pii_type": "email",
+            "scanned": 2,
+            "hits": 2,
+            "hit_rate": 1.0,
+            "header_hint": true,
+            "confidence": 1.0
+        }
+    ]);
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn piiscan_no_headers() {
+    let wrk = Workdir::new("piiscan_no_headers");
+    wrk.create(
+        "data.csv",
+        vec![svec!["1", "10.0.0.1"], svec!["2", "10.0.0.2"]],
+    );
+    let mut cmd = wrk.command("piiscan");
+    cmd.arg("--no-headers").arg("data.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec![
+            "column",
+            "pii_type",
+            "scanned",
+            "hits",
+            "hit_rate",
+            "header_hint",
+            "confidence"
+        ],
+        svec!["2", "ip_address", "2", "2", "1.0", "false", "0.9"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn piiscan_fail_on() {
+    let wrk = setup("piiscan_fail_on");
+    let mut cmd = wrk.command("piiscan");
+    cmd.args(["--fail-on", "0.8"]).arg("contacts.csv");
+
+    wrk.assert_err(&mut cmd);
+    let got_stderr = wrk.output_stderr(&mut cmd);
+    assert_eq!(
+        got_stderr,
+        "PII detected at or above the --fail-on threshold of 0.8: email (email), ssn (ssn), card \
+         (credit_card), iban (iban), ip (ip_address)\n"
+    );
+
+    // nothing is at or above the threshold
+    let mut cmd = wrk.command("piiscan");
+    cmd.args(["--fail-on", "0.9"]).arg("contacts.csv");
+
+    wrk.assert_success(&mut cmd);
+}
+
+#[test]
+fn piiscan_no_pii() {
+    let wrk = Workdir::new("piiscan_no_pii");
+    wrk.create(
+        "data.csv",
+        vec![svec!["id", "amount"], svec!["1", "10.5"], svec!["2", "300"]],
+    );
+    let mut cmd = wrk.command("piiscan");
+    cmd.args(["--fail-on", "0"]).arg("data.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![svec![
+        "column",
+        "pii_type",
+        "scanned",
+        "hits",
+        "hit_rate",
+        "header_hint",
+        "confidence"
+    ]];
+    assert_eq!(got, expected);
+    wrk.assert_success(&mut cmd);
+}
@@ -202,3 +202,282 @@ fn pseudo_overflow() {
          last valid counter is 18446744073709551615.\n"
     );
 }
+
+const TEST_KEY: &str = "correct horse battery staple";
+
+fn people() -> Vec<Vec<String>> {
+    vec![
+        svec!["buyer", "seller", "customer_id"],
+        svec!["Mary", "John", "1234567"],
+        svec!["John", "Sue", "0012345"],
+        svec!["Sue", "", "9999999999"],
+    ]
+}
+
+fn setup_people(name: &str) -> Workdir {
+    let wrk = Workdir::new(name);
+    wrk.create("data.csv", people());
+    wrk
+}
+
+#[test]
+fn pseudo_multiple_columns() {
+    let wrk = setup_people("pseudo_multiple_columns");
+    let mut cmd = wrk.command("pseudo");
+    cmd.arg("buyer,seller").arg("data.csv");
+
+    // the same value gets the same identifier in all the selected columns
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["buyer", "seller", "customer_id"],
+        svec!["0", "1", "1234567"],
+        svec!["1", "2", "0012345"],
+        svec!["2", "3", "9999999999"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn pseudo_hmac() {
+    let wrk = setup_people("pseudo_hmac");
+    let mut cmd = wrk.command("pseudo");
+    cmd.env("QSV_PSEUDO_KEY", TEST_KEY)
+        .arg("buyer,seller")
+        .args(["--method", "hmac"])
+        .args(["--length", "16"])
+        .arg("data.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["buyer", "seller", "customer_id"],
+        svec!["587ed4890237f70c", "3a4f202f4b1484df", "1234567"],
+        svec!["3a4f202f4b1484df", "2ec5ee7ab807f02d", "0012345"],
+        svec!["2ec5ee7ab807f02d", "", "9999999999"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn pseudo_hmac_formatstr_custom_key_env() {
+    let wrk = setup_people("pseudo_hmac_formatstr_custom_key_env");
+    let mut cmd = wrk.command("pseudo");
+    cmd.env("MY_SECRET", TEST_KEY)
+        .arg("buyer")
+        .args(["--method", "hmac"])
+        .args(["--key-env", "MY_SECRET"])
+        .args(["--length", "8"])
+        .args(["--formatstr", "P-{}"])
+        .arg("data.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["buyer", "seller", "customer_id"],
+        svec!["P-587ed489", "John", "1234567"],
+        svec!["P-3a4f202f", "Sue", "0012345"],
+        svec!["P-2ec5ee7a", "", "9999999999"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn pseudo_hmac_key_separation() {
+    let wrk = Workdir::new("pseudo_hmac_key_separation");
+    wrk.create(
+        "data.csv",
+        vec![
+            svec!["purpose"],
+            svec!["qsv-pseudo-fpe"],
+            svec!["qsv-pseudo-vault"],
+        ],
+    );
+    let mut cmd = wrk.command("pseudo");
+    cmd.env("QSV_PSEUDO_KEY", TEST_KEY)
+        .arg("purpose")
+        .args(["--method", "hmac"])
+        .arg("data.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    // the fpe & vault keys derived from TEST_KEY, i.e. HMAC-SHA256(TEST_KEY, purpose)
+    let derived_keys = [
+        "ef066497a9cf9dbbc21688a04fd67d022ce9f7232b8864fa38ea4ff440d5d31e",
+        "481500826c36454f8ee86d2cb7de43f41c321fef5b630b7759ad75da57e2acd2",
+    ];
+    for (row, derived_key) in got[1..].iter().zip(derived_keys) {
+        assert_eq!(row[0].len(), 64);
+        assert_ne!(row[0], derived_key);
+    }
+    let expected = vec![
+        svec!["purpose"],
+        svec!["258df7b7a5586993b24a462a4a724fa632c9b83152bb12b8e88b4b77a72b1b55"],
+        svec!["c409f16ff0f4b6ecfe9f3426264076d975e1d722195f2ea7638ced57828b1d95"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn pseudo_hmac_missing_key() {
+    let wrk = setup_people("pseudo_hmac_missing_key");
+    let mut cmd = wrk.command("pseudo");
+    cmd.env_remove("QSV_PSEUDO_KEY")
+        .arg("buyer")
+        .args(["--method", "hmac"])
+        .arg("data.csv");
+
+    wrk.assert_err(&mut cmd);
+}
+
+#[test]
+fn pseudo_fpe_roundtrip() {
+    let wrk = setup_people("pseudo_fpe_roundtrip");
+    let mut cmd = wrk.command("pseudo");
+    cmd.env("QSV_PSEUDO_KEY", TEST_KEY)
+        .arg("customer_id")
+        .args(["--method", "fpe"])
+        .arg("data.csv");
+
+    let encrypted: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    wrk.create("encrypted.csv", encrypted.clone());
+    let originals = ["1234567", "0012345", "9999999999"];
+    for (row, original) in encrypted[1..].iter().zip(originals) {
+        // the encrypted ids are numeric, with the same number of digits
+        assert_ne!(row[2], original);
+        assert_eq!(row[2].len(), original.len());
+        assert!(row[2].bytes().all(|b| b.is_ascii_digit()));
+    }
+
+    let mut cmd = wrk.command("pseudo");
+    cmd.env("QSV_PSEUDO_KEY", TEST_KEY)
+        .arg("customer_id")
+        .args(["--method", "fpe"])
+        .arg("--reverse")
+        .arg("encrypted.csv");
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    assert_eq!(got, people());
+}
+
+#[test]
+fn pseudo_fpe_not_numeric() {
+    let wrk = setup_people("pseudo_fpe_not_numeric");
+    let mut cmd = wrk.command("pseudo");
+    cmd.env("QSV_PSEUDO_KEY", TEST_KEY)
+        .arg("buyer")
+        .args(["--method", "fpe"])
+        .arg("data.csv");
+
+    wrk.assert_err(&mut cmd);
+}
+
+#[test]
+fn pseudo_vault_reverse() {
+    let wrk = setup_people("pseudo_vault_reverse");
+    let mut cmd = wrk.command("pseudo");
+    cmd.env("QSV_PSEUDO_KEY", TEST_KEY)
+        .arg("buyer,seller")
+        .args(["--method", "hmac"])
+        .args(["--vault", "people.vault"])
+        .args(["--output", "pseudo.csv"])
+        .arg("data.csv");
+    wrk.assert_success(&mut cmd);
+
+    // the vault doesn't contain the original values in plain text
+    let vault: String = wrk.from_str(&wrk.path("people.vault"));
+    assert!(vault.starts_with("pseudonym,encrypted_value\n"));
+    assert_eq!(vault.lines().count(), 4);
+    assert!(!vault.contains("Mary"));
+
+    // running it again doesn't add the same pseudonyms to the vault
+    wrk.assert_success(&mut cmd);
+    let vault: String = wrk.from_str(&wrk.path("people.vault"));
+    assert_eq!(vault.lines().count(), 4);
+
+    let mut cmd = wrk.command("pseudo");
+    cmd.env("QSV_PSEUDO_KEY", TEST_KEY)
+        .arg("buyer,seller")
+        .arg("--reverse")
+        .args(["--vault", "people.vault"])
+        .arg("pseudo.csv");
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    assert_eq!(got, people());
+
+    // the vault can't be decrypted with another key
+    let mut cmd = wrk.command("pseudo");
+    cmd.env("QSV_PSEUDO_KEY", "wrong key")
+        .arg("buyer,seller")
+        .arg("--reverse")
+        .args(["--vault", "people.vault"])
+        .arg("pseudo.csv");
+    wrk.assert_err(&mut cmd);
+}
+
+#[test]
+fn pseudo_vault_wrong_key_append() {
+    let wrk = setup_people("pseudo_vault_wrong_key_append");
+    let mut cmd = wrk.command("pseudo");
+    cmd.env("QSV_PSEUDO_KEY", TEST_KEY)
+        .arg("buyer")
+        .args(["--method", "hmac"])
+        .args(["--vault", "people.vault"])
+        .arg("data.csv");
+    wrk.assert_success(&mut cmd);
+    let vault: String = wrk.from_str(&wrk.path("people.vault"));
+
+    // appending to a vault with another key would make it undecryptable
+    let mut cmd = wrk.command("pseudo");
+    cmd.env("QSV_PSEUDO_KEY", "wrong key")
+        .arg("seller")
+        .args(["--method", "hmac"])
+        .args(["--vault", "people.vault"])
+        .arg("data.csv");
+    wrk.assert_err(&mut cmd);
+    let got: String = wrk.from_str(&wrk.path("people.vault"));
+    assert_eq!(got, vault);
+}
+
+#[test]
+fn pseudo_vault_incremental_requires_new_vault() {
+    let wrk = setup_people("pseudo_vault_incremental_requires_new_vault");
+    let mut cmd = wrk.command("pseudo");
+    cmd.env("QSV_PSEUDO_KEY", TEST_KEY)
+        .arg("buyer")
+        .args(["--formatstr", "ID-{}"])
+        .args(["--vault", "people.vault"])
+        .arg("data.csv");
+    wrk.assert_success(&mut cmd);
+
+    // a second run would reuse ID-0 for another value
+    let mut cmd = wrk.command("pseudo");
+    cmd.env("QSV_PSEUDO_KEY", TEST_KEY)
+        .arg("seller")
+        .args(["--formatstr", "ID-{}"])
+        .args(["--vault", "people.vault"])
+        .arg("data.csv");
+    wrk.assert_err(&mut cmd);
+}
+
+#[test]
+fn pseudo_vault_pseudonym_conflict() {
+    let wrk = setup_people("pseudo_vault_pseudonym_conflict");
+    wrk.create("other.csv", vec![svec!["name"], svec!["Zed"]]);
+
+    // map the pseudonym hmac gives Mary with --length 8 to another value
+    let mut cmd = wrk.command("pseudo");
+    cmd.env("QSV_PSEUDO_KEY", TEST_KEY)
+        .arg("name")
+        .args(["--formatstr", "587ed48{}"])
+        .args(["--start", "9"])
+        .args(["--vault", "people.vault"])
+        .arg("other.csv");
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    assert_eq!(got, vec![svec!["name"], svec!["587ed489"]]);
+
+    let mut cmd = wrk.command("pseudo");
+    cmd.env("QSV_PSEUDO_KEY", TEST_KEY)
+        .arg("buyer")
+        .args(["--method", "hmac"])
+        .args(["--length", "8"])
+        .args(["--vault", "people.vault"])
+        .arg("data.csv");
+    wrk.assert_err(&mut cmd);
+    let got_err = wrk.output_stderr(&mut cmd);
+    assert!(got_err.contains("\"587ed489\" is already in the vault"));
+}
@@ -0,0 +1,223 @@
+use crate::workdir::Workdir;
+
+#[test]
+fn rangejoin_numeric() {
+    let wrk = Workdir::new("rangejoin_numeric");
+    wrk.create(
+        "scores.csv",
+        vec![
+            svec!["name", "score"],
+            svec!["alice", "92"],
+            svec!["bob", "80"],
+            svec!["carol", "79.5"],
+            svec!["dave", ""],
+            svec!["erin", "120"],
+        ],
+    );
+    wrk.create(
+        "grades.csv",
+        vec![
+            svec!["from", "to", "grade"],
+            svec!["90", "101", "A"],
+            svec!["80", "90", "B"],
+            svec!["0", "80", "C"],
+        ],
+    );
+
+    let mut cmd = wrk.command("rangejoin");
+    cmd.args(["score", "scores.csv", "from", "to", "grades.csv"]);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["name", "score", "from", "to", "grade"],
+        svec!["alice", "92", "90", "101", "A"],
+        svec!["bob", "80", "80", "90", "B"],
+        svec!["carol", "79.5", "0", "80", "C"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn rangejoin_closed_left_join() {
+    let wrk = Workdir::new("rangejoin_closed_left_join");
+    wrk.create(
+        "values.csv",
+        vec![svec!["v"], svec!["5"], svec!["10"], svec!["15"]],
+    );
+    wrk.create(
+        "ranges.csv",
+        vec![svec!["lo", "hi", "label"], svec!["5", "10", "five-ten"]],
+    );
+
+    let mut cmd = wrk.command("rangejoin");
+    cmd.args(["--closed", "right"]).arg("--left").args([
+        "v",
+        "values.csv",
+        "lo",
+        "hi",
+        "ranges.csv",
+    ]);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["v", "lo", "hi", "label"],
+        svec!["5", "", "", ""],
+        svec!["10", "5", "10", "five-ten"],
+        svec!["15", "", "", ""],
+    ];
+    assert_eq!(got, expected);
+
+    let mut cmd = wrk.command("rangejoin");
+    cmd.args(["--closed", "both"])
+        .args(["v", "values.csv", "lo", "hi", "ranges.csv"]);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["v", "lo", "hi", "label"],
+        svec!["5", "5", "10", "five-ten"],
+        svec!["10", "5", "10", "five-ten"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn rangejoin_overlapping_unbounded() {
+    let wrk = Workdir::new("rangejoin_overlapping_unbounded");
+    wrk.create("values.csv", vec![svec!["v"], svec!["-50"], svec!["7"]]);
+    wrk.create(
+        "ranges.csv",
+        vec![
+            svec!["lo", "hi", "label"],
+            svec!["", "0", "negative"],
+            svec!["5", "", "five-plus"],
+            svec!["0", "10", "single-digit"],
+            svec!["6", "8", "six-seven"],
+            svec!["9", "3", "invalid"],
+        ],
+    );
+
+    let mut cmd = wrk.command("rangejoin");
+    cmd.args(["v", "values.csv", "lo", "hi", "ranges.csv"]);
+
+    // matching ranges are joined in <input2> order
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["v", "lo", "hi", "label"],
+        svec!["-50", "", "0", "negative"],
+        svec!["7", "5", "", "five-plus"],
+        svec!["7", "0", "10", "single-digit"],
+        svec!["7", "6", "8", "six-seven"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn rangejoin_date() {
+    let wrk = Workdir::new("rangejoin_date");
+    wrk.create(
+        "invoices.csv",
+        vec![
+            svec!["invoice", "date"],
+            svec!["1", "2023-06-30"],
+            svec!["2", "2023-07-01"],
+            svec!["3", "2024-03-03"],
+            svec!["4", "2020-01-01"],
+        ],
+    );
+    wrk.create(
+        "rates.csv",
+        vec![
+            svec!["effective_from", "effective_to", "rate"],
+            svec!["2021-01-01", "2023-07-01", "0.05"],
+            svec!["2023-07-01", "", "0.06"],
+        ],
+    );
+
+    let mut cmd = wrk.command("rangejoin");
+    cmd.args(["--type", "date"]).arg("--left").args([
+        "date",
+        "invoices.csv",
+        "effective_from",
+        "effective_to",
+        "rates.csv",
+    ]);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["invoice", "date", "effective_from", "effective_to", "rate"],
+        svec!["1", "2023-06-30", "2021-01-01", "2023-07-01", "0.05"],
+        svec!["2", "2023-07-01", "2023-07-01", "", "0.06"],
+        svec!["3", "2024-03-03", "2023-07-01", "", "0.06"],
+        svec!["4", "2020-01-01", "", "", ""],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn rangejoin_ip() {
+    let wrk = Workdir::new("rangejoin_ip");
+    wrk.create(
+        "log.csv",
+        vec![
+            svec!["client"],
+            svec!["10.1.2.3"],
+            svec!["192.168.0.255"],
+            svec!["2001:db8::1"],
+            svec!["8.8.8.8"],
+            svec!["not an ip"],
+        ],
+    );
+    wrk.create(
+        "blocks.csv",
+        vec![
+            svec!["start", "end", "owner"],
+            svec!["10.0.0.0", "10.255.255.255", "private-a"],
+            svec!["192.168.0.0", "192.168.255.255", "private-c"],
+            svec!["2001:db8::", "2001:db8::ffff", "docs"],
+        ],
+    );
+
+    let mut cmd = wrk.command("rangejoin");
+    cmd.args(["--type", "ip"]).args(["--closed", "both"]).args([
+        "client",
+        "log.csv",
+        "start",
+        "end",
+        "blocks.csv",
+    ]);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["client", "start", "end", "owner"],
+        svec!["10.1.2.3", "10.0.0.0", "10.255.255.255", "private-a"],
+        svec![
+            "192.168.0.255",
+            "192.168.0.0",
+            "192.168.255.255",
+            "private-c"
+        ],
+        svec!["2001:db8::1", "2001:db8::", "2001:db8::ffff", "docs"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn rangejoin_invalid_args() {
+    let wrk = Workdir::new("rangejoin_invalid_args");
+    wrk.create("values.csv", vec![svec!["v", "w"], svec!["5", "6"]]);
+    wrk.create("ranges.csv", vec![svec!["lo", "hi"], svec!["0", "10"]]);
+
+    let mut cmd = wrk.command("rangejoin");
+    cmd.args(["--type", "text"])
+        .args(["v", "values.csv", "lo", "hi", "ranges.csv"]);
+    wrk.assert_err(&mut cmd);
+
+    let mut cmd = wrk.command("rangejoin");
+    cmd.args(["--closed", "open"])
+        .args(["v", "values.csv", "lo", "hi", "ranges.csv"]);
+    wrk.assert_err(&mut cmd);
+
+    let mut cmd = wrk.command("rangejoin");
+    cmd.args(["v,w", "values.csv", "lo", "hi", "ranges.csv"]);
+    wrk.assert_err(&mut cmd);
+}
@@ -257,3 +257,416 @@ fn generate_schema_with_defaults_to_stdout() {
 
     assert_json_eq!(expected_schema_json, output_schema_json);
 }
+
+fn create_schema_format_csv(wrk: &Workdir) {
+    wrk.create(
+        "sales.csv",
+        vec![
+            svec!["id", "amount", "name", "created_date", "big", "note"],
+            svec!["1", "3.5", "Alice", "2024-01-15", "3000000000", ""],
+            svec!["2", "120.25", "Bob", "2024-02-01", "-5", "x"],
+            svec!["3", "7.125", "Carol", "2024-03-10", "42", "yy"],
+        ],
+    );
+}
+
+#[test]
+#[file_serial]
+fn generate_schema_format_sql_ddl() {
+    let wrk = Workdir::new("generate_schema_format_sql_ddl");
+    create_schema_format_csv(&wrk);
+
+    let mut cmd = wrk.command("schema");
+    cmd.arg("sales.csv").args(["--format", "postgres"]);
+    wrk.assert_success(&mut cmd);
+
+    let got: String = wrk.from_str(&wrk.path("sales.csv.postgres.sql"));
+    let expected = r#"CREATE TABLE "sales" (
+    "id" SMALLINT NOT NULL,
+    "amount" NUMERIC(6,3) NOT NULL,
+    "name" VARCHAR(5) NOT NULL,
+    "created_date" DATE NOT NULL,
+    "big" BIGINT NOT NULL,
+    "note" VARCHAR(2)
+);
+"#;
+    assert_eq!(got, expected);
+
+    let mut cmd = wrk.command("schema");
+    cmd.arg("sales.csv").args(["--format", "duckdb"]);
+    wrk.assert_success(&mut cmd);
+
+    let got: String = wrk.from_str(&wrk.path("sales.csv.duckdb.sql"));
+    let expected = r#"CREATE TABLE "sales" (
+    "id" SMALLINT NOT NULL,
+    "amount" DECIMAL(6,3) NOT NULL,
+    "name" VARCHAR NOT NULL,
+    "created_date" DATE NOT NULL,
+    "big" BIGINT NOT NULL,
+    "note" VARCHAR
+);
+"#;
+    assert_eq!(got, expected);
+
+    let mut cmd = wrk.command("schema");
+    cmd.arg("sales.csv")
+        .args(["--format", "sqlite"])
+        .arg("--stdout");
+    let got: String = wrk.stdout(&mut cmd);
+    let expected = r#"CREATE TABLE "sales" (
+    "id" INTEGER NOT NULL,
+    "amount" REAL NOT NULL,
+    "name" TEXT NOT NULL,
+    "created_date" TEXT NOT NULL,
+    "big" INTEGER NOT NULL,
+    "note" TEXT
+);"#;
+    assert_eq!(got, expected);
+}
+
+#[test]
+#[file_serial]
+fn generate_schema_null_values() {
+    let wrk = Workdir::new("generate_schema_null_values");
+    wrk.create(
+        "codes.csv",
+        vec![
+            svec!["code", "qty"],
+            svec!["A", "1"],
+            svec!["NA", "2"],
+            svec!["B", "NA"],
+        ],
+    );
+
+    let mut cmd = wrk.command("schema");
+    cmd.arg("codes.csv")
+        .args(["--format", "frictionless"])
+        .args(["--null-values", "NA"])
+        .args(["--enum-threshold", "5"]);
+    wrk.assert_success(&mut cmd);
+
+    let got: String = wrk.from_str(&wrk.path("codes.csv.tableschema.json"));
+    let got_json: Value = serde_json::from_str(&got).unwrap();
+    assert_eq!(got_json["missingValues"], serde_json::json!(["", "NA"]));
+    assert_eq!(
+        got_json["fields"][0]["constraints"]["required"],
+        Value::Null
+    );
+    assert_eq!(
+        got_json["fields"][0]["constraints"]["enum"],
+        serde_json::json!(["A", "B"])
+    );
+    assert_eq!(got_json["fields"][1]["type"], "integer");
+}
+
+#[test]
+#[file_serial]
+fn generate_schema_null_values_and_validate() {
+    let wrk = Workdir::new("generate_schema_null_values_and_validate");
+    wrk.create(
+        "codes.csv",
+        vec![
+            svec!["code", "qty"],
+            svec!["A", "1"],
+            svec!["NA", "2"],
+            svec!["B", "NA"],
+        ],
+    );
+
+    let mut cmd = wrk.command("schema");
+    cmd.arg("codes.csv").args(["--null-values", "NA"]);
+    wrk.assert_success(&mut cmd);
+
+    let got: String = wrk.from_str(&wrk.path("codes.csv.schema.json"));
+    let got_json: Value = serde_json::from_str(&got).unwrap();
+    assert_eq!(got_json["missingValues"], serde_json::json!(["", "NA"]));
+    assert_eq!(got_json["required"], serde_json::json!([]));
+
+    // the NULL values are validated as empty values
+    let mut cmd = wrk.command("validate");
+    cmd.arg("codes.csv").arg("codes.csv.schema.json");
+    wrk.assert_success(&mut cmd);
+
+    // without the NULL values, the JSON Schema has no missingValues
+    let mut cmd = wrk.command("schema");
+    cmd.arg("codes.csv").arg("--force");
+    wrk.assert_success(&mut cmd);
+
+    let got: String = wrk.from_str(&wrk.path("codes.csv.schema.json"));
+    let got_json: Value = serde_json::from_str(&got).unwrap();
+    assert_eq!(got_json.get("missingValues"), None);
+}
+
+#[test]
+#[file_serial]
+fn generate_schema_format_frictionless_and_validate() {
+    let wrk = Workdir::new("generate_schema_format_frictionless_and_validate");
+    create_schema_format_csv(&wrk);
+
+    let mut cmd = wrk.command("schema");
+    cmd.arg("sales.csv")
+        .args(["--format", "frictionless"])
+        .args(["--enum-threshold", "3"]);
+    wrk.assert_success(&mut cmd);
+
+    let got: String = wrk.from_str(&wrk.path("sales.csv.tableschema.json"));
+    let got_json: Value = serde_json::from_str(&got).unwrap();
+    assert_eq!(got_json["missingValues"], serde_json::json!([""]));
+    assert_eq!(
+        got_json["fields"][0],
+        serde_json::json!({
+            "name": "id",
+            "type": "integer",
+            "description": "id column from sales.csv",
+            "constraints": {"required": true, "minimum": 1, "maximum": 3, "enum": [1, 2, 3]}
+        })
+    );
+    assert_eq!(got_json["fields"][3]["type"], "date");
+    assert_eq!(got_json["fields"][3]["format"], "any");
+    assert_eq!(
+        got_json["fields"][5]["constraints"],
+        serde_json::json!({"minLength": 0, "maxLength": 2, "enum": ["x", "yy"]})
+    );
+
+    // the generated Table Schema validates the data it was generated from
+    let mut cmd = wrk.command("validate");
+    cmd.arg("sales.csv").arg("sales.csv.tableschema.json");
+    wrk.assert_success(&mut cmd);
+}
+
+#[test]
+#[file_serial]
+fn generate_schema_format_avro_and_polars() {
+    let wrk = Workdir::new("generate_schema_format_avro_and_polars");
+    wrk.create(
+        "2024 sales.csv",
+        vec![
+            svec!["id", "unit price", "created_date"],
+            svec!["1", "3.5", "2024-01-15"],
+            svec!["2", "", "2024-02-01"],
+        ],
+    );
+
+    let mut cmd = wrk.command("schema");
+    cmd.arg("2024 sales.csv").args(["--format", "avro"]);
+    wrk.assert_success(&mut cmd);
+
+    let got: String = wrk.from_str(&wrk.path("2024 sales.csv.avsc"));
+    let got_json: Value = serde_json::from_str(&got).unwrap();
+    assert_eq!(got_json["type"], "record");
+    assert_eq!(got_json["name"], "_2024_sales");
+    assert_eq!(
+        got_json["fields"],
+        serde_json::json!([
+            {"name": "id", "doc": "id column from 2024 sales.csv", "type": "int"},
+            {"name": "unit_price", "doc": "unit price column from 2024 sales.csv",
+             "type": ["null", "double"], "default": null},
+            {"name": "created_date", "doc": "created_date column from 2024 sales.csv",
+             "type": {"type": "int", "logicalType": "date"}},
+        ])
+    );
+
+    let mut cmd = wrk.command("schema");
+    cmd.arg("2024 sales.csv")
+        .args(["--format", "polars"])
+        .arg("--strict-dates");
+    wrk.assert_success(&mut cmd);
+
+    let got: String = wrk.from_str(&wrk.path("2024 sales.csv.pschema.json"));
+    let got_json: Value = serde_json::from_str(&got).unwrap();
+    assert_eq!(
+        got_json,
+        serde_json::json!({"fields": {
+            "id": "Int64",
+            "unit price": "Float64",
+            "created_date": "Date"
+        }})
+    );
+}
+
+#[test]
+fn generate_schema_invalid_format() {
+    let wrk = Workdir::new("generate_schema_invalid_format");
+    create_schema_format_csv(&wrk);
+
+    let mut cmd = wrk.command("schema");
+    cmd.arg("sales.csv").args(["--format", "xml"]);
+    wrk.assert_err(&mut cmd);
+}
+
+fn create_monthly_csvs(wrk: &Workdir) {
+    wrk.create(
+        "jan.csv",
+        vec![
+            svec!["id", "region", "amount"],
+            svec!["1", "north", "10"],
+            svec!["2", "south", "20"],
+        ],
+    );
+    wrk.create(
+        "feb.csv",
+        vec![
+            svec!["id", "region", "amount", "note"],
+            svec!["3", "east", "15.5", "x"],
+            svec!["4", "north", "5", ""],
+        ],
+    );
+}
+
+#[test]
+#[file_serial]
+fn generate_schema_merge_multiple_inputs() {
+    let wrk = Workdir::new("generate_schema_merge_multiple_inputs");
+    create_monthly_csvs(&wrk);
+
+    let mut cmd = wrk.command("schema");
+    cmd.arg("jan.csv").arg("feb.csv");
+    wrk.assert_success(&mut cmd);
+
+    let got: String = wrk.from_str(&wrk.path("jan.csv.schema.json"));
+    let got_json: Value = serde_json::from_str(&got).unwrap();
+    assert_eq!(got_json["title"], "JSON Schema for jan.csv, feb.csv");
+    assert_json_eq!(
+        got_json["properties"]["id"],
+        serde_json::json!({
+            "description": "id column from jan.csv",
+            "type": ["integer"],
+            "minimum": 1,
+            "maximum": 4,
+            "enum": [1, 2, 3, 4]
+        })
+    );
+    assert_json_eq!(
+        got_json["properties"]["amount"],
+        serde_json::json!({
+            "description": "amount column from jan.csv",
+            "type": ["number"],
+            "minimum": 5.0,
+            "maximum": 20
+        })
+    );
+    assert_eq!(
+        got_json["properties"]["region"]["enum"],
+        serde_json::json!(["north", "south", "east"])
+    );
+    assert_eq!(got_json["properties"]["region"]["minLength"], 4);
+    // note is not in jan.csv, so it's not required
+    assert_eq!(
+        got_json["required"],
+        serde_json::json!(["id", "region", "amount"])
+    );
+
+    let got_changes: String = wrk.from_str(&wrk.path("jan.csv.schema-changes.tsv"));
+    let expected_changes = r#"source	field	keyword	from	to
+feb.csv	id	maximum	2	4
+feb.csv	id	enum	[1,2]	[1,2,3,4]
+feb.csv	region	minLength	5	4
+feb.csv	region	enum	["north","south"]	["north","south","east"]
+feb.csv	amount	type	["integer"]	["number"]
+feb.csv	amount	minimum	10	5.0
+feb.csv	amount	enum	[10,20]	
+feb.csv	note	column		added
+"#;
+    assert_eq!(got_changes, expected_changes);
+
+    // the merged schema validates both inputs
+    for input in ["jan.csv", "feb.csv"] {
+        let mut cmd = wrk.command("validate");
+        cmd.arg(input).arg("jan.csv.schema.json");
+        wrk.assert_success(&mut cmd);
+    }
+}
+
+#[test]
+#[file_serial]
+fn generate_schema_merge_base_scalar_types() {
+    let wrk = Workdir::new("generate_schema_merge_base_scalar_types");
+    create_monthly_csvs(&wrk);
+    wrk.create_from_string(
+        "sales.schema.json",
+        r#"{
+  "$schema": "https://json-schema.org/draft-07/schema",
+  "title": "Monthly sales",
+  "type": "object",
+  "properties": {
+    "id": {"type": "integer", "minimum": 1, "maximum": 100},
+    "region": {"type": "string", "enum": ["north", "south", "east", "west"]},
+    "amount": {"type": ["null", "number"], "minimum": 0, "maximum": 1000}
+  },
+  "required": ["id", "region", "amount"]
+}"#,
+    );
+
+    let mut cmd = wrk.command("schema");
+    cmd.arg("jan.csv").args(["--base", "sales.schema.json"]);
+    wrk.assert_success(&mut cmd);
+
+    // jan.csv conforms to the base schema, so its types are kept as they are
+    let got: String = wrk.from_str(&wrk.path("jan.csv.schema.json"));
+    let got_json: Value = serde_json::from_str(&got).unwrap();
+    assert_eq!(got_json["properties"]["id"]["type"], "integer");
+    assert_eq!(got_json["properties"]["region"]["type"], "string");
+    assert_eq!(
+        got_json["properties"]["amount"]["type"],
+        serde_json::json!(["null", "number"])
+    );
+
+    let got_err = wrk.output_stderr(&mut cmd);
+    assert!(got_err.contains("No schema changes - all inputs conform to the schema."));
+    assert!(!Path::new(&wrk.path("jan.csv.schema-changes.tsv")).exists());
+}
+
+#[test]
+#[file_serial]
+fn generate_schema_merge_base() {
+    let wrk = Workdir::new("generate_schema_merge_base");
+    create_monthly_csvs(&wrk);
+    wrk.create_from_string(
+        "sales.schema.json",
+        r#"{
+  "$schema": "https://json-schema.org/draft-07/schema",
+  "title": "Monthly sales",
+  "type": "object",
+  "properties": {
+    "id": {"type": ["integer"], "minimum": 1, "maximum": 100},
+    "region": {"type": ["string"], "enum": ["north", "south", "east", "west"]},
+    "amount": {"type": ["number"], "minimum": 0, "maximum": 1000, "currency": false}
+  },
+  "required": ["id", "region", "amount"]
+}"#,
+    );
+
+    let mut cmd = wrk.command("schema");
+    cmd.arg("feb.csv")
+        .args(["--base", "sales.schema.json"])
+        .arg("--stdout");
+    let got: String = wrk.stdout(&mut cmd);
+    let got_json: Value = serde_json::from_str(&got).unwrap();
+
+    // feb.csv is within the base schema's ranges & enums, and the custom keywords are kept
+    assert_eq!(got_json["title"], "Monthly sales");
+    assert_json_eq!(
+        got_json["properties"]["amount"],
+        serde_json::json!({"type": ["number"], "minimum": 0, "maximum": 1000, "currency": false})
+    );
+    assert_json_eq!(
+        got_json["properties"]["region"],
+        serde_json::json!({"type": ["string"], "enum": ["north", "south", "east", "west"]})
+    );
+    assert_eq!(
+        got_json["properties"]["note"]["type"],
+        serde_json::json!(["string", "null"])
+    );
+    assert_eq!(
+        got_json["required"],
+        serde_json::json!(["id", "region", "amount"])
+    );
+    // with --stdout, the change log is only sent to stderr
+    assert!(!Path::new(&wrk.path("feb.csv.schema-changes.tsv")).exists());
+
+    let mut cmd = wrk.command("schema");
+    cmd.arg("feb.csv")
+        .args(["--base", "sales.schema.json"])
+        .args(["--format", "postgres"]);
+    wrk.assert_err(&mut cmd);
+}
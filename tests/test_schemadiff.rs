@@ -0,0 +1,164 @@
+use crate::workdir::Workdir;
+
+fn create_stats_files(wrk: &Workdir) {
+    wrk.create(
+        "baseline.stats.csv",
+        vec![
+            svec![
+                "field",
+                "type",
+                "sparsity",
+                "cardinality",
+                "mean",
+                "q1",
+                "q2_median",
+                "q3"
+            ],
+            svec!["id", "Integer", "0", "100", "50.5", "25", "50", "75"],
+            svec!["amount", "Float", "0", "80", "10", "5", "9", "15"],
+            svec!["customer_name", "String", "0", "60", "", "", "", ""],
+            svec!["region", "String", "0", "4", "", "", "", ""],
+            svec!["legacy_flag", "String", "0.5", "2", "", "", "", ""],
+        ],
+    );
+    wrk.create(
+        "current.stats.csv",
+        vec![
+            svec![
+                "field",
+                "type",
+                "sparsity",
+                "cardinality",
+                "mean",
+                "q1",
+                "q2_median",
+                "q3"
+            ],
+            svec!["id", "Integer", "0", "130", "53", "26", "51", "79"],
+            svec!["amount", "Float", "0", "85", "12", "5.2", "9", "18"],
+            svec!["customer_nm", "String", "0", "62", "", "", "", ""],
+            svec!["region", "Integer", "0.1", "4", "2.5", "1", "2", "3"],
+            svec!["discount", "Float", "0.2", "10", "0.1", "0", "0.1", "0.15"],
+        ],
+    );
+}
+
+#[test]
+#[cfg(feature = "fuzzy")]
+fn schemadiff_stats_files() {
+    let wrk = Workdir::new("schemadiff_stats_files");
+    create_stats_files(&wrk);
+
+    let mut cmd = wrk.command("schemadiff");
+    cmd.arg("baseline.stats.csv").arg("current.stats.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["field", "change", "baseline", "current", "delta"],
+        svec!["id", "cardinality", "100", "130", "0.3"],
+        svec!["amount", "mean", "10", "12", "0.2"],
+        svec!["amount", "q3", "15", "18", "0.2"],
+        svec![
+            "customer_name",
+            "renamed",
+            "customer_name",
+            "customer_nm",
+            "0.8462"
+        ],
+        svec!["region", "type", "String", "Integer", ""],
+        svec!["region", "null_rate", "0", "0.1", "0.1"],
+        svec!["legacy_flag", "removed", "String", "", ""],
+        svec!["discount", "added", "", "Float", ""],
+    ];
+    assert_eq!(got, expected);
+    wrk.assert_err(&mut cmd);
+}
+
+#[test]
+fn schemadiff_thresholds() {
+    let wrk = Workdir::new("schemadiff_thresholds");
+    create_stats_files(&wrk);
+
+    let mut cmd = wrk.command("schemadiff");
+    cmd.arg("baseline.stats.csv")
+        .arg("current.stats.csv")
+        .args(["--cardinality-threshold", "0.5"])
+        .args(["--drift-threshold", "0.25"])
+        .args(["--null-threshold", "0.1"])
+        .args(["--rename-threshold", "0.9"]);
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["field", "change", "baseline", "current", "delta"],
+        svec!["customer_name", "removed", "String", "", ""],
+        svec!["region", "type", "String", "Integer", ""],
+        svec!["legacy_flag", "removed", "String", "", ""],
+        svec!["customer_nm", "added", "", "String", ""],
+        svec!["discount", "added", "", "Float", ""],
+    ];
+    assert_eq!(got, expected);
+    wrk.assert_err(&mut cmd);
+}
+
+#[test]
+fn schemadiff_no_differences() {
+    let wrk = Workdir::new("schemadiff_no_differences");
+    create_stats_files(&wrk);
+
+    let mut cmd = wrk.command("schemadiff");
+    cmd.arg("baseline.stats.csv").arg("baseline.stats.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![svec!["field", "change", "baseline", "current", "delta"]];
+    assert_eq!(got, expected);
+    wrk.assert_success(&mut cmd);
+}
+
+#[test]
+fn schemadiff_csv_files() {
+    let wrk = Workdir::new("schemadiff_csv_files");
+    wrk.create(
+        "jan.csv",
+        vec![
+            svec!["id", "region", "amount"],
+            svec!["1", "north", "10"],
+            svec!["2", "south", "20"],
+            svec!["3", "east", "30"],
+            svec!["4", "west", "40"],
+        ],
+    );
+    wrk.create(
+        "feb.csv",
+        vec![
+            svec!["id", "region", "amount", "note"],
+            svec!["1", "north", "10", "x"],
+            svec!["2", "south", "20", "y"],
+            svec!["3", "east", "30", "z"],
+            svec!["4", "west", "40", "w"],
+        ],
+    );
+
+    let mut cmd = wrk.command("schemadiff");
+    cmd.arg("jan.csv").arg("feb.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["field", "change", "baseline", "current", "delta"],
+        svec!["note", "added", "", "String", ""],
+    ];
+    assert_eq!(got, expected);
+    wrk.assert_err(&mut cmd);
+}
+
+#[test]
+fn schemadiff_not_a_stats_file() {
+    let wrk = Workdir::new("schemadiff_not_a_stats_file");
+    create_stats_files(&wrk);
+    wrk.create("bad.stats.csv", vec![svec!["a", "b"], svec!["1", "2"]]);
+
+    let mut cmd = wrk.command("schemadiff");
+    cmd.arg("baseline.stats.csv").arg("bad.stats.csv");
+
+    let got = wrk.output_stderr(&mut cmd);
+    assert!(got.contains("bad.stats.csv is not a stats file"));
+}
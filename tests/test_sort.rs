@@ -516,6 +516,117 @@ fn sort_random_secure() {
     assert_eq!(got, expected);
 }
 
+#[test]
+fn sort_keys_multi() {
+    let wrk = Workdir::new("sort_keys_multi");
+    wrk.create(
+        "in.csv",
+        vec![
+            svec!["date", "file", "amount"],
+            svec!["2024-01-02", "file10", "5"],
+            svec!["2024-01-02", "file2", "7"],
+            svec!["2023-12-31", "file1", ""],
+            svec!["2024-01-02", "file2", "12"],
+            svec!["", "file3", "1"],
+            svec!["2023-12-31", "file1", "3"],
+        ],
+    );
+
+    let mut cmd = wrk.command("sort");
+    cmd.args([
+        "--keys",
+        "date:date:desc,file:natural:asc,amount:num:desc:nullsfirst",
+    ])
+    .arg("in.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["date", "file", "amount"],
+        svec!["2024-01-02", "file2", "12"],
+        svec!["2024-01-02", "file2", "7"],
+        svec!["2024-01-02", "file10", "5"],
+        svec!["2023-12-31", "file1", ""],
+        svec!["2023-12-31", "file1", "3"],
+        svec!["", "file3", "1"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn sort_keys_natural_version() {
+    let wrk = Workdir::new("sort_keys_natural_version");
+    wrk.create(
+        "in.csv",
+        vec![
+            svec!["version"],
+            svec!["1.10.0"],
+            svec!["1.9.2"],
+            svec!["1.9.10"],
+            svec!["1.09.3"],
+            svec!["2.0"],
+        ],
+    );
+
+    let mut cmd = wrk.command("sort");
+    cmd.args(["-k", "version:natural"]).arg("in.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["version"],
+        svec!["1.9.2"],
+        svec!["1.09.3"],
+        svec!["1.9.10"],
+        svec!["1.10.0"],
+        svec!["2.0"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn sort_keys_num_invalid_nulls() {
+    let wrk = Workdir::new("sort_keys_num_invalid_nulls");
+    wrk.create(
+        "in.csv",
+        vec![
+            svec!["n"],
+            svec!["n/a"],
+            svec!["10"],
+            svec![""],
+            svec!["-2.5"],
+            svec!["9"],
+        ],
+    );
+
+    // unparseable values sort after the numbers, empty values are last by default
+    let mut cmd = wrk.command("sort");
+    cmd.args(["--keys", "1:num"]).arg("in.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["n"],
+        svec!["-2.5"],
+        svec!["9"],
+        svec!["10"],
+        svec!["n/a"],
+        svec![""],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn sort_keys_conflicts() {
+    let wrk = Workdir::new("sort_keys_conflicts");
+    wrk.create("in.csv", vec![svec!["n"], svec!["1"]]);
+
+    let mut cmd = wrk.command("sort");
+    cmd.args(["--keys", "n:num"]).arg("--reverse").arg("in.csv");
+    wrk.assert_err(&mut cmd);
+
+    let mut cmd = wrk.command("sort");
+    cmd.args(["--keys", "nope:num"]).arg("in.csv");
+    wrk.assert_err(&mut cmd);
+}
+
 /// Order `a` and `b` lexicographically using `Ord`
 pub fn iter_cmp<A, L, R>(mut a: L, mut b: R) -> cmp::Ordering
 where
@@ -226,3 +226,28 @@ fn sortcheck_simple_all_json_progressbar() {
     );
     wrk.assert_err(&mut cmd);
 }
+
+#[test]
+fn sortcheck_keys() {
+    let wrk = Workdir::new("sortcheck_keys");
+    wrk.create(
+        "in.csv",
+        vec![
+            svec!["region", "amount"],
+            svec!["file2", "30"],
+            svec!["file2", "4"],
+            svec!["file10", "100"],
+        ],
+    );
+
+    // not sorted lexicographically...
+    let mut cmd = wrk.command("sortcheck");
+    cmd.arg("in.csv");
+    wrk.assert_err(&mut cmd);
+
+    // ...but sorted naturally by region, then numerically descending by amount
+    let mut cmd = wrk.command("sortcheck");
+    cmd.args(["--keys", "region:natural,amount:num:desc"])
+        .arg("in.csv");
+    wrk.assert_success(&mut cmd);
+}
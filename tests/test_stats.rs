@@ -821,6 +821,36 @@ fn stats_with_date_type() {
     assert_eq!(dos2unix(&got2), dos2unix(&expected2).trim_end());
 }
 
+#[test]
+fn stats_null_values() {
+    let wrk = Workdir::new("stats_null_values");
+    wrk.create(
+        "in.csv",
+        vec![
+            svec!["n"],
+            svec!["1"],
+            svec!["NA"],
+            svec!["3"],
+            svec!["-999"],
+            svec![""],
+        ],
+    );
+
+    let mut cmd = wrk.command("stats");
+    cmd.args(["--null-values", "NA,-999"]).arg("in.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let stat = |name: &str| {
+        let idx = got[0].iter().position(|h| h == name).unwrap();
+        got[1][idx].clone()
+    };
+    assert_eq!(stat("type"), "Integer");
+    assert_eq!(stat("min"), "1");
+    assert_eq!(stat("max"), "3");
+    assert_eq!(stat("nullcount"), "3");
+    assert_eq!(stat("sparsity"), "0.6");
+}
+
 #[test]
 fn stats_typesonly() {
     let wrk = Workdir::new("stats_typesonly");
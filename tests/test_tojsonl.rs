@@ -27,6 +27,30 @@ fn tojsonl_simple() {
     assert_eq!(got, expected);
 }
 
+#[test]
+#[serial]
+fn tojsonl_null_values() {
+    let wrk = Workdir::new("tojsonl_null_values");
+    wrk.create(
+        "in.csv",
+        vec![
+            svec!["id", "name", "weight"],
+            svec!["1", "Mark", "150.2"],
+            svec!["2", "NULL", "NA"],
+            svec!["3", "Bob", "199.5"],
+        ],
+    );
+
+    let mut cmd = wrk.command("tojsonl");
+    cmd.args(["--null-values", "NA,NULL"]).arg("in.csv");
+
+    let got: String = wrk.stdout(&mut cmd);
+    let expected = r#"{"id":1,"name":"Mark","weight":150.2}
+{"id":2,"name":null,"weight":null}
+{"id":3,"name":"Bob","weight":199.5}"#;
+    assert_eq!(got, expected);
+}
+
 #[test]
 #[serial]
 fn tojsonl_boolean() {
@@ -326,3 +326,568 @@ fn validate_adur_public_toilets_dataset_with_json_schema_url() {
     assert_eq!(adur_errors(), validation_error_output);
     wrk.assert_err(&mut cmd);
 }
+
+#[test]
+fn validate_custom_keywords() {
+    let wrk = Workdir::new("validate_custom_keywords").flexible(true);
+    wrk.create(
+        "countries.csv",
+        vec![
+            svec!["name", "iso_code"],
+            svec!["Philippines", "PH"],
+            svec!["United States", "US"],
+        ],
+    );
+    wrk.create(
+        "data.csv",
+        vec![
+            svec!["country", "code", "sold_on"],
+            svec!["Philippines", "PH", "2024-03-15"],
+            svec!["Atlantis", "US", ""],
+            svec!["United States", "XX", "someday"],
+        ],
+    );
+    wrk.create_from_string(
+        "schema.json",
+        r#"{
+    "$schema": "https://json-schema.org/draft/2020-12/schema",
+    "type": "object",
+    "properties": {
+        "country": { "type": "string", "dynamicEnum": "countries.csv" },
+        "code": { "type": "string", "dynamicEnum": { "file": "countries.csv", "column": "iso_code" } },
+        "sold_on": { "type": ["string", "null"], "dateFormat": "dmy" }
+    }
+}"#,
+    );
+    let mut cmd = wrk.command("validate");
+    cmd.arg("data.csv").arg("schema.json");
+
+    wrk.assert_err(&mut cmd);
+
+    let validation_error_output: String = wrk.from_str(&wrk.path("data.csv.validation-errors.tsv"));
+    assert_eq!(
+        validation_error_output,
+        "row_number\tfield\terror\n2\tcountry\t\"Atlantis\" is not one of the values in \
+         countries.csv\n3\tcode\t\"XX\" is not one of the values in \
+         countries.csv\n3\tsold_on\t\"someday\" is not a date in a recognized format\n"
+    );
+
+    let valid_output: String = wrk.from_str(&wrk.path("data.csv.valid"));
+    assert_eq!(
+        valid_output,
+        "country,code,sold_on\nPhilippines,PH,2024-03-15\n"
+    );
+}
+
+fn create_currency_files(wrk: &Workdir) {
+    wrk.create(
+        "data.csv",
+        vec![
+            svec!["price"],
+            svec!["$1,234.56"],
+            svec!["12.5"],
+            svec!["a lot"],
+        ],
+    );
+    wrk.create_from_string(
+        "schema.json",
+        r#"{
+    "type": "object",
+    "properties": {
+        "price": { "type": "string", "currency": true }
+    }
+}"#,
+    );
+}
+
+#[test]
+#[cfg(feature = "apply")]
+fn validate_currency_keyword() {
+    let wrk = Workdir::new("validate_currency_keyword").flexible(true);
+    create_currency_files(&wrk);
+    let mut cmd = wrk.command("validate");
+    cmd.arg("data.csv").arg("schema.json");
+
+    wrk.assert_err(&mut cmd);
+
+    let validation_error_output: String = wrk.from_str(&wrk.path("data.csv.validation-errors.tsv"));
+    assert_eq!(
+        validation_error_output,
+        "row_number\tfield\terror\n3\tprice\t\"a lot\" is not a valid currency amount\n"
+    );
+}
+
+#[test]
+#[cfg(not(feature = "apply"))]
+fn validate_currency_keyword_requires_apply() {
+    let wrk = Workdir::new("validate_currency_keyword_requires_apply").flexible(true);
+    create_currency_files(&wrk);
+    let mut cmd = wrk.command("validate");
+    cmd.arg("data.csv").arg("schema.json");
+
+    wrk.assert_err(&mut cmd);
+    let got_err = wrk.output_stderr(&mut cmd);
+    assert!(got_err.contains("the currency keyword requires the apply feature"));
+}
+
+#[test]
+fn validate_dynamic_enum_missing_column() {
+    let wrk = Workdir::new("validate_dynamic_enum_missing_column").flexible(true);
+    wrk.create("codes.csv", vec![svec!["code"], svec!["A"]]);
+    wrk.create("data.csv", vec![svec!["code"], svec!["A"]]);
+    wrk.create_from_string(
+        "schema.json",
+        r#"{
+    "type": "object",
+    "properties": {
+        "code": { "type": "string", "dynamicEnum": { "file": "codes.csv", "column": "id" } }
+    }
+}"#,
+    );
+    let mut cmd = wrk.command("validate");
+    cmd.arg("data.csv").arg("schema.json");
+
+    wrk.assert_err(&mut cmd);
+    let got_stderr = wrk.output_stderr(&mut cmd);
+    assert!(got_stderr.contains(
+        "Cannot compile schema json. error: Cannot load the dynamicEnum values from codes.csv: \
+         column \"id\" not found"
+    ));
+}
+
+#[test]
+fn validate_table_constraints() {
+    let wrk = Workdir::new("validate_table_constraints").flexible(true);
+    wrk.create(
+        "countries.csv",
+        vec![
+            svec!["iso_code", "name"],
+            svec!["PH", "Philippines"],
+            svec!["US", "United States"],
+        ],
+    );
+    wrk.create(
+        "data.csv",
+        vec![
+            svec!["region", "id", "email", "country"],
+            svec!["north", "1", "a@example.com", "PH"],
+            svec!["south", "1", "b@example.com", "US"],
+            svec!["north", "1", "a@example.com", "XX"],
+            svec!["north", "", "", ""],
+        ],
+    );
+    wrk.create_from_string(
+        "schema.json",
+        r#"{
+    "$schema": "https://json-schema.org/draft/2020-12/schema",
+    "type": "object",
+    "primaryKey": ["region", "id"],
+    "foreignKeys": [
+        { "fields": "country", "reference": { "resource": "countries.csv", "fields": "iso_code" } }
+    ],
+    "properties": {
+        "region": { "type": "string" },
+        "id": { "type": ["integer", "null"] },
+        "email": { "type": ["string", "null"], "unique": true },
+        "country": { "type": ["string", "null"] }
+    }
+}"#,
+    );
+    let mut cmd = wrk.command("validate");
+    cmd.arg("data.csv").arg("schema.json");
+
+    wrk.assert_err(&mut cmd);
+
+    let validation_error_output: String = wrk.from_str(&wrk.path("data.csv.validation-errors.tsv"));
+    assert_eq!(
+        validation_error_output,
+        "row_number\tfield\terror\n3\tregion,id\tprimary key \"north,1\" is a duplicate of row \
+         1\n3\temail\tunique value \"a@example.com\" is a duplicate of row 1\n3\tcountry\tforeign \
+         key \"XX\" is not in the iso_code column/s of countries.csv\n4\tregion,id\tprimary key \
+         column is empty\n"
+    );
+
+    let valid_output: String = wrk.from_str(&wrk.path("data.csv.valid"));
+    assert_eq!(
+        valid_output,
+        "region,id,email,country\nnorth,1,a@example.com,PH\nsouth,1,b@example.com,US\n"
+    );
+}
+
+#[test]
+fn validate_foreign_key_unknown_column() {
+    let wrk = Workdir::new("validate_foreign_key_unknown_column").flexible(true);
+    wrk.create("countries.csv", vec![svec!["iso_code"], svec!["PH"]]);
+    wrk.create("data.csv", vec![svec!["country"], svec!["PH"]]);
+    wrk.create_from_string(
+        "schema.json",
+        r#"{
+    "type": "object",
+    "foreignKeys": [
+        { "fields": "country", "reference": { "resource": "countries.csv", "fields": "code" } }
+    ],
+    "properties": { "country": { "type": "string" } }
+}"#,
+    );
+    let mut cmd = wrk.command("validate");
+    cmd.arg("data.csv").arg("schema.json");
+
+    wrk.assert_err(&mut cmd);
+    let got_stderr = wrk.output_stderr(&mut cmd);
+    assert_eq!(got_stderr, "countries.csv: column \"code\" not found.\n");
+}
+
+#[test]
+fn validate_frictionless_table_schema() {
+    let wrk = Workdir::new("validate_frictionless_table_schema").flexible(true);
+    wrk.create(
+        "data.csv",
+        vec![
+            svec!["id", "email", "joined", "active", "plan", "code", "age"],
+            svec![
+                "1",
+                "a@example.com",
+                "2024-01-31",
+                "true",
+                "basic",
+                "AB-1",
+                "30"
+            ],
+            svec!["2", "NA", "01/31/2024", "yes", "gold", "ab-1", "NA"],
+            svec![
+                "3",
+                "c@example",
+                "2024-02-01",
+                "FALSE",
+                "pro",
+                "CD-22",
+                "130"
+            ],
+        ],
+    );
+    wrk.create_from_string(
+        "schema.json",
+        r#"{
+    "fields": [
+        { "name": "id", "type": "integer", "constraints": { "required": true } },
+        { "name": "email", "type": "string", "format": "email" },
+        { "name": "joined", "type": "date" },
+        { "name": "active", "type": "boolean" },
+        { "name": "plan", "type": "string", "constraints": { "enum": ["basic", "pro"] } },
+        { "name": "code", "type": "string", "constraints": { "pattern": "[A-Z]{2}-\\d+" } },
+        { "name": "age", "type": "integer", "constraints": { "minimum": 0, "maximum": "120" } }
+    ],
+    "primaryKey": "id",
+    "missingValues": ["", "NA"]
+}"#,
+    );
+    let mut cmd = wrk.command("validate");
+    cmd.arg("data.csv").arg("schema.json");
+
+    wrk.assert_err(&mut cmd);
+
+    let validation_error_output: String = wrk.from_str(&wrk.path("data.csv.validation-errors.tsv"));
+    assert_eq!(
+        validation_error_output,
+        r#"row_number	field	error
+2	joined	"01/31/2024" does not match "^\d{4}-\d{2}-\d{2}$"
+2	active	"yes" is not one of ["true","True","TRUE","1","false","False","FALSE","0",null]
+2	plan	"gold" is not one of ["basic","pro",null]
+2	code	"ab-1" does not match "^(?:[A-Z]{2}-\d+)$"
+3	email	"c@example" does not match "^[^@\s]+@[^@\s]+\.[^@\s]+$"
+3	age	130 is greater than the maximum of 120
+"#
+    );
+}
+
+fn create_datapackage(wrk: &Workdir) {
+    wrk.create(
+        "countries.csv",
+        vec![
+            svec!["code", "name"],
+            svec!["PH", "Philippines"],
+            svec!["US", "United States"],
+        ],
+    );
+    wrk.create_with_delim(
+        "cities.csv",
+        vec![
+            svec!["city", "country", "population"],
+            svec!["Manila", "PH", "1846513"],
+            svec!["Atlantis", "XX", "-1"],
+        ],
+        b';',
+    );
+    wrk.create_from_string(
+        "datapackage.json",
+        r#"{
+    "name": "places",
+    "resources": [
+        {
+            "name": "countries",
+            "path": "countries.csv",
+            "schema": {
+                "fields": [
+                    { "name": "code", "type": "string", "constraints": { "required": true } },
+                    { "name": "name", "type": "string" }
+                ],
+                "primaryKey": "code"
+            }
+        },
+        {
+            "name": "cities",
+            "path": "cities.csv",
+            "dialect": { "delimiter": ";" },
+            "schema": {
+                "fields": [
+                    { "name": "city", "type": "string" },
+                    { "name": "country", "type": "string" },
+                    { "name": "population", "type": "integer", "constraints": { "minimum": 0 } }
+                ],
+                "foreignKeys": [
+                    { "fields": "country", "reference": { "resource": "countries", "fields": "code" } }
+                ]
+            }
+        }
+    ]
+}"#,
+    );
+}
+
+#[test]
+fn validate_datapackage() {
+    let wrk = Workdir::new("validate_datapackage").flexible(true);
+    create_datapackage(&wrk);
+
+    let mut cmd = wrk.command("validate");
+    cmd.arg("datapackage.json");
+
+    wrk.assert_err(&mut cmd);
+    let got_stderr = wrk.output_stderr(&mut cmd);
+    assert_eq!(
+        got_stderr,
+        "Validating resource \"countries\" (countries.csv)...\nAll 2 records valid.\nValidating \
+         resource \"cities\" (cities.csv)...\n1 out of 2 records invalid.\n1 out of 2 resources \
+         invalid.\n"
+    );
+
+    let validation_error_output: String =
+        wrk.from_str(&wrk.path("cities.csv.validation-errors.tsv"));
+    assert_eq!(
+        validation_error_output,
+        "row_number\tfield\terror\n2\tpopulation\t-1 is less than the minimum of \
+         0\n2\tcountry\tforeign key \"XX\" is not in the code column/s of countries.csv\n"
+    );
+    assert!(!wrk.path("countries.csv.validation-errors.tsv").exists());
+}
+
+#[test]
+fn validate_csv_with_datapackage_schema() {
+    let wrk = Workdir::new("validate_csv_with_datapackage_schema").flexible(true);
+    create_datapackage(&wrk);
+
+    let mut cmd = wrk.command("validate");
+    cmd.arg("countries.csv").arg("datapackage.json");
+
+    wrk.assert_success(&mut cmd);
+
+    let mut cmd = wrk.command("validate");
+    cmd.arg("rivers.csv").arg("datapackage.json");
+
+    wrk.assert_err(&mut cmd);
+    let got_stderr = wrk.output_stderr(&mut cmd);
+    assert!(
+        got_stderr.contains("No resource in the Data Package datapackage.json matches rivers.csv.")
+    );
+}
+
+#[test]
+fn validate_rules() {
+    let wrk = Workdir::new("validate_rules").flexible(true);
+    wrk.create(
+        "data.csv",
+        vec![
+            svec![
+                "start date",
+                "end_date",
+                "status",
+                "closed_at",
+                "subtotal",
+                "tax",
+                "total"
+            ],
+            svec![
+                "2024-01-01",
+                "2024-02-01",
+                "closed",
+                "2024-02-01",
+                "10.10",
+                "2.02",
+                "12.12"
+            ],
+            svec!["2024-03-01", "2024-02-01", "closed", "", "10", "2", "13"],
+            svec!["2024-03-01", "", "open", "", "10", "2", "12"],
+        ],
+    );
+    wrk.create_from_string(
+        "schema.json",
+        r#"{
+    "type": "object",
+    "properties": {
+        "start date": { "type": "string", "format": "date" },
+        "end_date": { "type": ["string", "null"], "format": "date" },
+        "status": { "type": "string" },
+        "closed_at": { "type": ["string", "null"] },
+        "subtotal": { "type": "number" },
+        "tax": { "type": "number" },
+        "total": { "type": "number" }
+    }
+}"#,
+    );
+    wrk.create_from_string(
+        "rules.txt",
+        r#"# cross-field rules
+end_after_start: end_date >= start_date
+closed_at_required: status != "closed" || typeof(closed_at) != "empty"
+
+total_adds_up: math::abs(total - (subtotal + tax)) < 0.005
+"#,
+    );
+    let mut cmd = wrk.command("validate");
+    cmd.arg("data.csv")
+        .arg("schema.json")
+        .args(["--rules", "rules.txt"]);
+
+    wrk.assert_err(&mut cmd);
+
+    let validation_error_output: String = wrk.from_str(&wrk.path("data.csv.validation-errors.tsv"));
+    assert_eq!(
+        validation_error_output,
+        "row_number\tfield\terror\n2\tstart date,end_date\tfailed rule \"end_after_start\": \
+         end_date >= start_date\n2\tstatus,closed_at\tfailed rule \"closed_at_required\": status \
+         != \"closed\" || typeof(closed_at) != \"empty\"\n2\tsubtotal,tax,total\tfailed rule \
+         \"total_adds_up\": math::abs(total - (subtotal + tax)) < 0.005\n"
+    );
+}
+
+#[test]
+fn validate_rules_without_schema_with_stats_cache() {
+    let wrk = Workdir::new("validate_rules_without_schema_with_stats_cache").flexible(true);
+    wrk.create(
+        "data.csv",
+        vec![svec!["min", "max"], svec!["9", "10"], svec!["10", "9"]],
+    );
+    wrk.create_from_string("rules.txt", "min_below_max: min < max\n");
+
+    // without a stats cache, the values are strings, so "9" < "10" is false
+    let mut cmd = wrk.command("validate");
+    cmd.arg("data.csv").args(["--rules", "rules.txt"]);
+
+    wrk.assert_err(&mut cmd);
+    let validation_error_output: String = wrk.from_str(&wrk.path("data.csv.validation-errors.tsv"));
+    assert_eq!(
+        validation_error_output,
+        "row_number\tfield\terror\n1\tmin,max\tfailed rule \"min_below_max\": min < max\n"
+    );
+
+    // with a stats cache, the values are integers
+    wrk.create(
+        "data.stats.csv",
+        vec![
+            svec!["field", "type"],
+            svec!["min", "Integer"],
+            svec!["max", "Integer"],
+        ],
+    );
+    let mut cmd = wrk.command("validate");
+    cmd.arg("data.csv").args(["--rules", "rules.txt"]);
+
+    wrk.assert_err(&mut cmd);
+    let validation_error_output: String = wrk.from_str(&wrk.path("data.csv.validation-errors.tsv"));
+    assert_eq!(
+        validation_error_output,
+        "row_number\tfield\terror\n2\tmin,max\tfailed rule \"min_below_max\": min < max\n"
+    );
+}
+
+#[test]
+fn validate_rules_unknown_column() {
+    let wrk = Workdir::new("validate_rules_unknown_column").flexible(true);
+    wrk.create("data.csv", vec![svec!["a", "b"], svec!["1", "2"]]);
+    wrk.create_from_string("rules.txt", "a_below_c: a < c\n");
+
+    let mut cmd = wrk.command("validate");
+    cmd.arg("data.csv").args(["--rules", "rules.txt"]);
+
+    wrk.assert_err(&mut cmd);
+    let got_stderr = wrk.output_stderr(&mut cmd);
+    assert!(got_stderr.contains("Invalid rule \"a_below_c\": column \"c\" not found."));
+}
+
+fn create_malformed_csv(wrk: &Workdir) {
+    let data: &[u8] = b"a,b,c\n1,2,3\n4,5\n6,x\"y,7\n\"8\"9,10,11\n12,\xff\xfe,13\n\
+                        \"multi\nline\",14,15\n16,17,18,19\n";
+    std::fs::write(wrk.path("data.csv"), data).unwrap();
+}
+
+#[test]
+fn validate_exhaustive() {
+    let wrk = Workdir::new("validate_exhaustive").flexible(true);
+    create_malformed_csv(&wrk);
+
+    let mut cmd = wrk.command("validate");
+    cmd.arg("--exhaustive").arg("data.csv");
+
+    wrk.assert_err(&mut cmd);
+    let got: serde_json::Value = serde_json::from_str(&wrk.stdout::<String>(&mut cmd)).unwrap();
+    let expected = serde_json::json!({
+        "delimiter_char": ",",
+        "header_row": true,
+        "quote_char": "\"",
+        "num_records": 7,
+        "num_fields": 3,
+        "num_malformed": 5,
+        "errors": [
+            { "record": 2, "line": 3, "byte_offset": 12, "expected_fields": 3, "actual_fields": 2,
+              "bad_quotes": [], "invalid_utf8": [] },
+            { "record": 3, "line": 4, "byte_offset": 16, "expected_fields": 3, "actual_fields": 3,
+              "bad_quotes": [19], "invalid_utf8": [] },
+            { "record": 4, "line": 5, "byte_offset": 24, "expected_fields": 3, "actual_fields": 3,
+              "bad_quotes": [26], "invalid_utf8": [] },
+            { "record": 5, "line": 6, "byte_offset": 35, "expected_fields": 3, "actual_fields": 3,
+              "bad_quotes": [], "invalid_utf8": [[38, 40]] },
+            { "record": 7, "line": 9, "byte_offset": 63, "expected_fields": 3, "actual_fields": 4,
+              "bad_quotes": [], "invalid_utf8": [] }
+        ]
+    });
+    assert_eq!(got, expected);
+
+    let got_stderr = wrk.output_stderr(&mut cmd);
+    assert_eq!(got_stderr, "5 malformed record/s found.\n");
+}
+
+#[test]
+fn validate_exhaustive_repair() {
+    let wrk = Workdir::new("validate_exhaustive_repair").flexible(true);
+    create_malformed_csv(&wrk);
+
+    let mut cmd = wrk.command("validate");
+    cmd.args(["--repair", "repaired.csv"]).arg("data.csv");
+
+    wrk.assert_err(&mut cmd);
+    let repaired: String = wrk.from_str(&wrk.path("repaired.csv"));
+    assert_eq!(
+        repaired,
+        "a,b,c\n1,2,3\n4,5,\n6,\"x\"\"y\",7\n89,10,11\n12,\u{FFFD}\u{FFFD},13\n\"multi\nline\",14,\
+         15\n16,17,18\n"
+    );
+
+    // the repaired file is valid
+    let mut cmd = wrk.command("validate");
+    cmd.arg("--exhaustive").arg("repaired.csv");
+
+    wrk.assert_success(&mut cmd);
+    let got: serde_json::Value = serde_json::from_str(&wrk.stdout::<String>(&mut cmd)).unwrap();
+    assert_eq!(got["num_records"], 7);
+    assert_eq!(got["num_malformed"], 0);
+}
@@ -0,0 +1,207 @@
+use crate::workdir::Workdir;
+
+fn sales() -> Vec<Vec<String>> {
+    vec![
+        svec!["region", "day", "sales"],
+        svec!["east", "1", "10"],
+        svec!["east", "2", "20"],
+        svec!["east", "3", ""],
+        svec!["east", "4", "40"],
+        svec!["west", "1", "5"],
+        svec!["west", "2", "7"],
+        svec!["west", "3", "12"],
+    ]
+}
+
+#[test]
+fn window_row_number_cumsum() {
+    let wrk = Workdir::new("window_row_number_cumsum");
+    wrk.create("in.csv", sales());
+
+    let mut cmd = wrk.command("window");
+    cmd.args(["-p", "region"])
+        .arg("row_number,cumsum:sales,cumcount:sales")
+        .arg("in.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec![
+            "region",
+            "day",
+            "sales",
+            "row_number",
+            "sales_cumsum",
+            "sales_cumcount"
+        ],
+        svec!["east", "1", "10", "1", "10", "1"],
+        svec!["east", "2", "20", "2", "30", "2"],
+        svec!["east", "3", "", "3", "30", "2"],
+        svec!["east", "4", "40", "4", "70", "3"],
+        svec!["west", "1", "5", "1", "5", "1"],
+        svec!["west", "2", "7", "2", "12", "2"],
+        svec!["west", "3", "12", "3", "24", "3"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn window_no_partition() {
+    let wrk = Workdir::new("window_no_partition");
+    wrk.create("in.csv", sales());
+
+    let mut cmd = wrk.command("window");
+    cmd.arg("row_number,cummax:sales").arg("in.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["region", "day", "sales", "row_number", "sales_cummax"],
+        svec!["east", "1", "10", "1", "10"],
+        svec!["east", "2", "20", "2", "20"],
+        svec!["east", "3", "", "3", "20"],
+        svec!["east", "4", "40", "4", "40"],
+        svec!["west", "1", "5", "5", "40"],
+        svec!["west", "2", "7", "6", "40"],
+        svec!["west", "3", "12", "7", "40"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn window_lag_lead_diff() {
+    let wrk = Workdir::new("window_lag_lead_diff");
+    wrk.create("in.csv", sales());
+
+    let mut cmd = wrk.command("window");
+    cmd.args(["-p", "region"])
+        .arg("lag:sales,lead:sales:2,diff:sales")
+        .arg("in.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec![
+            "region",
+            "day",
+            "sales",
+            "sales_lag",
+            "sales_lead2",
+            "sales_diff"
+        ],
+        svec!["east", "1", "10", "", "", ""],
+        svec!["east", "2", "20", "10", "40", "10"],
+        svec!["east", "3", "", "20", "", ""],
+        svec!["east", "4", "40", "", "", ""],
+        svec!["west", "1", "5", "", "12", ""],
+        svec!["west", "2", "7", "5", "", "2"],
+        svec!["west", "3", "12", "7", "", "5"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn window_rolling() {
+    let wrk = Workdir::new("window_rolling");
+    wrk.create("in.csv", sales());
+
+    let mut cmd = wrk.command("window");
+    cmd.args(["-p", "region"])
+        .arg("rolling_mean:sales:2,rolling_median:sales:3,rolling_max:sales:3")
+        .arg("in.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec![
+            "region",
+            "day",
+            "sales",
+            "sales_rolling_mean2",
+            "sales_rolling_median3",
+            "sales_rolling_max3"
+        ],
+        svec!["east", "1", "10", "10", "10", "10"],
+        svec!["east", "2", "20", "15", "15", "20"],
+        svec!["east", "3", "", "20", "15", "20"],
+        svec!["east", "4", "40", "40", "30", "40"],
+        svec!["west", "1", "5", "5", "5", "5"],
+        svec!["west", "2", "7", "6", "6", "7"],
+        svec!["west", "3", "12", "9.5", "7", "12"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn window_rolling_min_periods() {
+    let wrk = Workdir::new("window_rolling_min_periods");
+    wrk.create("in.csv", sales());
+
+    let mut cmd = wrk.command("window");
+    cmd.args(["-p", "region"])
+        .args(["--min-periods", "2"])
+        .arg("rolling_sum:sales:2")
+        .arg("in.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["region", "day", "sales", "sales_rolling_sum2"],
+        svec!["east", "1", "10", ""],
+        svec!["east", "2", "20", "30"],
+        svec!["east", "3", "", ""],
+        svec!["east", "4", "40", ""],
+        svec!["west", "1", "5", ""],
+        svec!["west", "2", "7", "12"],
+        svec!["west", "3", "12", "19"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn window_order_by_unsorted() {
+    let wrk = Workdir::new("window_order_by_unsorted");
+    wrk.create(
+        "in.csv",
+        vec![
+            svec!["region", "day", "sales"],
+            svec!["east", "2", "20"],
+            svec!["east", "10", "10"],
+            svec!["west", "1", "5"],
+        ],
+    );
+
+    // lexicographically, "10" sorts before "2"
+    let mut cmd = wrk.command("window");
+    cmd.args(["-p", "region"])
+        .args(["--order-by", "day"])
+        .arg("row_number")
+        .arg("in.csv");
+    wrk.assert_err(&mut cmd);
+
+    // but numerically, the rows are sorted
+    let mut cmd = wrk.command("window");
+    cmd.args(["-p", "region"])
+        .args(["--order-by", "day"])
+        .arg("--numeric")
+        .arg("row_number")
+        .arg("in.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["region", "day", "sales", "row_number"],
+        svec!["east", "2", "20", "1"],
+        svec!["east", "10", "10", "2"],
+        svec!["west", "1", "5", "1"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn window_invalid_function() {
+    let wrk = Workdir::new("window_invalid_function");
+    wrk.create("in.csv", sales());
+
+    let mut cmd = wrk.command("window");
+    cmd.arg("rolling_mean:sales").arg("in.csv");
+    wrk.assert_err(&mut cmd);
+
+    let mut cmd = wrk.command("window");
+    cmd.arg("ntile:sales").arg("in.csv");
+    wrk.assert_err(&mut cmd);
+}
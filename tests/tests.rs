@@ -47,6 +47,8 @@ mod test_dedup;
 mod test_describegpt;
 mod test_diff;
 #[cfg(any(feature = "feature_capable", feature = "lite"))]
+mod test_doctor;
+#[cfg(any(feature = "feature_capable", feature = "lite"))]
 mod test_enumerate;
 mod test_excel;
 mod test_exclude;
@@ -68,6 +70,10 @@ mod test_fmt;
 #[cfg(all(feature = "foreach", target_family = "unix"))]
 mod test_foreach;
 mod test_frequency;
+#[cfg(all(feature = "fuzzy", feature = "feature_capable"))]
+mod test_fuzzydedup;
+#[cfg(all(feature = "fuzzy", feature = "feature_capable"))]
+mod test_fuzzyjoin;
 #[cfg(all(feature = "feature_capable", feature = "geocode"))]
 mod test_geocode;
 mod test_headers;
@@ -82,10 +88,18 @@ mod test_jsonl;
 #[cfg(feature = "luau")]
 mod test_luau;
 #[cfg(any(feature = "feature_capable", feature = "lite"))]
+mod test_mask;
+#[cfg(any(feature = "feature_capable", feature = "lite"))]
 mod test_partition;
+#[cfg(any(feature = "feature_capable", feature = "lite"))]
+mod test_patch;
+#[cfg(all(feature = "piiscan", feature = "feature_capable"))]
+mod test_piiscan;
 mod test_pseudo;
 #[cfg(feature = "python")]
 mod test_py;
+#[cfg(any(feature = "feature_capable", feature = "lite"))]
+mod test_rangejoin;
 mod test_rename;
 mod test_replace;
 mod test_reverse;
@@ -93,6 +107,8 @@ mod test_safenames;
 mod test_sample;
 #[cfg(any(feature = "feature_capable", feature = "lite"))]
 mod test_schema;
+#[cfg(any(feature = "feature_capable", feature = "lite"))]
+mod test_schemadiff;
 mod test_search;
 mod test_searchset;
 mod test_select;
@@ -115,6 +131,8 @@ mod test_tojsonl;
 #[cfg(any(feature = "feature_capable", feature = "lite"))]
 mod test_transpose;
 mod test_validate;
+#[cfg(any(feature = "feature_capable", feature = "lite"))]
+mod test_window;
 
 fn qcheck<T: Testable>(p: T) {
     env::set_var("QSV_SKIPUTF8_CHECK", "1");